@@ -6,25 +6,15 @@ pub fn gen() -> Result<()> {
 
     // Create plots
     let samples = 5000;
-    let aces_plot = {
+    let tonemapper_plots = Tonemapper::ALL.map(|tonemapper| {
         let mut points = vec![];
         for x in 0..=samples {
             let x = 10.0 * (x as f32) / (samples as f32);
-            let y = ColorRgb::new(x, x, x).tonemap().r();
+            let y = ColorRgb::new(x, x, x).tonemap(tonemapper).r();
             points.push((x, y));
         }
-        points
-    };
-
-    let linear_plot = {
-        let mut points = vec![];
-        for x in 0..=samples {
-            let x = 10.0 * (x as f32) / (samples as f32);
-            let y = x.min(1.0);
-            points.push((x, y));
-        }
-        points
-    };
+        (tonemapper, points)
+    });
 
     // Create svg file.
     let mut output_svg = String::new();
@@ -47,20 +37,29 @@ pub fn gen() -> Result<()> {
             .label_style((font, 18))
             .draw()?;
 
-        let linear_color = RGBColor(0, 0, 0);
-        let aces_color = RGBColor(255, 140, 0);
-
-        chart
-            .draw_series(LineSeries::new(linear_plot, linear_color))?
-            .label("Linear")
-            .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], linear_color));
-
-        chart
-            .draw_series(LineSeries::new(aces_plot, aces_color.stroke_width(2)))?
-            .label("ACES")
-            .legend(move |(x, y)| {
-                PathElement::new([(x, y), (x + 20, y)], aces_color.stroke_width(2))
-            });
+        // Clamp is the linear reference, the rest get distinct colors.
+        let colors = [
+            RGBColor(0, 0, 0),
+            RGBColor(31, 119, 180),
+            RGBColor(44, 160, 44),
+            RGBColor(255, 140, 0),
+            RGBColor(214, 39, 40),
+            RGBColor(148, 103, 189),
+            RGBColor(140, 86, 75),
+        ];
+        for ((tonemapper, points), color) in tonemapper_plots.into_iter().zip(colors) {
+            let width = if tonemapper == Tonemapper::Clamp {
+                1
+            } else {
+                2
+            };
+            chart
+                .draw_series(LineSeries::new(points, color.stroke_width(width)))?
+                .label(tonemapper.name())
+                .legend(move |(x, y)| {
+                    PathElement::new([(x, y), (x + 20, y)], color.stroke_width(width))
+                });
+        }
 
         chart
             .configure_series_labels()
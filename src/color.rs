@@ -60,6 +60,14 @@ impl ColorRgb {
         )
     }
 
+    pub fn mean(self) -> f32 {
+        (self.r() + self.g() + self.b()) / 3.0
+    }
+
+    pub fn max_element(self) -> f32 {
+        self.r().max(self.g()).max(self.b())
+    }
+
     pub fn luminance(self) -> f32 {
         // "3.2: Derivation of luminance signal"
         // https://www.itu.int/dms_pubrec/itu-r/rec/bt/R-REC-BT.709-6-201506-I!!PDF-E.pdf
@@ -67,17 +75,13 @@ impl ColorRgb {
         0.2126 * self.r() + 0.7152 * self.g() + 0.0722 * self.b()
     }
 
-    pub fn tonemap(self) -> Self {
-        // https://knarkowicz.wordpress.com/2016/01/06/aces-filmic-tone-mapping-curve/
-        let aces = |x: f32| {
-            let a = 2.51;
-            let b = 0.03;
-            let c = 2.43;
-            let d = 0.59;
-            let e = 0.14;
-            f32::clamp((x * (a * x + b)) / (x * (c * x + d) + e), 0.0, 1.0)
-        };
-        Self::new(aces(self.r()), aces(self.g()), aces(self.b()))
+    pub fn transform(self, matrix: &Mat3) -> Self {
+        let v = matrix * Vec3::new(self.r(), self.g(), self.b());
+        Self::new(v.x, v.y, v.z)
+    }
+
+    pub fn tonemap(self, tonemapper: Tonemapper) -> Self {
+        tonemapper.apply(self)
     }
 
     pub fn into_srgb8(self) -> [u8; 3] {
@@ -113,6 +117,14 @@ impl ops::AddAssign for ColorRgb {
     }
 }
 
+impl ops::Sub for ColorRgb {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.r() - rhs.r(), self.g() - rhs.g(), self.b() - rhs.b())
+    }
+}
+
 impl ops::Mul for ColorRgb {
     type Output = Self;
 
@@ -145,6 +157,14 @@ impl ops::MulAssign for ColorRgb {
     }
 }
 
+impl ops::MulAssign<f32> for ColorRgb {
+    fn mul_assign(&mut self, rhs: f32) {
+        *self.r_mut() *= rhs;
+        *self.g_mut() *= rhs;
+        *self.b_mut() *= rhs;
+    }
+}
+
 impl ops::Div<f32> for ColorRgb {
     type Output = Self;
 
@@ -211,3 +231,772 @@ impl fmt::Display for ColorRgba {
         }
     }
 }
+
+//
+// Color spaces
+//
+
+//
+// Sources:
+//
+// RGB/XYZ Matrices and Chromatic Adaptation
+// Bruce Lindbloom
+// http://www.brucelindbloom.com/index.html?Eqn_RGB_XYZ_Matrix.html
+//
+// Academy Color Encoding System (ACES) S-2014-004: ACEScg
+// AMPAS, 2014
+// https://docs.acescentral.com/specifications/acescg/
+//
+// ITU-R BT.2100: Image parameter values for high dynamic range television
+// ITU-R, 2018
+// https://www.itu.int/rec/R-REC-BT.2100
+//
+// ITU-R BT.2408: Guidance for operational practices in HDR television production
+// ITU-R, 2022
+// https://www.itu.int/pub/R-REP-BT.2408
+//
+// ITU-T H.273: Coding-independent code points for video signal type identification
+// ITU-T, 2021
+// https://www.itu.int/rec/T-REC-H.273
+//
+
+// Luminance of diffuse white in HDR encodings, in cd/m².
+const HDR_REFERENCE_WHITE: f32 = 203.0;
+
+// Peak luminance of PQ, in cd/m².
+const PQ_PEAK_LUMINANCE: f32 = 10000.0;
+
+// Linear RGB color spaces, all of which contain the sRGB gamut.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ColorSpace {
+    #[default]
+    Srgb,
+    DisplayP3,
+    Rec2020,
+    AcesCg,
+}
+
+impl ColorSpace {
+    pub const ALL: [Self; 4] = [Self::Srgb, Self::DisplayP3, Self::Rec2020, Self::AcesCg];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Srgb => "srgb",
+            Self::DisplayP3 => "display-p3",
+            Self::Rec2020 => "rec2020",
+            Self::AcesCg => "acescg",
+        }
+    }
+
+    // CIE 1931 xy of the red, green and blue primaries, and of the white.
+    pub fn chromaticities(self) -> [[f32; 2]; 4] {
+        const D65: [f32; 2] = [0.3127, 0.3290];
+        const D60: [f32; 2] = [0.32168, 0.33767];
+        match self {
+            Self::Srgb => [[0.64, 0.33], [0.30, 0.60], [0.15, 0.06], D65],
+            Self::DisplayP3 => [[0.680, 0.320], [0.265, 0.690], [0.150, 0.060], D65],
+            Self::Rec2020 => [[0.708, 0.292], [0.170, 0.797], [0.131, 0.046], D65],
+            Self::AcesCg => [[0.713, 0.293], [0.165, 0.830], [0.128, 0.044], D60],
+        }
+    }
+
+    // CIE XYZ of the white, with a luminance of one.
+    pub fn white(self) -> Vec3 {
+        let [_, _, _, [x, y]] = self.chromaticities();
+        xyz_from_xy(x, y)
+    }
+
+    pub fn xyz_from_rgb(self) -> Mat3 {
+        let [r, g, b, _] = self.chromaticities();
+        let primaries = Mat3::from_columns(&[
+            xyz_from_xy(r[0], r[1]),
+            xyz_from_xy(g[0], g[1]),
+            xyz_from_xy(b[0], b[1]),
+        ]);
+        let scale = primaries
+            .try_inverse()
+            .expect("Primaries must be linearly independent")
+            * self.white();
+        primaries * Mat3::from_diagonal(&scale)
+    }
+
+    pub fn rgb_from_xyz(self) -> Mat3 {
+        self.xyz_from_rgb()
+            .try_inverse()
+            .expect("Primaries must be linearly independent")
+    }
+
+    // Converts linear RGB between color spaces, adapting the white with
+    // Bradford when the whites differ.
+    pub fn conversion(from: Self, to: Self) -> Mat3 {
+        if from == to {
+            return Mat3::identity();
+        }
+        to.rgb_from_xyz() * chromatic_adaptation(from.white(), to.white()) * from.xyz_from_rgb()
+    }
+}
+
+// Adapts CIE XYZ from one white to another with the Bradford transform.
+pub fn chromatic_adaptation(source_white: Vec3, target_white: Vec3) -> Mat3 {
+    let bradford = Mat3::new(
+        0.8951, 0.2664, -0.1614, //
+        -0.7502, 1.7135, 0.0367, //
+        0.0389, -0.0685, 1.0296,
+    );
+    let source = bradford * source_white;
+    let target = bradford * target_white;
+    let scale = Mat3::from_diagonal(&target.component_div(&source));
+    bradford
+        .try_inverse()
+        .expect("Bradford matrix must be invertible")
+        * scale
+        * bradford
+}
+
+// CIE XYZ of a chromaticity, with a luminance of one.
+pub fn xyz_from_xy(x: f32, y: f32) -> Vec3 {
+    Vec3::new(x / y, 1.0, (1.0 - x - y) / y)
+}
+
+// Display encodings of the final image. Standard dynamic range encodings take
+// tonemapped values in [0, 1]. High dynamic range encodings take the exposed
+// radiance as is, where one is diffuse white.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisplayEncoding {
+    #[default]
+    Srgb,
+    DisplayP3,
+    Rec2020Pq,
+    Rec2020Hlg,
+}
+
+impl DisplayEncoding {
+    pub const ALL: [Self; 4] = [
+        Self::Srgb,
+        Self::DisplayP3,
+        Self::Rec2020Pq,
+        Self::Rec2020Hlg,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Srgb => "srgb",
+            Self::DisplayP3 => "display-p3",
+            Self::Rec2020Pq => "rec2020-pq",
+            Self::Rec2020Hlg => "rec2020-hlg",
+        }
+    }
+
+    pub fn color_space(self) -> ColorSpace {
+        match self {
+            Self::Srgb => ColorSpace::Srgb,
+            Self::DisplayP3 => ColorSpace::DisplayP3,
+            Self::Rec2020Pq | Self::Rec2020Hlg => ColorSpace::Rec2020,
+        }
+    }
+
+    pub fn is_hdr(self) -> bool {
+        matches!(self, Self::Rec2020Pq | Self::Rec2020Hlg)
+    }
+
+    // Colour primaries, transfer characteristics, matrix coefficients and the
+    // full range flag of H.273, as stored in the PNG cICP chunk.
+    pub fn cicp(self) -> [u8; 4] {
+        match self {
+            Self::Srgb => [1, 13, 0, 1],
+            Self::DisplayP3 => [12, 13, 0, 1],
+            Self::Rec2020Pq => [9, 16, 0, 1],
+            Self::Rec2020Hlg => [9, 18, 0, 1],
+        }
+    }
+
+    // Linear display value of one channel to the encoded signal in [0, 1].
+    pub fn encode(self, value: f32) -> f32 {
+        match self {
+            Self::Srgb | Self::DisplayP3 => {
+                let x = value.clamp(0.0, 1.0);
+                if x <= 0.003_130_8 {
+                    12.92 * x
+                } else {
+                    1.055 * x.powf(1.0 / 2.4) - 0.055
+                }
+            }
+            Self::Rec2020Pq => {
+                const M1: f32 = 2610.0 / 16384.0;
+                const M2: f32 = 2523.0 / 4096.0 * 128.0;
+                const C1: f32 = 3424.0 / 4096.0;
+                const C2: f32 = 2413.0 / 4096.0 * 32.0;
+                const C3: f32 = 2392.0 / 4096.0 * 32.0;
+                let y = (value * HDR_REFERENCE_WHITE / PQ_PEAK_LUMINANCE).clamp(0.0, 1.0);
+                let y_m1 = y.powf(M1);
+                ((C1 + C2 * y_m1) / (1.0 + C3 * y_m1)).powf(M2)
+            }
+            Self::Rec2020Hlg => {
+                const A: f32 = 0.178_832_77;
+                const B: f32 = 1.0 - 4.0 * A;
+                const C: f32 = 0.559_910_7;
+                // Diffuse white is placed at 75% of the signal.
+                let reference_white = ((((0.75 - C) / A).exp() + B) / 12.0).clamp(0.0, 1.0);
+                let e = (value * reference_white).clamp(0.0, 1.0);
+                if e <= 1.0 / 12.0 {
+                    (3.0 * e).sqrt()
+                } else {
+                    A * (12.0 * e - B).ln() + C
+                }
+            }
+        }
+    }
+}
+
+// Decodes one channel of an sRGB image, such as a color texture, to a linear
+// value.
+pub fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.040_45 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+//
+// Tonemapping
+//
+
+//
+// Sources:
+//
+// ACES Filmic Tone Mapping Curve
+// Krzysztof Narkowicz, 2016
+// https://knarkowicz.wordpress.com/2016/01/06/aces-filmic-tone-mapping-curve/
+//
+// Photographic Tone Reproduction for Digital Images
+// Reinhard et al., 2002
+// https://doi.org/10.1145/566654.566575
+//
+// Filmic Tonemapping Operators
+// John Hable, 2010
+// http://filmicworlds.com/blog/filmic-tonemapping-operators/
+//
+// Academy Color Encoding System Developer Resources
+// Academy of Motion Picture Arts and Sciences, 2016
+// https://github.com/ampas/aces-dev
+//
+// Minimal AgX Implementation
+// Benjamin Wrensch, 2024
+// https://iolite-engine.com/blog_posts/minimal_agx_implementation
+//
+// Khronos PBR Neutral Tone Mapper
+// The Khronos Group, 2024
+// https://github.com/KhronosGroup/ToneMapping/tree/main/PBR_Neutral
+//
+
+// Luminance that is mapped to white by the extended Reinhard operator.
+const REINHARD_WHITE_POINT: f32 = 4.0;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Tonemapper {
+    // Clamps to the displayable range, without any tonemapping.
+    Clamp,
+    Reinhard,
+    Hable,
+    // Per channel fit of the ACES filmic curve.
+    #[default]
+    AcesNarkowicz,
+    // Full RRT and the ODT of a 100 nit monitor, in the ACEScg primaries.
+    Aces,
+    AgX,
+    PbrNeutral,
+}
+
+impl Tonemapper {
+    pub const ALL: [Self; 7] = [
+        Self::Clamp,
+        Self::Reinhard,
+        Self::Hable,
+        Self::AcesNarkowicz,
+        Self::Aces,
+        Self::AgX,
+        Self::PbrNeutral,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Clamp => "clamp",
+            Self::Reinhard => "reinhard",
+            Self::Hable => "hable",
+            Self::AcesNarkowicz => "aces-narkowicz",
+            Self::Aces => "aces",
+            Self::AgX => "agx",
+            Self::PbrNeutral => "pbr-neutral",
+        }
+    }
+
+    // Maps linear radiance to linear display values in [0, 1], both with sRGB
+    // primaries.
+    pub fn apply(self, color: ColorRgb) -> ColorRgb {
+        self.in_space(ColorSpace::Srgb).apply(color)
+    }
+
+    // Binds the operator to the primaries of a display. Operators that mix
+    // channels run in the space they were designed for, the others run on
+    // the display channels as they are.
+    pub fn in_space(self, space: ColorSpace) -> Tonemapping {
+        let operator_space = match self {
+            Self::Reinhard | Self::AgX => ColorSpace::Srgb,
+            Self::Aces => ColorSpace::AcesCg,
+            Self::Clamp | Self::Hable | Self::AcesNarkowicz | Self::PbrNeutral => space,
+        };
+        Tonemapping {
+            tonemapper: self,
+            operator_from_display: ColorSpace::conversion(space, operator_space),
+            display_from_operator: ColorSpace::conversion(operator_space, space),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Tonemapping {
+    tonemapper: Tonemapper,
+    operator_from_display: Mat3,
+    display_from_operator: Mat3,
+}
+
+impl Tonemapping {
+    // Maps linear radiance to linear display values in [0, 1].
+    pub fn apply(&self, color: ColorRgb) -> ColorRgb {
+        let color = color.transform(&self.operator_from_display);
+        let color = match self.tonemapper {
+            Tonemapper::Clamp => color,
+            Tonemapper::Reinhard => reinhard(color),
+            Tonemapper::Hable => hable(color),
+            Tonemapper::AcesNarkowicz => aces_narkowicz(color),
+            Tonemapper::Aces => aces(color),
+            Tonemapper::AgX => agx(color),
+            Tonemapper::PbrNeutral => pbr_neutral(color),
+        };
+        color.transform(&self.display_from_operator).clamp()
+    }
+}
+
+fn map_channels(color: ColorRgb, f: impl Fn(f32) -> f32) -> ColorRgb {
+    ColorRgb::new(f(color.r()), f(color.g()), f(color.b()))
+}
+
+fn reinhard(color: ColorRgb) -> ColorRgb {
+    // Applied to the luminance, which preserves the hue.
+    let luminance = color.luminance();
+    if luminance <= 0.0 {
+        return ColorRgb::BLACK;
+    }
+    let white2 = REINHARD_WHITE_POINT * REINHARD_WHITE_POINT;
+    let mapped = luminance * (1.0 + luminance / white2) / (1.0 + luminance);
+    color * (mapped / luminance)
+}
+
+fn hable(color: ColorRgb) -> ColorRgb {
+    const EXPOSURE_BIAS: f32 = 2.0;
+    const WHITE_POINT: f32 = 11.2;
+    let curve = |x: f32| {
+        let (a, b, c, d, e, f) = (0.15, 0.50, 0.10, 0.20, 0.02, 0.30);
+        (x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f) - e / f
+    };
+    let white_scale = 1.0 / curve(WHITE_POINT);
+    map_channels(color, |x| curve(EXPOSURE_BIAS * x.max(0.0)) * white_scale)
+}
+
+fn aces_narkowicz(color: ColorRgb) -> ColorRgb {
+    map_channels(color, |x| {
+        let (a, b, c, d, e) = (2.51, 0.03, 2.43, 0.59, 0.14);
+        let x = x.max(0.0);
+        (x * (a * x + b)) / (x * (c * x + d) + e)
+    })
+}
+
+// Reference rendering transform followed by the output device transform of
+// a 100 nit sRGB monitor in a dim surround, from the ACES 1.0.3 CTL. Takes and
+// returns ACEScg, the output is linear and not yet clamped.
+fn aces(color: ColorRgb) -> ColorRgb {
+    let oces = aces_rrt(color.transform(&ap0_from_ap1()));
+    aces_odt(oces)
+}
+
+// ACES2065-1 to the output color encoding specification.
+fn aces_rrt(aces: ColorRgb) -> ColorRgb {
+    const GLOW_GAIN: f32 = 0.05;
+    const GLOW_MID: f32 = 0.08;
+    const RED_SCALE: f32 = 0.82;
+    const RED_PIVOT: f32 = 0.03;
+    const RED_WIDTH: f32 = 135.0;
+    const SAT_FACTOR: f32 = 0.96;
+
+    // Glow module, which brightens dark saturated colors.
+    let saturation = aces_saturation(aces);
+    let yc = aces_yc(aces);
+    let shaper = {
+        let x = (saturation - 0.4) / 0.2;
+        let t = (1.0 - (0.5 * x).abs()).max(0.0);
+        0.5 * (1.0 + x.signum() * (1.0 - t * t))
+    };
+    let glow_gain = GLOW_GAIN * shaper;
+    let glow = if yc <= 2.0 / 3.0 * GLOW_MID {
+        glow_gain
+    } else if yc >= 2.0 * GLOW_MID {
+        0.0
+    } else {
+        glow_gain * (GLOW_MID / yc - 0.5)
+    };
+    let aces = aces * (1.0 + glow);
+
+    // Red modifier, which tames saturated reds.
+    let hue_weight = aces_hue_weight(aces_hue(aces), RED_WIDTH);
+    let red = aces.r() + hue_weight * saturation * (RED_PIVOT - aces.r()) * (1.0 - RED_SCALE);
+    let aces = ColorRgb::new(red.max(0.0), aces.g().max(0.0), aces.b().max(0.0));
+
+    // Tonescale in the rendering space, after a global desaturation.
+    let rgb = aces.transform(&ap1_from_ap0());
+    let rgb = map_channels(rgb, |x| x.clamp(0.0, 65504.0));
+    let rgb = aces_desaturate(rgb, SAT_FACTOR);
+    let rgb = map_channels(rgb, aces_rrt_tonescale);
+    rgb.transform(&ap0_from_ap1())
+}
+
+// Output color encoding specification to linear ACEScg code values of the
+// display.
+fn aces_odt(oces: ColorRgb) -> ColorRgb {
+    const CINEMA_WHITE: f32 = 48.0;
+    const CINEMA_BLACK: f32 = 0.02;
+    const DIM_SURROUND_GAMMA: f32 = 0.9811;
+    const SAT_FACTOR: f32 = 0.93;
+
+    let rgb = oces.transform(&ap1_from_ap0());
+    let rgb = map_channels(rgb, |x| {
+        (aces_odt_tonescale(x) - CINEMA_BLACK) / (CINEMA_WHITE - CINEMA_BLACK)
+    });
+
+    // Dark to dim surround. Raising the luminance to a power while keeping
+    // the chromaticity scales all channels alike.
+    let luminance = aces_luminance(rgb);
+    let rgb = if luminance > 0.0 {
+        rgb * luminance.powf(DIM_SURROUND_GAMMA - 1.0)
+    } else {
+        ColorRgb::BLACK
+    };
+    aces_desaturate(rgb, SAT_FACTOR)
+}
+
+// ACES2065-1 has the AP0 primaries, ACEScg the AP1 primaries, with the same
+// white.
+fn ap0_from_ap1() -> Mat3 {
+    Mat3::new(
+        0.695_452_2,
+        0.140_678_7,
+        0.163_869_06, //
+        0.044_794_563,
+        0.859_671_1,
+        0.095_534_32, //
+        -0.005_525_883,
+        0.004_025_21,
+        1.001_500_7,
+    )
+}
+
+fn ap1_from_ap0() -> Mat3 {
+    Mat3::new(
+        1.451_439_3,
+        -0.236_510_75,
+        -0.214_928_57, //
+        -0.076_553_77,
+        1.176_229_7,
+        -0.099_675_93, //
+        0.008_316_148,
+        -0.006_032_45,
+        0.997_716_3,
+    )
+}
+
+fn aces_luminance(rgb: ColorRgb) -> f32 {
+    0.272_228_72 * rgb.r() + 0.674_081_8 * rgb.g() + 0.053_689_517 * rgb.b()
+}
+
+fn aces_desaturate(rgb: ColorRgb, saturation: f32) -> ColorRgb {
+    let gray = aces_luminance(rgb);
+    map_channels(rgb, |x| gray + saturation * (x - gray))
+}
+
+fn aces_saturation(rgb: ColorRgb) -> f32 {
+    let max = rgb.max_element();
+    let min = rgb.r().min(rgb.g()).min(rgb.b());
+    (max.max(1e-10) - min.max(1e-10)) / max.max(1e-2)
+}
+
+fn aces_yc(rgb: ColorRgb) -> f32 {
+    const RADIUS_WEIGHT: f32 = 1.75;
+    let (r, g, b) = (rgb.r(), rgb.g(), rgb.b());
+    let chroma = (b * (b - g) + g * (g - r) + r * (r - b)).max(0.0).sqrt();
+    (r + g + b + RADIUS_WEIGHT * chroma) / 3.0
+}
+
+// In degrees, in [0, 360).
+fn aces_hue(rgb: ColorRgb) -> f32 {
+    // Neutral colors have a hue of zero.
+    let (r, g, b) = (rgb.r(), rgb.g(), rgb.b());
+    let hue = (3.0_f32.sqrt() * (g - b))
+        .atan2(2.0 * r - g - b)
+        .to_degrees();
+    if hue < 0.0 {
+        hue + 360.0
+    } else {
+        hue
+    }
+}
+
+// Cubic B-spline bump around a hue of zero, one at the center.
+fn aces_hue_weight(hue: f32, width: f32) -> f32 {
+    let hue = if hue > 180.0 { hue - 360.0 } else { hue };
+    let x = (hue + 0.5 * width) * 4.0 / width;
+    if !(0.0..4.0).contains(&x) {
+        return 0.0;
+    }
+    let j = x as usize;
+    let t = x.fract();
+    let y = match j {
+        0 => t * t * t / 6.0,
+        1 => (-3.0 * t * t * t + 3.0 * t * t + 3.0 * t + 1.0) / 6.0,
+        2 => (3.0 * t * t * t - 6.0 * t * t + 4.0) / 6.0,
+        _ => (1.0 - t).powi(3) / 6.0,
+    };
+    1.5 * y
+}
+
+fn aces_rrt_tonescale(x: f32) -> f32 {
+    aces_spline(
+        x,
+        &[
+            -4.0,
+            -4.0,
+            -3.157_376_6,
+            -0.485_25,
+            1.847_732_5,
+            1.847_732_5,
+        ],
+        &[-0.718_548_2, 2.081_030_6, 3.668_124, 4.0, 4.0, 4.0],
+        [
+            [0.18 * 2.0_f32.powi(-15), 0.0001],
+            [0.18, 4.8],
+            [0.18 * 2.0_f32.powi(18), 10000.0],
+        ],
+        [0.0, 0.0],
+    )
+}
+
+fn aces_odt_tonescale(x: f32) -> f32 {
+    aces_spline(
+        x,
+        &[
+            -1.698_970_1,
+            -1.698_970_1,
+            -1.4779,
+            -1.2291,
+            -0.8648,
+            -0.448,
+            0.005_18,
+            0.451_108,
+            0.911_374_4,
+            0.911_374_4,
+        ],
+        &[
+            0.515_438_7,
+            0.847_043_8,
+            1.1358,
+            1.3802,
+            1.5197,
+            1.5985,
+            1.6467,
+            1.674_609_1,
+            1.687_873_3,
+            1.687_873_3,
+        ],
+        [
+            [ODT_TONESCALE_X[0], 0.02],
+            [ODT_TONESCALE_X[1], 4.8],
+            [ODT_TONESCALE_X[2], 48.0],
+        ],
+        [0.0, 0.04],
+    )
+}
+
+// The RRT tonescale at 6.5 stops below, at and 6.5 stops above middle gray.
+const ODT_TONESCALE_X: [f32; 3] = [0.002_879_896, 4.8, 1_005.718_5];
+
+// Quadratic B-spline through log10 coefficients, between a minimum, middle
+// and maximum point, with linear extensions in log-log space.
+fn aces_spline(
+    x: f32,
+    coefs_low: &[f32],
+    coefs_high: &[f32],
+    [min, mid, max]: [[f32; 2]; 3],
+    [slope_low, slope_high]: [f32; 2],
+) -> f32 {
+    const HALF_MIN: f32 = 5.960_464_5e-8;
+    let segment = |coefs: &[f32], x0: f32, x1: f32, log_x: f32| {
+        let knots = (coefs.len() - 2) as f32;
+        let coord = (knots - 1.0) * (log_x - x0) / (x1 - x0);
+        let j = coord as usize;
+        let t = coord.fract();
+        let (c0, c1, c2) = (coefs[j], coefs[j + 1], coefs[j + 2]);
+        t * t * (0.5 * c0 - c1 + 0.5 * c2) + t * (c1 - c0) + 0.5 * (c0 + c1)
+    };
+    let log_x = x.max(HALF_MIN).log10();
+    let [log_min, log_mid, log_max] = [min[0].log10(), mid[0].log10(), max[0].log10()];
+    let log_y = if log_x <= log_min {
+        slope_low * (log_x - log_min) + min[1].log10()
+    } else if log_x < log_mid {
+        segment(coefs_low, log_min, log_mid, log_x)
+    } else if log_x < log_max {
+        segment(coefs_high, log_mid, log_max, log_x)
+    } else {
+        slope_high * (log_x - log_max) + max[1].log10()
+    };
+    10.0_f32.powf(log_y)
+}
+
+fn agx(color: ColorRgb) -> ColorRgb {
+    const MIN_EV: f32 = -12.473_93;
+    const MAX_EV: f32 = 4.026_069;
+    let inset = Mat3::new(
+        0.842_479_06,
+        0.078_433_6,
+        0.079_223_745, //
+        0.042_328_24,
+        0.878_468_6,
+        0.079_166_13, //
+        0.042_375_655,
+        0.078_433_6,
+        0.879_143,
+    );
+    let outset = Mat3::new(
+        1.196_879,
+        -0.098_020_88,
+        -0.099_029_74, //
+        -0.052_896_85,
+        1.151_903_1,
+        -0.098_961_18, //
+        -0.052_971_635,
+        -0.098_043_45,
+        1.151_073_7,
+    );
+    let color = map_channels(color.transform(&inset), |x| {
+        // Log encoding, followed by a polynomial fit of the sigmoid.
+        let x = (x.max(1e-10).log2().clamp(MIN_EV, MAX_EV) - MIN_EV) / (MAX_EV - MIN_EV);
+        let x2 = x * x;
+        let x4 = x2 * x2;
+        15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2 + 0.1191 * x
+            - 0.002_32
+    });
+    // The sigmoid outputs display encoded values, which are linearized with
+    // the 2.2 power reference display.
+    map_channels(color.transform(&outset), |x| x.max(0.0).powf(2.2))
+}
+
+fn pbr_neutral(color: ColorRgb) -> ColorRgb {
+    const START_COMPRESSION: f32 = 0.8 - 0.04;
+    const DESATURATION: f32 = 0.15;
+    let x = color.r().min(color.g()).min(color.b());
+    let offset = if x < 0.08 { x - 6.25 * x * x } else { 0.04 };
+    let color = map_channels(color, |c| c - offset);
+    let peak = color.max_element();
+    if peak < START_COMPRESSION {
+        return color;
+    }
+    let d = 1.0 - START_COMPRESSION;
+    let new_peak = 1.0 - d * d / (peak + d - START_COMPRESSION);
+    let color = color * (new_peak / peak);
+    let g = 1.0 - 1.0 / (DESATURATION * (peak - new_peak) + 1.0);
+    let [r, g, b] = lerp_array(color.into(), [new_peak; 3], g);
+    ColorRgb::new(r, g, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tonemappers() {
+        // Black stays black, every operator is monotonic and stays in range.
+        for tonemapper in Tonemapper::ALL {
+            let black = tonemapper.apply(ColorRgb::BLACK);
+            assert!(black.max_element() < 1e-3, "{tonemapper:?}: {black}");
+            let mut previous = 0.0;
+            for index in 0..=200 {
+                let x = 2.0_f32.powf(index as f32 / 10.0 - 10.0);
+                let y = tonemapper.apply(ColorRgb::new(x, x, x)).luminance();
+                assert!((0.0..=1.0).contains(&y), "{tonemapper:?}: x={x}, y={y}");
+                assert!(y >= previous - 1e-4, "{tonemapper:?}: x={x}, y={y}");
+                previous = y;
+            }
+        }
+    }
+
+    #[test]
+    fn aces_rrt_odt() {
+        for (x, stops) in ODT_TONESCALE_X.into_iter().zip([-6.5, 0.0, 6.5]) {
+            let rrt = aces_rrt_tonescale(0.18 * 2.0_f32.powf(stops));
+            assert_abs_diff_eq!(rrt, x, epsilon = 1e-4 * x);
+        }
+
+        // Middle gray ends up at about 10% of the display luminance.
+        let gray = aces(ColorRgb::new(0.18, 0.18, 0.18));
+        assert_abs_diff_eq!(gray.r(), 0.1041, epsilon = 1e-3);
+        assert_abs_diff_eq!(gray.r(), gray.g(), epsilon = 1e-5);
+        assert_abs_diff_eq!(gray.r(), gray.b(), epsilon = 1e-5);
+
+        // The result doesn't depend on the primaries of the display.
+        let srgb = ColorRgb::new(0.6, 0.2, 0.05);
+        let to_p3 = ColorSpace::conversion(ColorSpace::Srgb, ColorSpace::DisplayP3);
+        for tonemapper in Tonemapper::ALL {
+            if matches!(
+                tonemapper,
+                Tonemapper::Reinhard | Tonemapper::Aces | Tonemapper::AgX
+            ) {
+                let a = tonemapper.apply(srgb).transform(&to_p3);
+                let b = tonemapper
+                    .in_space(ColorSpace::DisplayP3)
+                    .apply(srgb.transform(&to_p3));
+                assert_abs_diff_eq!(a.r(), b.r(), epsilon = 1e-4);
+                assert_abs_diff_eq!(a.g(), b.g(), epsilon = 1e-4);
+                assert_abs_diff_eq!(a.b(), b.b(), epsilon = 1e-4);
+            }
+        }
+    }
+
+    #[test]
+    fn color_spaces() {
+        // sRGB white is white everywhere, and conversions round trip.
+        let white = Vec3::new(1.0, 1.0, 1.0);
+        for space in ColorSpace::ALL {
+            let to = ColorSpace::conversion(ColorSpace::Srgb, space);
+            let from = ColorSpace::conversion(space, ColorSpace::Srgb);
+            assert_abs_diff_eq!(to * white, white, epsilon = 1e-4);
+            assert_abs_diff_eq!(from * to, Mat3::identity(), epsilon = 1e-4);
+        }
+
+        // The luminance row of sRGB matches `ColorRgb::luminance`.
+        let xyz_from_srgb = ColorSpace::Srgb.xyz_from_rgb();
+        assert_abs_diff_eq!(xyz_from_srgb[(1, 0)], 0.2126, epsilon = 1e-4);
+        assert_abs_diff_eq!(xyz_from_srgb[(1, 1)], 0.7152, epsilon = 1e-4);
+        assert_abs_diff_eq!(xyz_from_srgb[(1, 2)], 0.0722, epsilon = 1e-4);
+
+        // Diffuse white lands on the reference levels of BT.2408.
+        assert_abs_diff_eq!(DisplayEncoding::Rec2020Pq.encode(1.0), 0.58, epsilon = 1e-2);
+        assert_abs_diff_eq!(
+            DisplayEncoding::Rec2020Hlg.encode(1.0),
+            0.75,
+            epsilon = 1e-4
+        );
+        assert_abs_diff_eq!(
+            DisplayEncoding::Rec2020Pq.encode(PQ_PEAK_LUMINANCE / HDR_REFERENCE_WHITE),
+            1.0,
+            epsilon = 1e-4
+        );
+    }
+}
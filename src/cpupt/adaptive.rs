@@ -0,0 +1,118 @@
+use super::*;
+
+// Every pixel keeps a running mean and variance of its samples with Welford's
+// algorithm, which stays accurate when the mean is large compared to the
+// spread. With adaptive sampling enabled, a tile stops being sampled once every
+// pixel in it has the minimum sample count, and the standard error of its mean
+// luminance relative to the mean is below the threshold. The samples saved on
+// converged tiles are spent on the remaining ones, up to a multiple of the
+// sample count.
+
+//
+// Sources:
+//
+// Note on a Method for Calculating Corrected Sums of Squares and Products
+// B. P. Welford, 1962
+// https://doi.org/10.1080/00401706.1962.10490022
+//
+// A Hierarchical Automatic Stopping Condition for Monte Carlo Global Illumination
+// Dammertz et al., 2009
+// https://jo.dreggn.org/home/2009_stopping.pdf
+//
+
+// Upper limit of samples per pixel in noisy tiles, relative to the sample count.
+pub(super) const MAX_SAMPLE_FACTOR: u32 = 8;
+
+// Keeps dark pixels from never converging.
+const MIN_RELATIVE_LUMINANCE: f32 = 1e-2;
+
+#[derive(Clone, Copy)]
+pub(super) struct PixelEstimate {
+    count: u32,
+    mean: ColorRgb,
+    m2: ColorRgb,
+}
+
+impl PixelEstimate {
+    pub const EMPTY: Self = Self {
+        count: 0,
+        mean: ColorRgb::BLACK,
+        m2: ColorRgb::BLACK,
+    };
+
+    pub fn add(&mut self, sample: ColorRgb) {
+        self.count += 1;
+        let delta = sample - self.mean;
+        self.mean += delta * (1.0 / self.count as f32);
+        self.m2 += delta * (sample - self.mean);
+    }
+
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    pub fn mean(&self) -> ColorRgb {
+        self.mean
+    }
+
+    // Unbiased variance of the samples, per channel.
+    pub fn variance(&self) -> ColorRgb {
+        if self.count < 2 {
+            return ColorRgb::BLACK;
+        }
+        let variance = self.m2 * (1.0 / (self.count - 1) as f32);
+        ColorRgb::new(
+            variance.r().max(0.0),
+            variance.g().max(0.0),
+            variance.b().max(0.0),
+        )
+    }
+
+    // Standard error of the mean luminance, relative to the mean luminance.
+    pub fn relative_error(&self) -> f32 {
+        if self.count < 2 {
+            return f32::INFINITY;
+        }
+        let standard_error = (self.variance().luminance() / self.count as f32).sqrt();
+        standard_error / self.mean.luminance().max(MIN_RELATIVE_LUMINANCE)
+    }
+}
+
+pub(super) fn tile_converged(
+    tile: &PixelTile,
+    image_width: u32,
+    estimates: &[PixelEstimate],
+    threshold: f32,
+    min_samples_per_pixel: u32,
+) -> bool {
+    for pixel_y in tile.start_y..tile.end_y {
+        for pixel_x in tile.start_x..tile.end_x {
+            let estimate = &estimates[(pixel_x + pixel_y * image_width) as usize];
+            if estimate.count() < min_samples_per_pixel || estimate.relative_error() > threshold {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn welford() {
+        let samples = [1.0_f32, 3.0, 5.0, 7.0].map(|v| ColorRgb::new(v, 1000.0 + v, 2.0));
+        let mut estimate = PixelEstimate::EMPTY;
+        for sample in samples {
+            estimate.add(sample);
+        }
+
+        // Mean 4, variance (9 + 1 + 1 + 9) / 3.
+        assert_eq!(estimate.count(), 4);
+        assert_abs_diff_eq!(estimate.mean().r(), 4.0, epsilon = 1e-5);
+        assert_abs_diff_eq!(estimate.variance().r(), 20.0 / 3.0, epsilon = 1e-4);
+        assert_abs_diff_eq!(estimate.variance().g(), 20.0 / 3.0, epsilon = 1e-2);
+        assert_abs_diff_eq!(estimate.variance().b(), 0.0);
+    }
+}
@@ -0,0 +1,342 @@
+use super::*;
+
+// Arbitrary output variables are recorded at the first surface hit of each
+// primary ray, next to the radiance. Continuous values are averaged over all
+// samples that hit a surface, ids are taken from the first sample that did.
+// Pixels where every sample missed are left at zero, with `NO_ID` as ids.
+
+pub const NO_ID: u32 = u32::MAX;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AovKind {
+    Radiance,
+    Albedo,
+    Normal,
+    Depth,
+    Position,
+    MeshId,
+    MaterialId,
+    Variance,
+    SampleCount,
+}
+
+impl AovKind {
+    pub const ALL: [Self; 9] = [
+        Self::Radiance,
+        Self::Albedo,
+        Self::Normal,
+        Self::Depth,
+        Self::Position,
+        Self::MeshId,
+        Self::MaterialId,
+        Self::Variance,
+        Self::SampleCount,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Radiance => "radiance",
+            Self::Albedo => "albedo",
+            Self::Normal => "normal",
+            Self::Depth => "depth",
+            Self::Position => "position",
+            Self::MeshId => "mesh-id",
+            Self::MaterialId => "material-id",
+            Self::Variance => "variance",
+            Self::SampleCount => "sample-count",
+        }
+    }
+}
+
+// What a primary ray saw at its first surface hit.
+#[derive(Clone, Copy)]
+pub(super) struct FirstHit {
+    // Base color of the resolved material, or the reflectance at normal
+    // incidence for conductors.
+    pub albedo: ColorRgb,
+    pub normal: Vec3,
+    pub position: Point3,
+    // Distance from the camera.
+    pub depth: f32,
+    pub mesh: u32,
+    pub material: u32,
+}
+
+// Running sums of one pixel over all samples.
+#[derive(Clone, Copy)]
+pub(super) struct AovAccumulator {
+    albedo: ColorRgb,
+    normal: Vec3,
+    position: Vec3,
+    depth: f32,
+    hit_count: u32,
+    mesh: u32,
+    material: u32,
+}
+
+impl AovAccumulator {
+    pub const EMPTY: Self = Self {
+        albedo: ColorRgb::BLACK,
+        normal: Vec3::new(0.0, 0.0, 0.0),
+        position: Vec3::new(0.0, 0.0, 0.0),
+        depth: 0.0,
+        hit_count: 0,
+        mesh: NO_ID,
+        material: NO_ID,
+    };
+
+    pub fn add(&mut self, first_hit: Option<&FirstHit>) {
+        if let Some(hit) = first_hit {
+            self.albedo += hit.albedo;
+            self.normal += hit.normal;
+            self.position += hit.position.coords;
+            self.depth += hit.depth;
+            if self.hit_count == 0 {
+                self.mesh = hit.mesh;
+                self.material = hit.material;
+            }
+            self.hit_count += 1;
+        }
+    }
+}
+
+pub struct Aovs {
+    pub albedo: Vec<ColorRgb>,
+    // Unit length, or zero where nothing was hit.
+    pub normal: Vec<Vec3>,
+    pub depth: Vec<f32>,
+    pub position: Vec<Point3>,
+    pub mesh_id: Vec<u32>,
+    pub material_id: Vec<u32>,
+    // Unbiased variance of the radiance samples, per channel.
+    pub variance: Vec<ColorRgb>,
+    pub sample_count: Vec<u32>,
+}
+
+impl Aovs {
+    pub(super) fn resolve(
+        accumulators: &[AovAccumulator],
+        estimates: &[adaptive::PixelEstimate],
+    ) -> Self {
+        let pixel_count = accumulators.len();
+        let mut aovs = Self {
+            albedo: Vec::with_capacity(pixel_count),
+            normal: Vec::with_capacity(pixel_count),
+            depth: Vec::with_capacity(pixel_count),
+            position: Vec::with_capacity(pixel_count),
+            mesh_id: Vec::with_capacity(pixel_count),
+            material_id: Vec::with_capacity(pixel_count),
+            variance: Vec::with_capacity(pixel_count),
+            sample_count: Vec::with_capacity(pixel_count),
+        };
+        for (acc, estimate) in accumulators.iter().zip(estimates) {
+            let hit_factor = 1.0 / acc.hit_count.max(1) as f32;
+            aovs.albedo.push(acc.albedo * hit_factor);
+            aovs.normal
+                .push(acc.normal.try_normalize(1e-6).unwrap_or_else(Vec3::zeros));
+            aovs.depth.push(acc.depth * hit_factor);
+            aovs.position.push(Point3::from(acc.position * hit_factor));
+            aovs.mesh_id.push(acc.mesh);
+            aovs.material_id.push(acc.material);
+            aovs.variance.push(estimate.variance());
+            aovs.sample_count.push(estimate.count());
+        }
+        aovs
+    }
+
+    // Maps an AOV to displayable colors. Returns `None` for radiance, which
+    // is displayed by the regular image.
+    pub fn visualize(&self, kind: AovKind) -> Option<Vec<ColorRgb>> {
+        let hit = |index: usize| self.mesh_id[index] != NO_ID;
+        let colors = match kind {
+            AovKind::Radiance => return None,
+            AovKind::Albedo => self.albedo.clone(),
+            AovKind::Normal => self
+                .normal
+                .iter()
+                .enumerate()
+                .map(|(index, n)| {
+                    if hit(index) {
+                        ColorRgb::new(0.5 * (n.x + 1.0), 0.5 * (n.y + 1.0), 0.5 * (n.z + 1.0))
+                    } else {
+                        ColorRgb::BLACK
+                    }
+                })
+                .collect(),
+            AovKind::Depth => {
+                // Near is bright, the farthest hit is black.
+                let max_depth = self.depth.iter().copied().fold(0.0_f32, f32::max);
+                self.depth
+                    .iter()
+                    .enumerate()
+                    .map(|(index, &depth)| {
+                        if hit(index) && max_depth > 0.0 {
+                            let value = 1.0 - depth / max_depth;
+                            ColorRgb::new(value, value, value)
+                        } else {
+                            ColorRgb::BLACK
+                        }
+                    })
+                    .collect()
+            }
+            AovKind::Position => {
+                // Normalized to the bounds of all hit positions.
+                let mut min = Vec3::repeat(f32::MAX);
+                let mut max = Vec3::repeat(f32::MIN);
+                for (index, position) in self.position.iter().enumerate() {
+                    if hit(index) {
+                        min = min.inf(&position.coords);
+                        max = max.sup(&position.coords);
+                    }
+                }
+                let extent = (max - min).map(|e| if e > 0.0 { e } else { 1.0 });
+                self.position
+                    .iter()
+                    .enumerate()
+                    .map(|(index, position)| {
+                        if hit(index) {
+                            let p = (position.coords - min).component_div(&extent);
+                            ColorRgb::new(p.x, p.y, p.z)
+                        } else {
+                            ColorRgb::BLACK
+                        }
+                    })
+                    .collect()
+            }
+            AovKind::MeshId => self.mesh_id.iter().copied().map(id_color).collect(),
+            AovKind::MaterialId => self.material_id.iter().copied().map(id_color).collect(),
+            AovKind::Variance => self
+                .variance
+                .iter()
+                .map(|v| v.tonemap(Tonemapper::default()))
+                .collect(),
+            AovKind::SampleCount => {
+                // Heatmap from the fewest to the most samples.
+                let min = self.sample_count.iter().copied().min().unwrap_or(0);
+                let max = self.sample_count.iter().copied().max().unwrap_or(0);
+                let range = (max - min).max(1) as f32;
+                self.sample_count
+                    .iter()
+                    .map(|&count| heatmap_color((count - min) as f32 / range))
+                    .collect()
+            }
+        };
+        Some(colors)
+    }
+
+    // Layers of a multi-channel EXR, with the radiance as the default layer.
+    pub fn exr_channels(&self, radiance: &[ColorRgb]) -> Vec<vz::hdr::ExrChannel> {
+        use vz::hdr::{ExrChannel, ExrSamples};
+        let float = |name: &str, samples: Vec<f32>| ExrChannel {
+            name: name.to_owned(),
+            samples: ExrSamples::Float(samples),
+        };
+        let uint = |name: &str, samples: &[u32]| ExrChannel {
+            name: name.to_owned(),
+            samples: ExrSamples::Uint(samples.to_vec()),
+        };
+        let color = |layer: &str, colors: &[ColorRgb]| {
+            [("R", 0), ("G", 1), ("B", 2)].map(|(channel, component)| {
+                let name = if layer.is_empty() {
+                    channel.to_owned()
+                } else {
+                    format!("{layer}.{channel}")
+                };
+                float(
+                    &name,
+                    colors
+                        .iter()
+                        .map(|c| [c.r(), c.g(), c.b()][component])
+                        .collect(),
+                )
+            })
+        };
+        let vector = |layer: &str, vectors: Vec<Vec3>| {
+            [("X", 0), ("Y", 1), ("Z", 2)].map(|(channel, component)| {
+                float(
+                    &format!("{layer}.{channel}"),
+                    vectors.iter().map(|v| v[component]).collect(),
+                )
+            })
+        };
+        let mut channels = vec![];
+        channels.extend(color("", radiance));
+        channels.extend(color("albedo", &self.albedo));
+        channels.extend(vector("normal", self.normal.clone()));
+        channels.push(float("Z", self.depth.clone()));
+        channels.extend(vector(
+            "position",
+            self.position.iter().map(|p| p.coords).collect(),
+        ));
+        channels.push(uint("meshId", &self.mesh_id));
+        channels.push(uint("materialId", &self.material_id));
+        channels.extend(color("variance", &self.variance));
+        channels.push(uint("sampleCount", &self.sample_count));
+        channels
+    }
+}
+
+// Distinct, stable colors for ids.
+fn id_color(id: u32) -> ColorRgb {
+    if id == NO_ID {
+        return ColorRgb::BLACK;
+    }
+    let mut h = id.wrapping_mul(0x9e37_79b9);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x2c1b_3c6d);
+    h ^= h >> 12;
+    let channel = |shift: u32| 0.2 + 0.8 * ((h >> shift) & 0xff) as f32 / 255.0;
+    ColorRgb::new(channel(0), channel(8), channel(16))
+}
+
+// Blue to red through cyan, green and yellow.
+fn heatmap_color(t: f32) -> ColorRgb {
+    const STOPS: [[f32; 3]; 5] = [
+        [0.0, 0.0, 0.5],
+        [0.0, 0.5, 1.0],
+        [0.0, 1.0, 0.25],
+        [1.0, 1.0, 0.0],
+        [1.0, 0.0, 0.0],
+    ];
+    let x = t.clamp(0.0, 1.0) * (STOPS.len() - 1) as f32;
+    let index = (x as usize).min(STOPS.len() - 2);
+    let [r, g, b] = lerp_array(STOPS[index], STOPS[index + 1], x - index as f32);
+    ColorRgb::new(r, g, b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_variance() {
+        let hit = FirstHit {
+            albedo: ColorRgb::new(0.5, 0.5, 0.5),
+            normal: Vec3::new(0.0, 1.0, 0.0),
+            position: Point3::new(1.0, 2.0, 3.0),
+            depth: 4.0,
+            mesh: 2,
+            material: 1,
+        };
+        let mut acc = AovAccumulator::EMPTY;
+        let mut estimate = adaptive::PixelEstimate::EMPTY;
+        for (radiance, first_hit) in [(1.0, None), (3.0, Some(&hit)), (5.0, Some(&hit))] {
+            acc.add(first_hit);
+            estimate.add(ColorRgb::new(radiance, 1.0, 1.0));
+        }
+        let aovs = Aovs::resolve(
+            &[acc, AovAccumulator::EMPTY],
+            &[estimate, adaptive::PixelEstimate::EMPTY],
+        );
+
+        // Samples 1, 3 and 5 have mean 3 and variance (4 + 0 + 4) / 2.
+        assert_abs_diff_eq!(aovs.variance[0].r(), 4.0, epsilon = 1e-5);
+        assert_abs_diff_eq!(aovs.variance[0].g(), 0.0, epsilon = 1e-5);
+        assert_abs_diff_eq!(aovs.depth[0], 4.0);
+        assert_eq!(aovs.mesh_id[0], 2);
+        assert_eq!(aovs.material_id[0], 1);
+        assert_eq!(aovs.sample_count, [3, 0]);
+        assert_eq!(aovs.mesh_id[1], NO_ID);
+        assert_eq!(aovs.normal[1], Vec3::zeros());
+    }
+}
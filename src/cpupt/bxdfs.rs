@@ -179,6 +179,10 @@ pub enum Model {
     DisneyDiffuse,
     DisneySpecular,
     DisneySheen,
+    CharlieSheen,
+    Disney,
+    Conductor,
+    OrenNayar,
 }
 
 impl std::fmt::Display for Model {
@@ -191,6 +195,10 @@ impl std::fmt::Display for Model {
                 Self::DisneyDiffuse => "disney-diffuse",
                 Self::DisneySpecular => "disney-specular",
                 Self::DisneySheen => "disney-sheen",
+                Self::CharlieSheen => "charlie-sheen",
+                Self::Disney => "disney",
+                Self::Conductor => "conductor",
+                Self::OrenNayar => "oren-nayar",
             }
         )
     }
@@ -332,70 +340,162 @@ impl Bxdf for DisneyDiffuse {
 }
 
 //
-// BxDF - Disney Specular
+// BxDF - Oren-Nayar
 //
 
 //
 // Sources:
 //
-// Understanding the Masking-Shadowing Function in Microfacet-Based BRDFs
-// Eric Heitz and Eugene D’Eon, 2014
-// https://jcgt.org/published/0003/02/03/paper.pdf
+// Generalization of Lambert's Reflectance Model
+// Michael Oren and Shree K. Nayar, 1994
+// https://www1.cs.columbia.edu/CAVE/publications/pdfs/Oren_SIGGRAPH94.pdf
 //
-// A Simpler and Exact Sampling Routine for the GGX Distribution of Visible Normals
-// Eric Heitz, 2017
-// https://hal.science/hal-01509746/document
+// EON: A practical energy-preserving rough diffuse BRDF
+// Jamie Portsmouth, Peter Kutz and Stephen Hill, 2025
+// https://arxiv.org/abs/2410.18026
 //
 
+// Fujii's improved Oren-Nayar model, with an energy-preserving multiple
+// scattering term. Roughness is the standard deviation of the facet slopes,
+// which makes roughness=0 exactly Lambertian.
+
+const FON_CONSTANT_1: f32 = 0.5 - 2.0 / (3.0 * PI);
+const FON_CONSTANT_2: f32 = 2.0 / 3.0 - 28.0 / (15.0 * PI);
+
 #[derive(Clone, Copy, Debug)]
-pub struct DisneySpecularParams {
+pub struct OrenNayarParams {
+    pub hemisphere: HemisphereSampler,
     pub base_color: ColorRgb,
-    pub metallic: f32,
-    pub specular: f32,
-    pub specular_tint: f32,
     pub roughness: f32,
-    pub anisotropic: f32,
 }
 
 #[derive(Clone, Copy, Debug)]
-pub struct DisneySpecular {
-    specular_color: ColorRgb,
-    alpha_x: f32,
-    alpha_y: f32,
+pub struct OrenNayar {
+    hemisphere: HemisphereSampler,
+    base_color: ColorRgb,
+    roughness: f32,
 }
 
-impl DisneySpecular {
-    pub fn new(p: &DisneySpecularParams) -> Self {
-        assert_range!(p.metallic, 0.0, 1.0);
-        assert_range!(p.specular, 0.0, 1.0);
-        assert_range!(p.specular_tint, 0.0, 1.0);
+impl OrenNayar {
+    pub fn new(p: &OrenNayarParams) -> Self {
         assert_range!(p.roughness, 0.0, 1.0);
-        assert_range!(p.anisotropic, 0.0, 1.0);
 
-        let specular_color = {
-            let luminance = p.base_color.luminance();
-            let tint_color = if luminance > 0.0 {
-                p.base_color / luminance
-            } else {
-                ColorRgb::WHITE
-            };
-            let metallic_color =
-                p.specular * 0.08 * lerp_color(&ColorRgb::WHITE, &tint_color, p.specular_tint);
-            lerp_color(&metallic_color, &p.base_color, p.metallic)
+        Self {
+            hemisphere: p.hemisphere,
+            base_color: p.base_color,
+            roughness: p.roughness,
+        }
+    }
+
+    // Polynomial fit of the single scattering directional albedo.
+    fn albedo(&self, cos_theta: f32) -> f32 {
+        const G1: f32 = 0.057_108_53;
+        const G2: f32 = 0.491_881_87;
+        const G3: f32 = -0.332_181_44;
+        const G4: f32 = 0.071_443;
+        let mu = 1.0 - cos_theta;
+        let g_over_pi = mu * (G1 + mu * (G2 + mu * (G3 + mu * G4)));
+        (1.0 + self.roughness * g_over_pi) / (1.0 + FON_CONSTANT_1 * self.roughness)
+    }
+}
+
+impl Bxdf for OrenNayar {
+    fn model(&self) -> Model {
+        Model::OrenNayar
+    }
+
+    fn eval(&self, wo: &Outgoing, wi: &Incoming) -> Reflectance {
+        // Angles.
+        let cos_theta_i = wi.cos_theta().abs().max(EPSILON);
+        let cos_theta_o = wo.cos_theta().abs().max(EPSILON);
+
+        // Single scattering.
+        let s = wi.0.dot(&wo.0) - cos_theta_i * cos_theta_o;
+        let s_over_t = if s > 0.0 {
+            s / cos_theta_i.max(cos_theta_o)
+        } else {
+            s
         };
+        let a = 1.0 / (1.0 + FON_CONSTANT_1 * self.roughness);
+        let single = self.base_color * INV_PI * a * (1.0 + self.roughness * s_over_t);
+
+        // Multiple scattering.
+        let e_o = self.albedo(cos_theta_o);
+        let e_i = self.albedo(cos_theta_i);
+        let e_avg = a * (1.0 + FON_CONSTANT_2 * self.roughness);
+        let rho_ms = |rho: f32| rho * rho * e_avg / (1.0 - rho * (1.0 - e_avg));
+        let rho_ms = ColorRgb::new(
+            rho_ms(self.base_color.r()),
+            rho_ms(self.base_color.g()),
+            rho_ms(self.base_color.b()),
+        );
+        let multiple = rho_ms * INV_PI * (1.0 - e_o).max(0.0) * (1.0 - e_i).max(0.0)
+            / (1.0 - e_avg).max(EPSILON);
+
+        single + multiple
+    }
+
+    fn pdf(&self, _: &Outgoing, wi: &Incoming) -> Pdf {
+        self.hemisphere.pdf(wi.cos_theta().abs())
+    }
+
+    fn sample(&self, wo: &Outgoing, u: UniformSample2D) -> Option<Sample> {
+        let wi = LocalVector(*self.hemisphere.sample(u.0, u.1));
+        let pdf = self.pdf(wo, &wi);
+        if pdf > EPSILON {
+            Some(Sample {
+                r: self.eval(wo, &wi),
+                wi,
+                pdf,
+            })
+        } else {
+            None
+        }
+    }
+}
 
-        let aspect = (1.0 - p.anisotropic * 0.9).sqrt();
-        let alpha_x = f32::max(0.001, p.roughness.powi(2) / aspect);
-        let alpha_y = f32::max(0.001, p.roughness.powi(2) * aspect);
+//
+// Microfacet distribution - GGX
+//
 
+//
+// Sources:
+//
+// Understanding the Masking-Shadowing Function in Microfacet-Based BRDFs
+// Eric Heitz and Eugene D’Eon, 2014
+// https://jcgt.org/published/0003/02/03/paper.pdf
+//
+// A Simpler and Exact Sampling Routine for the GGX Distribution of Visible Normals
+// Eric Heitz, 2017
+// https://hal.science/hal-01509746/document
+//
+// Revisiting Physically Based Shading at Imageworks
+// Christopher Kulla and Alejandro Conty, 2017
+// https://blog.selfshadow.com/publications/s2017-shading-course/imageworks/s2017_pbs_imageworks_slides_v2.pdf
+//
+
+// Resolution of the tabulated directional albedo in `ggx_albedo.rs`, over
+// both roughness and cos(θ).
+pub const GGX_ALBEDO_SIZE: usize = 32;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Ggx {
+    roughness: f32,
+    alpha_x: f32,
+    alpha_y: f32,
+}
+
+impl Ggx {
+    pub fn new(roughness: f32, anisotropic: f32) -> Self {
+        let aspect = (1.0 - anisotropic * 0.9).sqrt();
         Self {
-            specular_color,
-            alpha_x,
-            alpha_y,
+            roughness,
+            alpha_x: f32::max(0.001, roughness.powi(2) / aspect),
+            alpha_y: f32::max(0.001, roughness.powi(2) * aspect),
         }
     }
 
-    fn ggx_d(&self, wm: &MicrosurfaceNormal) -> f32 {
+    fn d(self, wm: &MicrosurfaceNormal) -> f32 {
         let tan2_theta = wm.tan2_theta();
         if !tan2_theta.is_finite() {
             return 0.0;
@@ -409,7 +509,7 @@ impl DisneySpecular {
         1.0 / (PI * alpha_x * alpha_y * cos4_theta * (1.0 + e).powi(2))
     }
 
-    fn ggx_lambda(&self, w: &LocalVector) -> f32 {
+    fn lambda(self, w: &LocalVector) -> f32 {
         let tan2_theta = w.tan2_theta();
         if !tan2_theta.is_finite() {
             return 0.0;
@@ -419,19 +519,19 @@ impl DisneySpecular {
         let alpha_x = self.alpha_x;
         let alpha_y = self.alpha_y;
         let alpha2 = (cos_phi * alpha_x).powi(2) + (sin_phi * alpha_y).powi(2);
-        ((1.0 + alpha2 + tan2_theta).sqrt() - 1.0) / 2.0
+        ((1.0 + alpha2 * tan2_theta).sqrt() - 1.0) / 2.0
     }
 
-    fn ggx_g1(&self, w: &LocalVector) -> f32 {
-        1.0 / (1.0 + self.ggx_lambda(w))
+    fn g1(self, w: &LocalVector) -> f32 {
+        1.0 / (1.0 + self.lambda(w))
     }
 
     // Height-correlated masking-shadowing function.
-    fn ggx_g(&self, wo: &Outgoing, wi: &Incoming) -> f32 {
-        1.0 / (1.0 + self.ggx_lambda(wo) + self.ggx_lambda(wi))
+    fn g(self, wo: &Outgoing, wi: &Incoming) -> f32 {
+        1.0 / (1.0 + self.lambda(wo) + self.lambda(wi))
     }
 
-    fn ggx_sample_wm(&self, wo: &Outgoing, u: UniformSample2D) -> MicrosurfaceNormal {
+    fn sample_wm(self, wo: &Outgoing, u: UniformSample2D) -> MicrosurfaceNormal {
         // Stretch.
         let v = vector![self.alpha_x * wo.0.x, wo.0.y, self.alpha_y * wo.0.z].normalize();
         let v = if v.y < 0.0 { -v } else { v };
@@ -463,10 +563,319 @@ impl DisneySpecular {
         LocalVector(vector![self.alpha_x * n.x, n.y, self.alpha_y * n.z].normalize())
     }
 
+    // Cook-Torrance microfacet model without the Fresnel term.
+    fn eval(self, wo: &Outgoing, wi: &Incoming) -> f32 {
+        // Angles.
+        let cos_theta_i = wi.cos_theta().abs().max(EPSILON);
+        let cos_theta_o = wo.cos_theta().abs().max(EPSILON);
+
+        let wm = &wo.microsurface_normal(wi);
+        let d = self.d(wm);
+        let g = self.g(wo, wi);
+        d * g / (4.0 * cos_theta_i * cos_theta_o)
+    }
+
+    // Density of reflected directions from visible normal sampling.
+    fn pdf(self, wo: &Outgoing, wi: &Incoming) -> Pdf {
+        let wm = &wo.microsurface_normal(wi);
+        let g1 = self.g1(wo);
+        let d = self.d(wm);
+        let dot_om = wo.0.dot(&wm.0).abs().max(EPSILON);
+        let cos_theta_o = wo.cos_theta().abs().max(EPSILON);
+        let d_pdf = g1 / cos_theta_o * d * dot_om;
+        d_pdf / (4.0 * dot_om)
+    }
+
+    fn sample(self, wo: &Outgoing, u: UniformSample2D) -> Option<Incoming> {
+        let wm = self.sample_wm(wo, u);
+        let wi = LocalVector(reflect_vector(&wo.0, &wm.0));
+        wo.same_hemisphere(&wi).then_some(wi)
+    }
+
+    // Monte Carlo estimate of the directional albedo E(μ) of a perfect
+    // reflector, i.e. the energy left after a single scattering event. With
+    // visible normal sampling the weight reduces to G2/G1.
+    pub fn directional_albedo(self, cos_theta: f32, sample_count: u32) -> f32 {
+        let cos_theta = cos_theta.clamp(EPSILON, 1.0);
+        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+        let wo = LocalVector(vector![sin_theta, cos_theta, 0.0]);
+        let g1 = self.g1(&wo);
+        let mut sum = 0.0;
+        for sample_index in 0..sample_count {
+            let u = (
+                sobol_burley::sample(sample_index, 0, 0),
+                sobol_burley::sample(sample_index, 1, 0),
+            );
+            if let Some(wi) = self.sample(&wo, u) {
+                sum += self.g(&wo, &wi) / g1;
+            }
+        }
+        sum / sample_count as f32
+    }
+
+    // Bilinear lookup into the tabulated directional albedo.
+    fn albedo(self, cos_theta: f32) -> f32 {
+        let max_index = (GGX_ALBEDO_SIZE - 1) as f32;
+        let x = self.roughness.clamp(0.0, 1.0) * max_index;
+        let y = cos_theta.abs().clamp(0.0, 1.0) * max_index;
+        let x0 = (x as usize).min(GGX_ALBEDO_SIZE - 2);
+        let y0 = (y as usize).min(GGX_ALBEDO_SIZE - 2);
+        let tx = x - x0 as f32;
+        let ty = y - y0 as f32;
+        let table = &ggx_albedo::GGX_ALBEDO;
+        let e0 = (1.0 - ty) * table[x0][y0] + ty * table[x0][y0 + 1];
+        let e1 = (1.0 - ty) * table[x0 + 1][y0] + ty * table[x0 + 1][y0 + 1];
+        (1.0 - tx) * e0 + tx * e1
+    }
+
+    // Cosine-weighted hemispherical average of the directional albedo.
+    fn average_albedo(self) -> f32 {
+        let max_index = (GGX_ALBEDO_SIZE - 1) as f32;
+        let x = self.roughness.clamp(0.0, 1.0) * max_index;
+        let x0 = (x as usize).min(GGX_ALBEDO_SIZE - 2);
+        let tx = x - x0 as f32;
+        let table = &ggx_albedo::GGX_AVERAGE_ALBEDO;
+        (1.0 - tx) * table[x0] + tx * table[x0 + 1]
+    }
+
+    // Kulla-Conty lobe that restores the energy lost to multiple scattering
+    // between microfacets. `f_avg` is the hemispherical average of the
+    // Fresnel term, which tints the restored energy for colored conductors.
+    fn multiple_scattering(self, wo: &Outgoing, wi: &Incoming, f_avg: ColorRgb) -> ColorRgb {
+        if !wo.same_hemisphere(wi) {
+            return ColorRgb::BLACK;
+        }
+        let e_avg = self.average_albedo();
+        if e_avg >= 1.0 {
+            return ColorRgb::BLACK;
+        }
+        let e_o = self.albedo(wo.cos_theta());
+        let e_i = self.albedo(wi.cos_theta());
+        let f_ms = (1.0 - e_o) * (1.0 - e_i) / (PI * (1.0 - e_avg));
+        let fresnel = |f_avg: f32| f_avg * f_avg * e_avg / (1.0 - f_avg * (1.0 - e_avg));
+        ColorRgb::new(fresnel(f_avg.r()), fresnel(f_avg.g()), fresnel(f_avg.b())) * f_ms
+    }
+}
+
+// Directional albedo tables for `ggx_albedo.rs`, indexed by roughness and
+// cos(θ), both sampled uniformly over [0,1].
+pub fn ggx_albedo_tables(
+    sample_count: u32,
+) -> (
+    [[f32; GGX_ALBEDO_SIZE]; GGX_ALBEDO_SIZE],
+    [f32; GGX_ALBEDO_SIZE],
+) {
+    let max_index = (GGX_ALBEDO_SIZE - 1) as f32;
+    let mut albedo = [[0.0; GGX_ALBEDO_SIZE]; GGX_ALBEDO_SIZE];
+    let mut average_albedo = [0.0; GGX_ALBEDO_SIZE];
+    for (roughness_index, row) in albedo.iter_mut().enumerate() {
+        let ggx = Ggx::new(roughness_index as f32 / max_index, 0.0);
+        for (cos_theta_index, value) in row.iter_mut().enumerate() {
+            *value = ggx.directional_albedo(cos_theta_index as f32 / max_index, sample_count);
+        }
+
+        // E_avg = 2 ∫ E(μ) μ dμ, with the trapezoidal rule.
+        let mut sum = 0.0;
+        for cos_theta_index in 0..GGX_ALBEDO_SIZE - 1 {
+            let mu_0 = cos_theta_index as f32 / max_index;
+            let mu_1 = (cos_theta_index + 1) as f32 / max_index;
+            let f_0 = row[cos_theta_index] * mu_0;
+            let f_1 = row[cos_theta_index + 1] * mu_1;
+            sum += 0.5 * (f_0 + f_1) * (mu_1 - mu_0);
+        }
+        average_albedo[roughness_index] = (2.0 * sum).min(1.0);
+    }
+    (albedo, average_albedo)
+}
+
+//
+// Fresnel - Thin film
+//
+
+//
+// Sources:
+//
+// A Practical Extension to Microfacet Theory for the Modeling of Varying Iridescence
+// Laurent Belcour and Pascal Barla, 2017
+// https://belcour.github.io/blog/research/publication/2017/05/01/brdf-thin-film.html
+//
+// KHR_materials_iridescence
+// The Khronos Group, 2022
+// https://github.com/KhronosGroup/glTF/tree/main/extensions/2.0/Khronos/KHR_materials_iridescence
+//
+
+// A thin dielectric film on top of the specular layer. Light reflected by the
+// top and the bottom of the film interferes, which tints the Fresnel term
+// depending on the film thickness and the viewing angle. `weight` blends
+// between the plain and the iridescent Fresnel term, and `thickness` is in
+// nanometers. In spectral mode the interference is evaluated exactly at the
+// sampled wavelengths, otherwise it is integrated against the color matching
+// functions with a Gaussian fit, in the primaries of the working space.
+#[derive(Clone, Copy, Debug)]
+pub struct ThinFilm {
+    pub weight: f32,
+    pub ior: f32,
+    pub thickness: f32,
+    pub wavelengths: Option<[f32; 3]>,
+    pub working_from_srgb: Mat3,
+}
+
+impl Default for ThinFilm {
+    fn default() -> Self {
+        Self {
+            weight: 0.0,
+            ior: 1.3,
+            thickness: 400.0,
+            wavelengths: None,
+            working_from_srgb: Mat3::identity(),
+        }
+    }
+}
+
+impl ThinFilm {
+    // Blends the Fresnel term `fresnel` of the base layer with the iridescent
+    // one. The base layer is described by its reflectance at normal incidence.
+    fn fresnel(self, cos_theta: f32, f0: ColorRgb, fresnel: ColorRgb) -> ColorRgb {
+        if self.weight <= 0.0 {
+            return fresnel;
+        }
+        let iridescence = self.airy_reflectance(cos_theta.clamp(0.0, 1.0), f0);
+        lerp_color(&fresnel, &iridescence, self.weight)
+    }
+
+    fn airy_reflectance(self, cos_theta_1: f32, f0: ColorRgb) -> ColorRgb {
+        let ior_to_f0 = |ior_t: f32, ior_i: f32| ((ior_t - ior_i) / (ior_t + ior_i)).powi(2);
+        let f0_to_ior = |f0: f32| {
+            let sqrt_f0 = f0.clamp(0.0, 0.9999).sqrt();
+            (1.0 + sqrt_f0) / (1.0 - sqrt_f0)
+        };
+        let schlick = |f0: f32, cos_theta: f32| f0 + (1.0 - f0) * (1.0 - cos_theta).powi(5);
+
+        // Vanishing films fade to the ior of air.
+        let t = (self.thickness / 30.0).clamp(0.0, 1.0);
+        let film_ior = 1.0 + (self.ior - 1.0) * t * t * (3.0 - 2.0 * t);
+
+        // Refraction into the film, with total internal reflection.
+        let sin2_theta_2 = (1.0 / film_ior).powi(2) * (1.0 - cos_theta_1 * cos_theta_1);
+        let cos2_theta_2 = 1.0 - sin2_theta_2;
+        if cos2_theta_2 < 0.0 {
+            return ColorRgb::WHITE;
+        }
+        let cos_theta_2 = cos2_theta_2.sqrt();
+
+        // First interface, air to film.
+        let r12 = schlick(ior_to_f0(film_ior, 1.0), cos_theta_1);
+        let t121 = 1.0 - r12;
+        let phi12 = if film_ior < 1.0 { PI } else { 0.0 };
+        let phi21 = PI - phi12;
+
+        // Optical path difference between the interfering rays.
+        let opd = 2.0 * film_ior * self.thickness * cos_theta_2;
+
+        // Second interface, film to base, per channel.
+        let channel = |f0: f32, channel_index: usize| {
+            let base_ior = f0_to_ior(f0);
+            let r23 = schlick(ior_to_f0(base_ior, film_ior), cos_theta_2);
+            let phi23 = if base_ior < film_ior { PI } else { 0.0 };
+            let phi = phi21 + phi23;
+
+            let r123 = (r12 * r23).clamp(1e-5, 0.9999);
+            let r = r123.sqrt();
+            let rs = t121 * t121 * r23 / (1.0 - r123);
+            let c0 = r12 + rs;
+            let cm = rs - t121;
+            let reflectance = if let Some(wavelengths) = self.wavelengths {
+                // Sum of all orders m ≥ 1 of 2 r^m cos(m x) in closed form.
+                let x = TAU * opd / wavelengths[channel_index] + phi;
+                let sum = (r * x.cos() - r * r) / (1.0 - 2.0 * r * x.cos() + r * r);
+                c0 + 2.0 * cm * sum
+            } else {
+                let mut reflectance = c0;
+                let mut cm = cm;
+                for m in 1..=2 {
+                    cm *= r;
+                    let m = m as f32;
+                    let sensitivity: [f32; 3] = thin_film_sensitivity(m * opd, m * phi)
+                        .transform(&self.working_from_srgb)
+                        .into();
+                    reflectance += cm * 2.0 * sensitivity[channel_index];
+                }
+                reflectance
+            };
+            reflectance.max(0.0)
+        };
+        ColorRgb::new(channel(f0.r(), 0), channel(f0.g(), 1), channel(f0.b(), 2))
+    }
+}
+
+// Fourier transform of the color matching functions, fitted with Gaussians
+// and converted to linear sRGB.
+fn thin_film_sensitivity(opd: f32, shift: f32) -> ColorRgb {
+    let phase = TAU * opd * 1.0e-9;
+    let gaussian = |val: f32, pos: f32, var: f32| {
+        val * (TAU * var).sqrt() * (pos * phase + shift).cos() * (-phase * phase * var).exp()
+    };
+    let x =
+        gaussian(5.4856e-13, 1.6810e+06, 4.3278e+09) + gaussian(9.7470e-14, 2.2399e+06, 4.5282e+09);
+    let y = gaussian(4.4201e-13, 1.7953e+06, 9.3046e+09);
+    let z = gaussian(5.2481e-13, 2.2084e+06, 6.6121e+09);
+    spectrum::linear_srgb_from_xyz([x, y, z].map(|value| value / 1.0685e-7))
+}
+
+//
+// BxDF - Disney Specular
+//
+
+#[derive(Clone, Copy, Debug)]
+pub struct DisneySpecularParams {
+    pub base_color: ColorRgb,
+    pub metallic: f32,
+    pub specular: f32,
+    pub specular_tint: f32,
+    pub roughness: f32,
+    pub anisotropic: f32,
+    pub thin_film: ThinFilm,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct DisneySpecular {
+    specular_color: ColorRgb,
+    ggx: Ggx,
+    thin_film: ThinFilm,
+}
+
+impl DisneySpecular {
+    pub fn new(p: &DisneySpecularParams) -> Self {
+        assert_range!(p.metallic, 0.0, 1.0);
+        assert_range!(p.specular, 0.0, 1.0);
+        assert_range!(p.specular_tint, 0.0, 1.0);
+        assert_range!(p.roughness, 0.0, 1.0);
+        assert_range!(p.anisotropic, 0.0, 1.0);
+
+        let specular_color = {
+            let luminance = p.base_color.luminance();
+            let tint_color = if luminance > 0.0 {
+                p.base_color / luminance
+            } else {
+                ColorRgb::WHITE
+            };
+            let metallic_color =
+                p.specular * 0.08 * lerp_color(&ColorRgb::WHITE, &tint_color, p.specular_tint);
+            lerp_color(&metallic_color, &p.base_color, p.metallic)
+        };
+
+        Self {
+            specular_color,
+            ggx: Ggx::new(p.roughness, p.anisotropic),
+            thin_film: p.thin_film,
+        }
+    }
+
     fn fresnel(&self, wo: &Outgoing, wm: &MicrosurfaceNormal) -> ColorRgb {
         let dot_om = wo.0.dot(&wm.0);
         let fresnel = (1.0 - dot_om).clamp(0.0, 1.0).powi(5);
-        lerp_color(&self.specular_color, &ColorRgb::WHITE, fresnel)
+        let fresnel = lerp_color(&self.specular_color, &ColorRgb::WHITE, fresnel);
+        self.thin_film.fresnel(dot_om, self.specular_color, fresnel)
     }
 }
 
@@ -476,34 +885,18 @@ impl Bxdf for DisneySpecular {
     }
 
     fn eval(&self, wo: &Outgoing, wi: &Incoming) -> Reflectance {
-        // Angles.
-        let cos_theta_i = wi.cos_theta().abs().max(EPSILON);
-        let cos_theta_o = wo.cos_theta().abs().max(EPSILON);
-
-        // Cook-Torrance microfacet model.
         let wm = &wo.microsurface_normal(wi);
-        let d = self.ggx_d(wm);
-        let g = self.ggx_g(wo, wi);
-        let f = self.fresnel(wo, wm);
-        d * g * f / (4.0 * cos_theta_i * cos_theta_o)
+        // The average of Schlick's approximation is F0 + (1 - F0) / 21.
+        let f_avg = lerp_color(&self.specular_color, &ColorRgb::WHITE, 1.0 / 21.0);
+        self.ggx.eval(wo, wi) * self.fresnel(wo, wm) + self.ggx.multiple_scattering(wo, wi, f_avg)
     }
 
     fn pdf(&self, wo: &Outgoing, wi: &Incoming) -> Pdf {
-        let wm = &wo.microsurface_normal(wi);
-        let g1 = self.ggx_g1(wo);
-        let d = self.ggx_d(wm);
-        let dot_om = wo.0.dot(&wm.0).abs().max(EPSILON);
-        let cos_theta_o = wo.cos_theta().abs().max(EPSILON);
-        let d_pdf = g1 / cos_theta_o * d * dot_om;
-        d_pdf / (4.0 * dot_om)
+        self.ggx.pdf(wo, wi)
     }
 
     fn sample(&self, wo: &Outgoing, u: UniformSample2D) -> Option<Sample> {
-        let wm = self.ggx_sample_wm(wo, u);
-        let wi = LocalVector(reflect_vector(&wo.0, &wm.0));
-        if !wo.same_hemisphere(&wi) {
-            return None;
-        }
+        let wi = self.ggx.sample(wo, u)?;
         let pdf = self.pdf(wo, &wi);
         if pdf > EPSILON {
             Some(Sample {
@@ -582,3 +975,855 @@ impl Bxdf for DisneySheen {
         }
     }
 }
+
+//
+// BxDF - Charlie Sheen
+//
+
+//
+// Sources:
+//
+// Production Friendly Microfacet Sheen BRDF
+// Alejandro Conty Estevez and Christopher Kulla, 2017
+// https://blog.selfshadow.com/publications/s2017-shading-course/imageworks/s2017_pbs_imageworks_sheen.pdf
+//
+// KHR_materials_sheen
+// The Khronos Group, 2022
+// https://github.com/KhronosGroup/glTF/tree/main/extensions/2.0/Khronos/KHR_materials_sheen
+//
+
+// Resolution of the tabulated directional albedo in `sheen_albedo.rs`, over
+// both sheen roughness and cos(θ).
+pub const SHEEN_ALBEDO_SIZE: usize = 32;
+
+// Fitted coefficients of the masking function at roughness 0 and 1.
+const CHARLIE_LAMBDA_0: [f32; 5] = [25.3245, 3.324_35, 0.168_01, -1.273_93, -4.859_67];
+const CHARLIE_LAMBDA_1: [f32; 5] = [21.5473, 3.829_87, 0.198_23, -1.977_60, -4.320_54];
+
+#[derive(Clone, Copy, Debug)]
+pub struct CharlieSheenParams {
+    pub hemisphere: HemisphereSampler,
+    pub sheen_color: ColorRgb,
+    pub sheen_roughness: f32,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct CharlieSheen {
+    hemisphere: HemisphereSampler,
+    sheen_color: ColorRgb,
+    roughness: f32,
+    alpha: f32,
+}
+
+impl CharlieSheen {
+    pub fn new(p: &CharlieSheenParams) -> Self {
+        assert_range!(p.sheen_roughness, 0.0, 1.0);
+
+        Self {
+            hemisphere: p.hemisphere,
+            sheen_color: p.sheen_color,
+            roughness: p.sheen_roughness,
+            alpha: p.sheen_roughness.powi(2).max(0.01),
+        }
+    }
+
+    // Fraction of the energy the base layer loses to the sheen, when light
+    // leaves at `cos_theta_o` and arrives at `cos_theta_i`.
+    fn albedo_scaling(&self, cos_theta_o: f32, cos_theta_i: f32) -> f32 {
+        let sheen = self.sheen_color.max_element();
+        (1.0 - sheen * self.albedo(cos_theta_o)).min(1.0 - sheen * self.albedo(cos_theta_i))
+    }
+
+    // Bilinear lookup into the tabulated directional albedo of a white sheen.
+    fn albedo(&self, cos_theta: f32) -> f32 {
+        let max_index = (SHEEN_ALBEDO_SIZE - 1) as f32;
+        let x = self.roughness.clamp(0.0, 1.0) * max_index;
+        let y = cos_theta.abs().clamp(0.0, 1.0) * max_index;
+        let x0 = (x as usize).min(SHEEN_ALBEDO_SIZE - 2);
+        let y0 = (y as usize).min(SHEEN_ALBEDO_SIZE - 2);
+        let tx = x - x0 as f32;
+        let ty = y - y0 as f32;
+        let table = &sheen_albedo::SHEEN_ALBEDO;
+        let e0 = (1.0 - ty) * table[x0][y0] + ty * table[x0][y0 + 1];
+        let e1 = (1.0 - ty) * table[x0 + 1][y0] + ty * table[x0 + 1][y0 + 1];
+        (1.0 - tx) * e0 + tx * e1
+    }
+
+    // Monte Carlo estimate of the directional albedo of a white sheen.
+    fn directional_albedo(&self, cos_theta: f32, sample_count: u32) -> f32 {
+        let white = Self {
+            sheen_color: ColorRgb::WHITE,
+            ..*self
+        };
+        let cos_theta = cos_theta.max(1e-4);
+        let wo = LocalVector(vector![
+            (1.0 - cos_theta * cos_theta).sqrt(),
+            cos_theta,
+            0.0
+        ]);
+        let mut sum = 0.0;
+        for sample_index in 0..sample_count {
+            let u = (
+                sobol_burley::sample(sample_index, 0, 0),
+                sobol_burley::sample(sample_index, 1, 0),
+            );
+            if let Some(sample) = white.sample(&wo, u) {
+                sum += sample.r.r() * sample.wi.cos_theta() / sample.pdf;
+            }
+        }
+        sum / sample_count as f32
+    }
+
+    // Inverted Gaussian-like distribution of fibers, which favors microfacets
+    // that are perpendicular to the surface normal.
+    fn d(&self, wm: &MicrosurfaceNormal) -> f32 {
+        let inv_alpha = 1.0 / self.alpha;
+        (2.0 + inv_alpha) * wm.sin_theta().powf(inv_alpha) / TAU
+    }
+
+    fn lambda(&self, w: &LocalVector) -> f32 {
+        let cos_theta = w.cos_theta().abs();
+        let t = (1.0 - self.alpha).powi(2);
+        let [a, b, c, d, e] = std::array::from_fn::<_, 5, _>(|index| {
+            t * CHARLIE_LAMBDA_0[index] + (1.0 - t) * CHARLIE_LAMBDA_1[index]
+        });
+        let l = |x: f32| a / (1.0 + b * x.powf(c)) + d * x + e;
+        let lambda = if cos_theta < 0.5 {
+            l(cos_theta).exp()
+        } else {
+            (2.0 * l(0.5) - l(1.0 - cos_theta)).exp()
+        };
+
+        // Soften the shadowing terminator.
+        lambda.powf(1.0 + 2.0 * (1.0 - cos_theta).powi(8))
+    }
+}
+
+impl Bxdf for CharlieSheen {
+    fn model(&self) -> Model {
+        Model::CharlieSheen
+    }
+
+    fn eval(&self, wo: &Outgoing, wi: &Incoming) -> Reflectance {
+        if !wo.same_hemisphere(wi) {
+            return ColorRgb::BLACK;
+        }
+
+        // Angles.
+        let cos_theta_i = wi.cos_theta().abs().max(EPSILON);
+        let cos_theta_o = wo.cos_theta().abs().max(EPSILON);
+
+        let wm = &wo.microsurface_normal(wi);
+        let d = self.d(wm);
+        let g = 1.0 / (1.0 + self.lambda(wo) + self.lambda(wi));
+        self.sheen_color * d * g / (4.0 * cos_theta_i * cos_theta_o)
+    }
+
+    fn pdf(&self, _: &Outgoing, wi: &Incoming) -> Pdf {
+        self.hemisphere.pdf(wi.cos_theta().abs())
+    }
+
+    fn sample(&self, wo: &Outgoing, u: UniformSample2D) -> Option<Sample> {
+        let wi = LocalVector(*self.hemisphere.sample(u.0, u.1));
+        let pdf = self.pdf(wo, &wi);
+        if pdf > EPSILON {
+            Some(Sample {
+                r: self.eval(wo, &wi),
+                wi,
+                pdf,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+// Directional albedo table for `sheen_albedo.rs`, indexed by sheen roughness
+// and cos(θ), both sampled uniformly over [0,1].
+pub fn sheen_albedo_table(sample_count: u32) -> [[f32; SHEEN_ALBEDO_SIZE]; SHEEN_ALBEDO_SIZE] {
+    let max_index = (SHEEN_ALBEDO_SIZE - 1) as f32;
+    let mut albedo = [[0.0; SHEEN_ALBEDO_SIZE]; SHEEN_ALBEDO_SIZE];
+    for (roughness_index, row) in albedo.iter_mut().enumerate() {
+        let sheen = CharlieSheen::new(&CharlieSheenParams {
+            hemisphere: HemisphereSampler::Cosine,
+            sheen_color: ColorRgb::WHITE,
+            sheen_roughness: roughness_index as f32 / max_index,
+        });
+        for (cos_theta_index, value) in row.iter_mut().enumerate() {
+            *value = sheen
+                .directional_albedo(cos_theta_index as f32 / max_index, sample_count)
+                .min(1.0);
+        }
+    }
+    albedo
+}
+
+//
+// BxDF - Disney
+//
+
+// Combination of the Disney diffuse, specular and sheen lobes. Sampling picks
+// either the diffuse or the specular lobe, and the reflectance is the sum of
+// all lobes. The Charlie sheen is an alternative to the Disney sheen, each is
+// disabled with a zero sheen or sheen color. The Charlie sheen sits on top of
+// the other lobes, which are scaled down by the energy it reflects.
+
+#[derive(Clone, Copy, Debug)]
+pub struct DisneyParams {
+    pub hemisphere: HemisphereSampler,
+    pub base_color: ColorRgb,
+    pub metallic: f32,
+    pub specular: f32,
+    pub specular_tint: f32,
+    pub roughness: f32,
+    pub anisotropic: f32,
+    pub sheen: f32,
+    pub sheen_tint: f32,
+    pub sheen_color: ColorRgb,
+    pub sheen_roughness: f32,
+    pub thin_film: ThinFilm,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Disney {
+    diffuse: DisneyDiffuse,
+    specular: DisneySpecular,
+    sheen: DisneySheen,
+    charlie_sheen: Option<CharlieSheen>,
+    diffuse_weight: f32,
+}
+
+impl Disney {
+    pub fn new(p: &DisneyParams) -> Self {
+        Self {
+            diffuse: DisneyDiffuse::new(&DisneyDiffuseParams {
+                hemisphere: p.hemisphere,
+                base_color: p.base_color,
+                roughness: p.roughness,
+            }),
+            specular: DisneySpecular::new(&DisneySpecularParams {
+                base_color: p.base_color,
+                metallic: p.metallic,
+                specular: p.specular,
+                specular_tint: p.specular_tint,
+                roughness: p.roughness,
+                anisotropic: p.anisotropic,
+                thin_film: p.thin_film,
+            }),
+            sheen: DisneySheen::new(&DisneySheenParams {
+                hemisphere: p.hemisphere,
+                base_color: p.base_color,
+                sheen: p.sheen,
+                sheen_tint: p.sheen_tint,
+            }),
+            charlie_sheen: (p.sheen_color.max_element() > 0.0).then(|| {
+                CharlieSheen::new(&CharlieSheenParams {
+                    hemisphere: p.hemisphere,
+                    sheen_color: p.sheen_color,
+                    sheen_roughness: p.sheen_roughness,
+                })
+            }),
+            diffuse_weight: (1.0 - p.metallic) * (1.0 - p.specular),
+        }
+    }
+}
+
+impl Bxdf for Disney {
+    fn model(&self) -> Model {
+        Model::Disney
+    }
+
+    fn eval(&self, wo: &Outgoing, wi: &Incoming) -> Reflectance {
+        let r = self.diffuse.eval(wo, wi) + self.specular.eval(wo, wi) + self.sheen.eval(wo, wi);
+        if let Some(charlie_sheen) = &self.charlie_sheen {
+            let scaling = charlie_sheen.albedo_scaling(wo.cos_theta(), wi.cos_theta());
+            r * scaling + charlie_sheen.eval(wo, wi)
+        } else {
+            r
+        }
+    }
+
+    fn pdf(&self, wo: &Outgoing, wi: &Incoming) -> Pdf {
+        let diffuse_pdf = self.diffuse.pdf(wo, wi);
+        let specular_pdf = self.specular.pdf(wo, wi);
+        self.diffuse_weight * diffuse_pdf + (1.0 - self.diffuse_weight) * specular_pdf
+    }
+
+    fn sample(&self, wo: &Outgoing, u: UniformSample2D) -> Option<Sample> {
+        // Select the lobe with the first dimension, then stretch it back to
+        // [0,1) so it can be reused for sampling the lobe.
+        let weight = self.diffuse_weight;
+        let sample = if u.0 < weight {
+            let u0 = (u.0 / weight).min(1.0 - f32::EPSILON);
+            self.diffuse.sample(wo, (u0, u.1))?
+        } else {
+            let u0 = ((u.0 - weight) / (1.0 - weight)).min(1.0 - f32::EPSILON);
+            self.specular.sample(wo, (u0, u.1))?
+        };
+        let wi = sample.wi;
+        let pdf = self.pdf(wo, &wi);
+        if pdf > 0.0 {
+            Some(Sample {
+                r: self.eval(wo, &wi),
+                wi,
+                pdf,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+//
+// BxDF - Conductor
+//
+
+//
+// Sources:
+//
+// Physically Based Rendering: From Theory to Implementation, 3rd edition
+// Matt Pharr, Wenzel Jakob, Greg Humphreys, 2016
+// https://pbr-book.org/3ed-2018/Reflection_Models/Specular_Reflection_and_Transmission
+//
+// Refractive index database
+// Mikhail Polyanskiy
+// https://refractiveindex.info
+//
+
+// Measured metals. The complex index of refraction is given at the dominant
+// wavelengths of the sRGB primaries, and interpolated between them for the
+// spectral mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ConductorPreset {
+    #[default]
+    Gold,
+    Silver,
+    Copper,
+    Aluminium,
+    Iron,
+    Chromium,
+    Titanium,
+    Platinum,
+}
+
+const CONDUCTOR_WAVELENGTHS: [f32; 3] = [611.0, 549.0, 464.0];
+
+impl ConductorPreset {
+    pub const ALL: [Self; 8] = [
+        Self::Gold,
+        Self::Silver,
+        Self::Copper,
+        Self::Aluminium,
+        Self::Iron,
+        Self::Chromium,
+        Self::Titanium,
+        Self::Platinum,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Gold => "gold",
+            Self::Silver => "silver",
+            Self::Copper => "copper",
+            Self::Aluminium => "aluminium",
+            Self::Iron => "iron",
+            Self::Chromium => "chromium",
+            Self::Titanium => "titanium",
+            Self::Platinum => "platinum",
+        }
+    }
+
+    // Real part `eta` and imaginary part `k` of the index of refraction.
+    pub fn ior(self) -> (ColorRgb, ColorRgb) {
+        let (eta, k) = match self {
+            Self::Gold => (
+                [0.143_119, 0.374_957, 1.442_48],
+                [3.983_16, 2.385_72, 1.603_22],
+            ),
+            Self::Silver => (
+                [0.155_265, 0.116_723, 0.138_342],
+                [4.828_35, 3.122_25, 2.146_96],
+            ),
+            Self::Copper => (
+                [0.200_438, 0.924_033, 1.102_21],
+                [3.912_95, 2.452_85, 2.142_19],
+            ),
+            Self::Aluminium => (
+                [1.657_46, 0.880_369, 0.521_229],
+                [9.223_87, 6.269_52, 4.837],
+            ),
+            Self::Iron => ([2.9114, 2.9497, 2.5845], [3.0893, 2.9318, 2.7670]),
+            Self::Chromium => ([3.1071, 3.1812, 2.3230], [3.3314, 3.3291, 3.1350]),
+            Self::Titanium => ([2.7407, 2.5418, 2.2670], [3.8143, 3.4345, 3.0385]),
+            Self::Platinum => ([2.3757, 2.0847, 1.8453], [4.2655, 3.7153, 3.1365]),
+        };
+        (
+            ColorRgb::new(eta[0], eta[1], eta[2]),
+            ColorRgb::new(k[0], k[1], k[2]),
+        )
+    }
+
+    // Reflectance at normal incidence.
+    pub fn reflectance(self) -> ColorRgb {
+        let (eta, k) = self.ior();
+        ColorRgb::new(
+            fresnel_conductor(1.0, eta.r(), k.r()),
+            fresnel_conductor(1.0, eta.g(), k.g()),
+            fresnel_conductor(1.0, eta.b(), k.b()),
+        )
+    }
+
+    // Index of refraction at wavelengths in nanometers, clamped outside of
+    // the tabulated range.
+    pub fn spectral_ior(self, lambdas: [f32; 3]) -> (ColorRgb, ColorRgb) {
+        let (eta, k) = self.ior();
+        let lerp = |values: ColorRgb, lambda: f32| {
+            let [r, g, b] = CONDUCTOR_WAVELENGTHS;
+            if lambda >= r {
+                values.r()
+            } else if lambda >= g {
+                let t = (lambda - g) / (r - g);
+                (1.0 - t) * values.g() + t * values.r()
+            } else if lambda >= b {
+                let t = (lambda - b) / (g - b);
+                (1.0 - t) * values.b() + t * values.g()
+            } else {
+                values.b()
+            }
+        };
+        let [eta_0, eta_1, eta_2] = lambdas.map(|lambda| lerp(eta, lambda));
+        let [k_0, k_1, k_2] = lambdas.map(|lambda| lerp(k, lambda));
+        (
+            ColorRgb::new(eta_0, eta_1, eta_2),
+            ColorRgb::new(k_0, k_1, k_2),
+        )
+    }
+}
+
+impl std::fmt::Display for ConductorPreset {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+// `eta` and `k` are given per sRGB channel. The reflectance is converted to
+// the working space with `working_from_srgb`.
+pub struct ConductorParams {
+    pub eta: ColorRgb,
+    pub k: ColorRgb,
+    pub roughness: f32,
+    pub anisotropic: f32,
+    pub thin_film: ThinFilm,
+    pub working_from_srgb: Mat3,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Conductor {
+    eta: ColorRgb,
+    k: ColorRgb,
+    f0: ColorRgb,
+    f_avg: ColorRgb,
+    ggx: Ggx,
+    thin_film: ThinFilm,
+    working_from_srgb: Mat3,
+}
+
+impl Conductor {
+    pub fn new(p: &ConductorParams) -> Self {
+        assert_range!(p.roughness, 0.0, 1.0);
+        assert_range!(p.anisotropic, 0.0, 1.0);
+
+        // Approximate the Fresnel average with the one of Schlick's
+        // approximation at the same normal incidence reflectance.
+        let f0 = ColorRgb::new(
+            fresnel_conductor(1.0, p.eta.r(), p.k.r()),
+            fresnel_conductor(1.0, p.eta.g(), p.k.g()),
+            fresnel_conductor(1.0, p.eta.b(), p.k.b()),
+        );
+
+        Self {
+            eta: p.eta,
+            k: p.k,
+            f0,
+            f_avg: lerp_color(&f0, &ColorRgb::WHITE, 1.0 / 21.0),
+            ggx: Ggx::new(p.roughness, p.anisotropic),
+            // Evaluated in sRGB along with the rest, and converted as a whole.
+            thin_film: ThinFilm {
+                working_from_srgb: Mat3::identity(),
+                ..p.thin_film
+            },
+            working_from_srgb: p.working_from_srgb,
+        }
+    }
+}
+
+impl Bxdf for Conductor {
+    fn model(&self) -> Model {
+        Model::Conductor
+    }
+
+    fn eval(&self, wo: &Outgoing, wi: &Incoming) -> Reflectance {
+        let wm = &wo.microsurface_normal(wi);
+        let cos_theta = wo.0.dot(&wm.0).clamp(0.0, 1.0);
+        let f = ColorRgb::new(
+            fresnel_conductor(cos_theta, self.eta.r(), self.k.r()),
+            fresnel_conductor(cos_theta, self.eta.g(), self.k.g()),
+            fresnel_conductor(cos_theta, self.eta.b(), self.k.b()),
+        );
+        let f = self.thin_film.fresnel(cos_theta, self.f0, f);
+        let r = self.ggx.eval(wo, wi) * f + self.ggx.multiple_scattering(wo, wi, self.f_avg);
+        r.transform(&self.working_from_srgb)
+    }
+
+    fn pdf(&self, wo: &Outgoing, wi: &Incoming) -> Pdf {
+        self.ggx.pdf(wo, wi)
+    }
+
+    fn sample(&self, wo: &Outgoing, u: UniformSample2D) -> Option<Sample> {
+        let wi = self.ggx.sample(wo, u)?;
+        let pdf = self.pdf(wo, &wi);
+        if pdf > EPSILON {
+            Some(Sample {
+                r: self.eval(wo, &wi),
+                wi,
+                pdf,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+// Fresnel reflectance of unpolarized light at the boundary between air and a
+// conductor with the complex index of refraction `eta + ik`.
+fn fresnel_conductor(cos_theta: f32, eta: f32, k: f32) -> f32 {
+    let cos2_theta = cos_theta * cos_theta;
+    let sin2_theta = 1.0 - cos2_theta;
+    let eta2 = eta * eta;
+    let k2 = k * k;
+
+    let t0 = eta2 - k2 - sin2_theta;
+    let a2_plus_b2 = (t0 * t0 + 4.0 * eta2 * k2).sqrt();
+    let t1 = a2_plus_b2 + cos2_theta;
+    let a = (0.5 * (a2_plus_b2 + t0)).max(0.0).sqrt();
+    let t2 = 2.0 * cos_theta * a;
+    let rs = (t1 - t2) / (t1 + t2);
+
+    let t3 = cos2_theta * a2_plus_b2 + sin2_theta * sin2_theta;
+    let t4 = t2 * sin2_theta;
+    let rp = rs * (t3 - t4) / (t3 + t4);
+
+    0.5 * (rp + rs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conductor_fresnel() {
+        // At normal incidence the reflectance has a closed form, and every
+        // conductor becomes a perfect mirror at grazing angles.
+        for preset in ConductorPreset::ALL {
+            let (eta, k) = preset.ior();
+            for (eta, k) in [(eta.r(), k.r()), (eta.g(), k.g()), (eta.b(), k.b())] {
+                let f0 = ((eta - 1.0).powi(2) + k * k) / ((eta + 1.0).powi(2) + k * k);
+                assert_abs_diff_eq!(fresnel_conductor(1.0, eta, k), f0, epsilon = 1e-5);
+                assert_abs_diff_eq!(fresnel_conductor(0.0, eta, k), 1.0, epsilon = 1e-5);
+                let f = fresnel_conductor(0.5, eta, k);
+                assert!((0.0..=1.0).contains(&f), "preset={preset}, f={f}");
+            }
+        }
+    }
+
+    #[test]
+    fn conductor_working_space() {
+        // Gold in ACEScg, converted back to sRGB, has the color of gold in
+        // sRGB, with and without iridescence.
+        let srgb_from_acescg = ColorSpace::conversion(ColorSpace::AcesCg, ColorSpace::Srgb);
+        let acescg_from_srgb = ColorSpace::conversion(ColorSpace::Srgb, ColorSpace::AcesCg);
+        let (eta, k) = ConductorPreset::Gold.ior();
+        let wo = LocalVector(vector![0.6, 0.8, 0.0]);
+        let wi = LocalVector(vector![-0.28, 0.96, 0.0]);
+        for weight in [0.0, 1.0] {
+            let conductor = |working_from_srgb: Mat3| {
+                Conductor::new(&ConductorParams {
+                    eta,
+                    k,
+                    roughness: 0.5,
+                    anisotropic: 0.0,
+                    thin_film: ThinFilm {
+                        weight,
+                        working_from_srgb,
+                        ..ThinFilm::default()
+                    },
+                    working_from_srgb,
+                })
+            };
+            let srgb = conductor(Mat3::identity()).eval(&wo, &wi);
+            let acescg = conductor(acescg_from_srgb).eval(&wo, &wi);
+            let converted = acescg.transform(&srgb_from_acescg);
+            assert_abs_diff_eq!(converted.r(), srgb.r(), epsilon = 1e-4);
+            assert_abs_diff_eq!(converted.g(), srgb.g(), epsilon = 1e-4);
+            assert_abs_diff_eq!(converted.b(), srgb.b(), epsilon = 1e-4);
+            assert!(acescg.r() > acescg.b());
+        }
+    }
+
+    #[test]
+    fn ggx_white_furnace() {
+        // A white metal with multiple scattering compensation should reflect
+        // all incoming energy, regardless of roughness.
+        for roughness in [0.25, 0.5, 0.75, 1.0] {
+            let bxdf = DisneySpecular::new(&DisneySpecularParams {
+                base_color: ColorRgb::WHITE,
+                metallic: 1.0,
+                specular: 0.5,
+                specular_tint: 0.0,
+                roughness,
+                anisotropic: 0.0,
+                thin_film: ThinFilm::default(),
+            });
+            for cos_theta in [0.25_f32, 0.5, 1.0] {
+                let wo = LocalVector(vector![
+                    (1.0 - cos_theta * cos_theta).sqrt(),
+                    cos_theta,
+                    0.0
+                ]);
+                let sample_count = 1 << 14;
+                let mut albedo = 0.0;
+                for sample_index in 0..sample_count {
+                    let u = (
+                        sobol_burley::sample(sample_index, 0, 0),
+                        sobol_burley::sample(sample_index, 1, 0),
+                    );
+                    if let Some(sample) = bxdf.sample(&wo, u) {
+                        albedo += sample.r.mean() * sample.wi.cos_theta() / sample.pdf;
+                    }
+                }
+                albedo /= sample_count as f32;
+                assert_abs_diff_eq!(albedo, 1.0, epsilon = 0.02);
+            }
+        }
+    }
+
+    #[test]
+    fn ggx_smith_lambda() {
+        // Λ(ω) = (√(1 + α²tan²θ) - 1) / 2, with α = 1 at 45°.
+        let wo = LocalVector(vector![
+            std::f32::consts::FRAC_1_SQRT_2,
+            std::f32::consts::FRAC_1_SQRT_2,
+            0.0
+        ]);
+        assert_abs_diff_eq!(Ggx::new(1.0, 0.0).lambda(&wo), 0.207_107, epsilon = 1e-5);
+
+        // The masking function normalizes the distribution of visible normals,
+        // ∫ G1(ωo) max(0, ωo·ωm) D(ωm) dωm = cos θo.
+        let (theta_steps, phi_steps) = (512, 256);
+        let d_theta = 0.5 * PI / theta_steps as f32;
+        let d_phi = TAU / phi_steps as f32;
+        for (roughness, anisotropic) in [(0.5, 0.0), (0.75, 0.8), (1.0, 0.0)] {
+            let ggx = Ggx::new(roughness, anisotropic);
+            for (cos_theta, phi) in [(0.2_f32, 0.3_f32), (0.6, 1.2), (1.0, 0.0)] {
+                let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+                let wo = LocalVector(vector![
+                    sin_theta * phi.cos(),
+                    cos_theta,
+                    sin_theta * phi.sin()
+                ]);
+                let mut projected_area = 0.0;
+                for theta_index in 0..theta_steps {
+                    let theta = (theta_index as f32 + 0.5) * d_theta;
+                    let (sin_theta, cos_theta) = theta.sin_cos();
+                    for phi_index in 0..phi_steps {
+                        let phi = (phi_index as f32 + 0.5) * d_phi;
+                        let wm = LocalVector(vector![
+                            sin_theta * phi.cos(),
+                            cos_theta,
+                            sin_theta * phi.sin()
+                        ]);
+                        projected_area += wo.0.dot(&wm.0).max(0.0) * ggx.d(&wm) * sin_theta;
+                    }
+                }
+                projected_area *= ggx.g1(&wo) * d_theta * d_phi;
+                assert_abs_diff_eq!(projected_area, cos_theta, epsilon = 0.01);
+            }
+        }
+    }
+
+    #[test]
+    fn oren_nayar_white_furnace() {
+        // The energy-preserving Oren-Nayar should reflect all incoming energy
+        // for a white surface, regardless of roughness.
+        for roughness in [0.0, 0.5, 1.0] {
+            let bxdf = OrenNayar::new(&OrenNayarParams {
+                hemisphere: HemisphereSampler::Cosine,
+                base_color: ColorRgb::WHITE,
+                roughness,
+            });
+            for cos_theta in [0.25_f32, 0.5, 1.0] {
+                let wo = LocalVector(vector![
+                    (1.0 - cos_theta * cos_theta).sqrt(),
+                    cos_theta,
+                    0.0
+                ]);
+                let sample_count = 1 << 14;
+                let mut albedo = 0.0;
+                for sample_index in 0..sample_count {
+                    let u = (
+                        sobol_burley::sample(sample_index, 0, 0),
+                        sobol_burley::sample(sample_index, 1, 0),
+                    );
+                    if let Some(sample) = bxdf.sample(&wo, u) {
+                        albedo += sample.r.mean() * sample.wi.cos_theta() / sample.pdf;
+                    }
+                }
+                albedo /= sample_count as f32;
+                assert_abs_diff_eq!(albedo, 1.0, epsilon = 0.02);
+            }
+        }
+    }
+
+    #[test]
+    fn charlie_sheen_distribution() {
+        // The projected area of the microfacets must be the unit disc, and a
+        // white sheen must not reflect more energy than it receives.
+        for sheen_roughness in [0.25_f32, 0.5, 1.0] {
+            let bxdf = CharlieSheen::new(&CharlieSheenParams {
+                hemisphere: HemisphereSampler::Cosine,
+                sheen_color: ColorRgb::WHITE,
+                sheen_roughness,
+            });
+            let n = 512;
+            let mut projected_area = 0.0;
+            for i in 0..n {
+                for j in 0..n {
+                    let cos_theta = (i as f32 + 0.5) / n as f32;
+                    let phi = TAU * (j as f32 + 0.5) / n as f32;
+                    let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+                    let wm = LocalVector(vector![
+                        sin_theta * phi.cos(),
+                        cos_theta,
+                        sin_theta * phi.sin()
+                    ]);
+                    projected_area += bxdf.d(&wm) * cos_theta;
+                }
+            }
+            projected_area *= TAU / (n * n) as f32;
+            assert_abs_diff_eq!(projected_area, 1.0, epsilon = 0.01);
+
+            for cos_theta in [0.25_f32, 0.5, 1.0] {
+                let wo = LocalVector(vector![
+                    (1.0 - cos_theta * cos_theta).sqrt(),
+                    cos_theta,
+                    0.0
+                ]);
+                let sample_count = 1 << 14;
+                let mut albedo = 0.0;
+                for sample_index in 0..sample_count {
+                    let u = (
+                        sobol_burley::sample(sample_index, 0, 0),
+                        sobol_burley::sample(sample_index, 1, 0),
+                    );
+                    if let Some(sample) = bxdf.sample(&wo, u) {
+                        albedo += sample.r.mean() * sample.wi.cos_theta() / sample.pdf;
+                    }
+                }
+                albedo /= sample_count as f32;
+                assert!(albedo > 0.0 && albedo <= 1.0, "albedo={albedo}");
+            }
+        }
+    }
+
+    #[test]
+    fn disney_sheen_white_furnace() {
+        // A white sheen on top of a white base must not reflect more energy
+        // than the base alone. Disney diffuse itself is slightly above one at
+        // grazing angles, so the base is the reference instead of one.
+        let albedo = |bxdf: &Disney, cos_theta: f32| {
+            let wo = LocalVector(vector![
+                (1.0 - cos_theta * cos_theta).sqrt(),
+                cos_theta,
+                0.0
+            ]);
+            let sample_count = 1 << 14;
+            let mut albedo = 0.0;
+            for sample_index in 0..sample_count {
+                let u = (
+                    sobol_burley::sample(sample_index, 0, 0),
+                    sobol_burley::sample(sample_index, 1, 0),
+                );
+                if let Some(sample) = bxdf.sample(&wo, u) {
+                    albedo += sample.r.mean() * sample.wi.cos_theta() / sample.pdf;
+                }
+            }
+            albedo / sample_count as f32
+        };
+        for (roughness, sheen_roughness) in [(0.5, 0.3), (1.0, 0.5), (0.8, 1.0)] {
+            let disney = |sheen_color: ColorRgb| {
+                Disney::new(&DisneyParams {
+                    hemisphere: HemisphereSampler::Cosine,
+                    base_color: ColorRgb::WHITE,
+                    metallic: 0.0,
+                    specular: 0.0,
+                    specular_tint: 0.0,
+                    roughness,
+                    anisotropic: 0.0,
+                    sheen: 0.0,
+                    sheen_tint: 0.0,
+                    sheen_color,
+                    sheen_roughness,
+                    thin_film: ThinFilm::default(),
+                })
+            };
+            let base = disney(ColorRgb::BLACK);
+            let sheen = disney(ColorRgb::WHITE);
+            for cos_theta in [0.25_f32, 0.5, 1.0] {
+                let base_albedo = albedo(&base, cos_theta);
+                let sheen_albedo = albedo(&sheen, cos_theta);
+                assert!(
+                    sheen_albedo <= base_albedo + 0.01,
+                    "base={base_albedo}, sheen={sheen_albedo}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn thin_film_spectral() {
+        // The Gaussian fit in RGB mode should agree with integrating the exact
+        // spectral interference against the color matching functions.
+        let f0 = ColorRgb::new(0.04, 0.04, 0.04);
+        for thickness in [100.0, 250.0, 400.0, 700.0] {
+            for cos_theta in [0.5, 1.0] {
+                let thin_film = ThinFilm {
+                    weight: 1.0,
+                    thickness,
+                    ..ThinFilm::default()
+                };
+                let rgb = thin_film.airy_reflectance(cos_theta, f0);
+
+                let mut xyz = [0.0; 3];
+                let mut y_sum = 0.0;
+                for lambda in 380..=780 {
+                    let lambda = lambda as f32;
+                    let thin_film = ThinFilm {
+                        wavelengths: Some([lambda; 3]),
+                        ..thin_film
+                    };
+                    let r = thin_film.airy_reflectance(cos_theta, f0).r();
+                    let cmf = spectrum::cie_xyz(lambda);
+                    for (xyz, cmf) in xyz.iter_mut().zip(cmf) {
+                        *xyz += r * cmf;
+                    }
+                    y_sum += cmf[1];
+                }
+                let spectral = spectrum::linear_srgb_from_xyz(xyz.map(|value| value / y_sum));
+                assert_abs_diff_eq!(rgb.r(), spectral.r(), epsilon = 0.025);
+                assert_abs_diff_eq!(rgb.g(), spectral.g(), epsilon = 0.025);
+                assert_abs_diff_eq!(rgb.b(), spectral.b(), epsilon = 0.025);
+            }
+        }
+    }
+}
@@ -0,0 +1,302 @@
+use super::*;
+
+// Edge-avoiding À-Trous wavelet filter, guided by the first hit AOVs. The
+// radiance is divided by the albedo before filtering, so that texture detail
+// isn't blurred, and multiplied back afterwards. Each iteration applies a 5x5
+// B3 spline kernel with holes, doubling the step size every time. Neighbors
+// are weighted down by differences in normal, depth, albedo and luminance,
+// where the luminance is relative to the standard deviation of the estimate.
+// Pixels where nothing was hit are left untouched.
+
+//
+// Sources:
+//
+// Edge-Avoiding À-Trous Wavelet Transform for fast Global Illumination Filtering
+// Dammertz et al., 2010
+// https://jo.dreggn.org/home/2010_atrous.pdf
+//
+// Spatiotemporal Variance-Guided Filtering
+// Schied et al., 2017
+// https://research.nvidia.com/publication/2017-07_spatiotemporal-variance-guided-filtering-real-time-reconstruction-path-traced
+//
+
+const KERNEL: [f32; 5] = [1.0 / 16.0, 1.0 / 4.0, 3.0 / 8.0, 1.0 / 4.0, 1.0 / 16.0];
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DenoiserParams {
+    pub iterations: u32,
+    // Exponent of the cosine between normals.
+    pub sigma_normal: f32,
+    // Depth difference, relative to the local depth gradient.
+    pub sigma_depth: f32,
+    pub sigma_albedo: f32,
+    // Luminance difference, relative to the standard deviation.
+    pub sigma_luminance: f32,
+}
+
+impl Default for DenoiserParams {
+    fn default() -> Self {
+        Self {
+            iterations: 5,
+            sigma_normal: 128.0,
+            sigma_depth: 1.0,
+            sigma_albedo: 0.1,
+            sigma_luminance: 4.0,
+        }
+    }
+}
+
+impl GuiElement for DenoiserParams {
+    fn gui(&mut self, ui: &imgui::Ui) {
+        imgui::Drag::new("Iterations")
+            .range(1, 8)
+            .build(ui, &mut self.iterations);
+        imgui::Drag::new("Sigma normal")
+            .range(1.0, 256.0)
+            .speed(1.0)
+            .build(ui, &mut self.sigma_normal);
+        imgui::Drag::new("Sigma depth")
+            .range(0.01, 10.0)
+            .speed(0.01)
+            .build(ui, &mut self.sigma_depth);
+        imgui::Drag::new("Sigma albedo")
+            .range(0.01, 1.0)
+            .speed(0.01)
+            .build(ui, &mut self.sigma_albedo);
+        imgui::Drag::new("Sigma luminance")
+            .range(0.1, 32.0)
+            .speed(0.05)
+            .build(ui, &mut self.sigma_luminance);
+    }
+}
+
+pub fn denoise(
+    params: &DenoiserParams,
+    radiance: &[ColorRgb],
+    aovs: &Aovs,
+    (w, h): (u32, u32),
+) -> Vec<ColorRgb> {
+    let (w, h) = (w as usize, h as usize);
+    let hit = |index: usize| aovs.mesh_id[index] != aov::NO_ID;
+    let demodulate = |c: f32, a: f32| if a > 1e-3 { c / a } else { c };
+
+    // Illumination and the variance of its luminance.
+    let mut illumination = radiance
+        .iter()
+        .zip(&aovs.albedo)
+        .map(|(c, a)| {
+            ColorRgb::new(
+                demodulate(c.r(), a.r()),
+                demodulate(c.g(), a.g()),
+                demodulate(c.b(), a.b()),
+            )
+        })
+        .collect::<Vec<_>>();
+    let mut variance = aovs
+        .variance
+        .iter()
+        .zip(&aovs.albedo)
+        .zip(&aovs.sample_count)
+        .map(|((v, a), &sample_count)| {
+            let demodulate = |v: f32, a: f32| if a > 1e-3 { v / (a * a) } else { v };
+            let v = ColorRgb::new(
+                demodulate(v.r(), a.r()),
+                demodulate(v.g(), a.g()),
+                demodulate(v.b(), a.b()),
+            );
+            v.luminance().max(0.0) / sample_count.max(1) as f32
+        })
+        .collect::<Vec<_>>();
+
+    // Depth change per pixel, for depth weights that work on slanted surfaces.
+    let depth_gradient = (0..w * h)
+        .into_par_iter()
+        .map(|index| {
+            let (x, y) = (index % w, index / w);
+            let depth = aovs.depth[index];
+            let mut gradient = 0.0_f32;
+            for (nx, ny) in [
+                (x + 1, y),
+                (x.wrapping_sub(1), y),
+                (x, y + 1),
+                (x, y.wrapping_sub(1)),
+            ] {
+                if nx < w && ny < h && hit(nx + ny * w) {
+                    gradient = gradient.max((aovs.depth[nx + ny * w] - depth).abs());
+                }
+            }
+            gradient
+        })
+        .collect::<Vec<_>>();
+
+    for iteration in 0..params.iterations {
+        let step = 1_usize << iteration;
+        let filtered = (0..w * h)
+            .into_par_iter()
+            .map(|index| {
+                if !hit(index) {
+                    return (illumination[index], variance[index]);
+                }
+                let (x, y) = (index % w, index / w);
+                let normal = aovs.normal[index];
+                let depth = aovs.depth[index];
+                let albedo = aovs.albedo[index];
+                let luminance = illumination[index].luminance();
+                let luminance_scale =
+                    1.0 / (params.sigma_luminance * variance[index].sqrt() + 1e-4);
+                let mut sum = ColorRgb::BLACK;
+                let mut sum_variance = 0.0;
+                let mut sum_weight = 0.0;
+                for (j, ky) in KERNEL.iter().enumerate() {
+                    let ny = y as isize + (j as isize - 2) * step as isize;
+                    if ny < 0 || ny >= h as isize {
+                        continue;
+                    }
+                    for (i, kx) in KERNEL.iter().enumerate() {
+                        let nx = x as isize + (i as isize - 2) * step as isize;
+                        if nx < 0 || nx >= w as isize {
+                            continue;
+                        }
+                        let neighbor = nx as usize + ny as usize * w;
+                        if !hit(neighbor) {
+                            continue;
+                        }
+                        let distance = ((i as f32 - 2.0).hypot(j as f32 - 2.0)) * step as f32;
+                        let w_normal = normal
+                            .dot(&aovs.normal[neighbor])
+                            .max(0.0)
+                            .powf(params.sigma_normal);
+                        let w_depth = (-(aovs.depth[neighbor] - depth).abs()
+                            / (params.sigma_depth * depth_gradient[index] * distance + 1e-4))
+                            .exp();
+                        let albedo_difference = aovs.albedo[neighbor] - albedo;
+                        let w_albedo = (-(albedo_difference * albedo_difference).mean()
+                            / (params.sigma_albedo * params.sigma_albedo))
+                            .exp();
+                        let w_luminance = (-(illumination[neighbor].luminance() - luminance).abs()
+                            * luminance_scale)
+                            .exp();
+                        let weight = kx * ky * w_normal * w_depth * w_albedo * w_luminance;
+                        sum += illumination[neighbor] * weight;
+                        sum_variance += weight * weight * variance[neighbor];
+                        sum_weight += weight;
+                    }
+                }
+                // The center pixel always has full weight, so the sum is never zero.
+                (
+                    sum * (1.0 / sum_weight),
+                    sum_variance / (sum_weight * sum_weight),
+                )
+            })
+            .collect::<Vec<_>>();
+        (illumination, variance) = filtered.into_iter().unzip();
+    }
+
+    // Remodulate, nothing is done for pixels without a hit.
+    (0..w * h)
+        .map(|index| {
+            if !hit(index) {
+                return radiance[index];
+            }
+            let (c, a) = (illumination[index], aovs.albedo[index]);
+            let remodulate = |c: f32, a: f32| if a > 1e-3 { c * a } else { c };
+            ColorRgb::new(
+                remodulate(c.r(), a.r()),
+                remodulate(c.g(), a.g()),
+                remodulate(c.b(), a.b()),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn denoise_constant() {
+        // A flat, uniformly lit plane must stay unchanged.
+        let size = (16, 8);
+        let pixel_count = 16 * 8;
+        let aovs = Aovs {
+            albedo: vec![ColorRgb::new(0.5, 0.25, 1.0); pixel_count],
+            normal: vec![Vec3::new(0.0, 1.0, 0.0); pixel_count],
+            depth: vec![2.0; pixel_count],
+            position: vec![Point3::origin(); pixel_count],
+            mesh_id: vec![0; pixel_count],
+            material_id: vec![0; pixel_count],
+            variance: vec![ColorRgb::new(0.1, 0.1, 0.1); pixel_count],
+            sample_count: vec![16; pixel_count],
+        };
+        let radiance = vec![ColorRgb::new(1.0, 0.5, 2.0); pixel_count];
+        let denoised = denoise(&DenoiserParams::default(), &radiance, &aovs, size);
+        for color in denoised {
+            assert_abs_diff_eq!(color.r(), 1.0, epsilon = 1e-5);
+            assert_abs_diff_eq!(color.g(), 0.5, epsilon = 1e-5);
+            assert_abs_diff_eq!(color.b(), 2.0, epsilon = 1e-5);
+        }
+    }
+
+    #[test]
+    fn denoise_noise_and_edges() {
+        // The left half faces the light and the right half is in shadow, the
+        // top half is darker than the bottom one. Noise must go down without
+        // the halves bleeding into each other.
+        let (w, h) = (32, 32);
+        let pixel_count = w * h;
+        let lit = |x: usize| if x < w / 2 { 1.0 } else { 0.2 };
+        let albedo = |y: usize| if y < h / 2 { 0.2 } else { 0.8 };
+        let aovs = Aovs {
+            albedo: (0..pixel_count)
+                .map(|i| ColorRgb::new(albedo(i / w), albedo(i / w), albedo(i / w)))
+                .collect(),
+            normal: (0..pixel_count)
+                .map(|i| {
+                    if i % w < w / 2 {
+                        Vec3::new(0.0, 1.0, 0.0)
+                    } else {
+                        Vec3::new(1.0, 0.0, 0.0)
+                    }
+                })
+                .collect(),
+            depth: vec![2.0; pixel_count],
+            position: vec![Point3::origin(); pixel_count],
+            mesh_id: vec![0; pixel_count],
+            material_id: vec![0; pixel_count],
+            variance: vec![ColorRgb::new(0.03, 0.03, 0.03); pixel_count],
+            sample_count: vec![1; pixel_count],
+        };
+        let clean = (0..pixel_count)
+            .map(|i| lit(i % w) * albedo(i / w))
+            .collect::<Vec<_>>();
+        let mut uniform = UniformSampler::new();
+        let radiance = clean
+            .iter()
+            .map(|&c| {
+                let c = c * (1.0 + 0.6 * (uniform.sample() - 0.5));
+                ColorRgb::new(c, c, c)
+            })
+            .collect::<Vec<_>>();
+        let denoised = denoise(&DenoiserParams::default(), &radiance, &aovs, (32, 32));
+
+        let error = |image: &[ColorRgb]| {
+            image
+                .iter()
+                .zip(&clean)
+                .map(|(c, &clean)| (c.g() - clean).powi(2))
+                .sum::<f32>()
+                / pixel_count as f32
+        };
+        assert!(error(&denoised) < 0.25 * error(&radiance));
+
+        // Averages of the columns next to the normal edge and of the rows next
+        // to the albedo edge.
+        let column = |x: usize| (0..h).map(|y| denoised[x + y * w].g()).sum::<f32>() / h as f32;
+        let row = |y: usize| (0..w).map(|x| denoised[x + y * w].g()).sum::<f32>() / w as f32;
+        assert_abs_diff_eq!(column(w / 2 - 1), 0.5, epsilon = 0.03);
+        assert_abs_diff_eq!(column(w / 2), 0.1, epsilon = 0.03);
+        assert_abs_diff_eq!(row(h / 2 - 1), 0.12, epsilon = 0.03);
+        assert_abs_diff_eq!(row(h / 2), 0.48, epsilon = 0.03);
+    }
+}
@@ -0,0 +1,82 @@
+use super::*;
+
+// Turns radiance in the working space into display values. Exposure comes
+// first, then white balance and the conversion to the primaries of the
+// display, which are merged into one matrix. Standard dynamic range encodings
+// are tonemapped, high dynamic range ones keep everything above diffuse white.
+
+#[derive(Clone, Copy, Debug)]
+pub struct DisplayTransform {
+    exposure: Exposure,
+    display_from_working: Mat3,
+    tonemapping: Tonemapping,
+    encoding: DisplayEncoding,
+}
+
+impl DisplayTransform {
+    pub fn new(
+        working_space: ColorSpace,
+        exposure: Exposure,
+        white_balance: WhiteBalance,
+        tonemapper: Tonemapper,
+        encoding: DisplayEncoding,
+    ) -> Self {
+        let display_from_working = ColorSpace::conversion(working_space, encoding.color_space())
+            * white_balance.transform(working_space);
+        Self {
+            exposure,
+            display_from_working,
+            tonemapping: tonemapper.in_space(encoding.color_space()),
+            encoding,
+        }
+    }
+
+    // Linear display value.
+    pub fn linear(&self, radiance: ColorRgb) -> ColorRgb {
+        let color = self
+            .exposure
+            .expose(radiance)
+            .transform(&self.display_from_working);
+        if self.encoding.is_hdr() {
+            ColorRgb::new(color.r().max(0.0), color.g().max(0.0), color.b().max(0.0))
+        } else {
+            self.tonemapping.apply(color)
+        }
+    }
+
+    // Encoded signal in [0, 1].
+    pub fn encode(&self, radiance: ColorRgb) -> ColorRgb {
+        let color = self.linear(radiance);
+        ColorRgb::new(
+            self.encoding.encode(color.r()),
+            self.encoding.encode(color.g()),
+            self.encoding.encode(color.b()),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_transform() {
+        // A neutral gray stays neutral, whatever the working space.
+        let gray = ColorRgb::new(0.18, 0.18, 0.18);
+        for working_space in ColorSpace::ALL {
+            for encoding in DisplayEncoding::ALL {
+                let display = DisplayTransform::new(
+                    working_space,
+                    Exposure::new(0.0),
+                    WhiteBalance::default(),
+                    Tonemapper::Clamp,
+                    encoding,
+                );
+                let color = display.linear(gray);
+                assert_abs_diff_eq!(color.r(), 0.18, epsilon = 1e-4);
+                assert_abs_diff_eq!(color.g(), 0.18, epsilon = 1e-4);
+                assert_abs_diff_eq!(color.b(), 0.18, epsilon = 1e-4);
+            }
+        }
+    }
+}
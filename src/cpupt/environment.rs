@@ -0,0 +1,208 @@
+use super::*;
+
+// Equirectangular environment map. The top row is straight up and the
+// longitude grows in the same direction as the sky azimuth.
+pub struct EnvironmentMap {
+    path: PathBuf,
+    size: (u32, u32),
+    texels: Vec<ColorRgb>,
+    distribution: sampling::Distribution2D,
+}
+
+impl EnvironmentMap {
+    pub fn load(path: &Path) -> Result<Self> {
+        let (texels, size) = vz::hdr::read_from_file(path)?;
+        Self::new(path, texels, size)
+    }
+
+    fn new(path: &Path, texels: Vec<ColorRgb>, (w, h): (u32, u32)) -> Result<Self> {
+        ensure!(
+            w > 0 && h > 0,
+            "Environment map must not be empty, got {w}x{h} instead"
+        );
+        ensure!(
+            texels.iter().all(ColorRgb::is_finite),
+            "Environment map must not contain infinities or NaNs"
+        );
+
+        // The texels near the poles cover smaller solid angles, which is
+        // accounted for with sin(θ).
+        let func = texels
+            .iter()
+            .enumerate()
+            .map(|(index, texel)| {
+                let y = index as u32 / w;
+                let sin_theta = (PI * (y as f32 + 0.5) / h as f32).sin();
+                texel.luminance().max(0.0) * sin_theta
+            })
+            .collect::<Vec<_>>();
+        let distribution = sampling::Distribution2D::new(&func, (w, h));
+
+        Ok(Self {
+            path: path.to_owned(),
+            size: (w, h),
+            texels,
+            distribution,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn texel(&self, (u, v): (f32, f32)) -> ColorRgb {
+        let (w, h) = self.size;
+        let x = ((u * w as f32) as u32).min(w - 1);
+        let y = ((v * h as f32) as u32).min(h - 1);
+        self.texels[(x + y * w) as usize]
+    }
+}
+
+#[derive(Clone)]
+pub struct EnvironmentParams {
+    pub map: Arc<EnvironmentMap>,
+    pub rotation: f32,
+    pub intensity: f32,
+}
+
+impl EnvironmentParams {
+    pub fn new(map: EnvironmentMap) -> Self {
+        Self {
+            map: Arc::new(map),
+            rotation: 0.0,
+            intensity: 1.0,
+        }
+    }
+}
+
+// Maps are compared by identity, comparing every texel would be too slow.
+impl PartialEq for EnvironmentParams {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.map, &other.map)
+            && self.rotation == other.rotation
+            && self.intensity == other.intensity
+    }
+}
+
+impl GuiElement for EnvironmentParams {
+    fn gui(&mut self, ui: &imgui::Ui) {
+        imgui::AngleSlider::new("Rotation")
+            .min_degrees(0.0)
+            .max_degrees(360.0)
+            .build(ui, &mut self.rotation);
+        ui.slider("Intensity", 0.0, 10.0, &mut self.intensity);
+    }
+}
+
+pub struct EnvironmentState {
+    map: Arc<EnvironmentMap>,
+    rotation: f32,
+    intensity: f32,
+}
+
+impl EnvironmentState {
+    pub fn new(params: &EnvironmentParams) -> Self {
+        Self {
+            map: params.map.clone(),
+            rotation: params.rotation,
+            intensity: params.intensity,
+        }
+    }
+
+    pub fn radiance(&self, ray_dir: &Normal) -> ColorRgb {
+        self.intensity * self.map.texel(self.uv_from_dir(ray_dir))
+    }
+
+    pub fn sample(&self, u: (f32, f32)) -> Option<(Normal, f32)> {
+        let (uv, uv_pdf) = self.map.distribution.sample(u);
+        let theta = PI * uv.1;
+        let sin_theta = theta.sin();
+        if uv_pdf <= 0.0 || sin_theta <= 0.0 {
+            return None;
+        }
+        let phi = TAU * uv.0 + self.rotation;
+        let dir = normal![sin_theta * phi.cos(), theta.cos(), sin_theta * phi.sin()];
+        Some((dir, uv_pdf / (2.0 * PI * PI * sin_theta)))
+    }
+
+    pub fn pdf(&self, ray_dir: &Normal) -> f32 {
+        let sin_theta = (1.0 - ray_dir.y * ray_dir.y).max(0.0).sqrt();
+        if sin_theta <= 0.0 {
+            return 0.0;
+        }
+        let uv_pdf = self.map.distribution.pdf(self.uv_from_dir(ray_dir));
+        uv_pdf / (2.0 * PI * PI * sin_theta)
+    }
+
+    fn uv_from_dir(&self, dir: &Normal) -> (f32, f32) {
+        let theta = dir.y.clamp(-1.0, 1.0).acos();
+        let phi = dir.z.atan2(dir.x);
+        let u = ((phi - self.rotation) / TAU).rem_euclid(1.0);
+        let v = theta / PI;
+        (u, v)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn importance_sampling() {
+        // A bright band and a dim background. Integrating the map with its own
+        // samples must give the same irradiance as uniform sphere samples, and
+        // the pdf must integrate to one over the sphere.
+        let (w, h) = (64, 32);
+        let texels = (0..w * h)
+            .map(|index| {
+                let (x, y) = (index % w, index / w);
+                if (8..12).contains(&x) && (10..20).contains(&y) {
+                    ColorRgb::new(50.0, 40.0, 30.0)
+                } else {
+                    ColorRgb::new(0.1, 0.2, 0.3)
+                }
+            })
+            .collect::<Vec<_>>();
+        let map = EnvironmentMap::new(Path::new("test"), texels, (w, h)).unwrap();
+        let environment = EnvironmentState::new(&EnvironmentParams {
+            rotation: 1.0,
+            ..EnvironmentParams::new(map)
+        });
+
+        let samples = 200_000;
+        let mut uniform = UniformSampler::new();
+        let mut importance = ColorRgb::BLACK;
+        let mut reference = ColorRgb::BLACK;
+        let mut pdf_integral = 0.0;
+        for _ in 0..samples {
+            if let Some((wi, pdf)) = environment.sample((uniform.sample(), uniform.sample())) {
+                importance += environment.radiance(&wi) * (wi.y.max(0.0) / pdf);
+            }
+            let y = 2.0 * uniform.sample() - 1.0;
+            let phi = TAU * uniform.sample();
+            let r = (1.0 - y * y).max(0.0).sqrt();
+            let wi = normal![r * phi.cos(), y, r * phi.sin()];
+            reference += environment.radiance(&wi) * (wi.y.max(0.0) * 4.0 * PI);
+            pdf_integral += environment.pdf(&wi) * 4.0 * PI;
+        }
+        let importance = importance * (1.0 / samples as f32);
+        let reference = reference * (1.0 / samples as f32);
+        let pdf_integral = pdf_integral / samples as f32;
+        assert_abs_diff_eq!(pdf_integral, 1.0, epsilon = 0.05);
+        assert_abs_diff_eq!(
+            importance.r(),
+            reference.r(),
+            epsilon = 0.05 * reference.r()
+        );
+        assert_abs_diff_eq!(
+            importance.g(),
+            reference.g(),
+            epsilon = 0.05 * reference.g()
+        );
+        assert_abs_diff_eq!(
+            importance.b(),
+            reference.b(),
+            epsilon = 0.05 * reference.b()
+        );
+    }
+}
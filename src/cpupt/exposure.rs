@@ -1,48 +1,301 @@
 use super::*;
 
+// Exposure scales the linear radiance before tonemapping. It is given directly
+// in stops, by the settings of a physical camera, or metered from the image.
+// Camera settings and metering both go through EV100, the exposure value at ISO
+// 100, with the saturation based sensitivity of ISO 12232: the luminance that
+// saturates the sensor is 1.2 * 2^EV100. Radiance is treated as cd/m².
+//
+// Automatic exposure meters the accumulated image on every output, so it
+// converges along with the image. Between renders, the metered EV100 is blended
+// with the one of the previous render, which smooths out animations.
+
+//
+// Sources:
+//
+// Moving Frostbite to Physically Based Rendering 3.0
+// Lagarde and de Rousiers, 2014
+// https://seblagarde.files.wordpress.com/2015/07/course_notes_moving_frostbite_to_pbr_v32.pdf
+//
+// Automatic Exposure
+// Krzysztof Narkowicz, 2016
+// https://knarkowicz.wordpress.com/2016/01/09/automatic-exposure/
+//
+
+// Saturation based speed, 78 / (0.65 * 100).
+const SATURATION_FACTOR: f32 = 1.2;
+
+// Calibration constant of reflected light meters.
+const METER_CALIBRATION: f32 = 12.5;
+
+// Keeps black pixels from pulling the log average to zero.
+const MIN_METERED_LUMINANCE: f32 = 1e-4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ExposureMode {
+    Manual {
+        stops: f32,
+    },
+    Camera {
+        iso: f32,
+        f_number: f32,
+        // In seconds.
+        shutter_time: f32,
+    },
+    Auto {
+        metering: Metering,
+        // In stops, positive values brighten the image.
+        compensation: f32,
+        // Fraction of the previous EV100 kept between renders, in [0, 1).
+        smoothing: f32,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Metering {
+    // Geometric mean of the luminance.
+    LogAverage,
+    // Luminance at a percentile of all pixels, in [0, 1].
+    Percentile(f32),
+}
+
+impl ExposureMode {
+    const DEFAULTS: [Self; 3] = [
+        Self::Manual { stops: 4.0 },
+        Self::Camera {
+            iso: 100.0,
+            f_number: 4.0,
+            shutter_time: 1.0,
+        },
+        Self::Auto {
+            metering: Metering::LogAverage,
+            compensation: 0.0,
+            smoothing: 0.0,
+        },
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Manual { .. } => "Manual",
+            Self::Camera { .. } => "Camera",
+            Self::Auto { .. } => "Auto",
+        }
+    }
+}
+
+impl Default for ExposureMode {
+    fn default() -> Self {
+        Self::DEFAULTS[0]
+    }
+}
+
+impl Metering {
+    fn name(self) -> &'static str {
+        match self {
+            Self::LogAverage => "Log average",
+            Self::Percentile(_) => "Percentile",
+        }
+    }
+
+    fn luminance(self, radiance: &[ColorRgb]) -> Option<f32> {
+        let mut luminances = radiance
+            .iter()
+            .map(|c| c.luminance())
+            .filter(|l| l.is_finite())
+            .map(|l| l.max(MIN_METERED_LUMINANCE))
+            .collect::<Vec<_>>();
+        if luminances.is_empty() {
+            return None;
+        }
+        let luminance = match self {
+            Self::LogAverage => {
+                let sum = luminances.iter().map(|l| l.ln()).sum::<f32>();
+                (sum / luminances.len() as f32).exp()
+            }
+            Self::Percentile(percentile) => {
+                let index = (percentile.clamp(0.0, 1.0) * (luminances.len() - 1) as f32) as usize;
+                *luminances.select_nth_unstable_by(index, f32::total_cmp).1
+            }
+        };
+        Some(luminance)
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct Exposure {
-    stops: f32,
-    exposure: f32,
+    mode: ExposureMode,
+    ev100: f32,
+    scale: f32,
 }
 
 impl Default for Exposure {
     fn default() -> Self {
-        Self::new(4.0)
+        Self::with_mode(ExposureMode::default())
     }
 }
 
 impl Exposure {
     pub fn new(stops: f32) -> Self {
+        Self::with_mode(ExposureMode::Manual { stops })
+    }
+
+    // Automatic exposure starts out at EV100 0, until it is metered.
+    pub fn with_mode(mode: ExposureMode) -> Self {
+        let ev100 = match mode {
+            ExposureMode::Manual { stops } => stops - SATURATION_FACTOR.log2(),
+            ExposureMode::Camera {
+                iso,
+                f_number,
+                shutter_time,
+            } => (f_number * f_number / shutter_time * 100.0 / iso).log2(),
+            ExposureMode::Auto { .. } => 0.0,
+        };
+        Self::with_ev100(mode, ev100)
+    }
+
+    fn with_ev100(mode: ExposureMode, ev100: f32) -> Self {
         Self {
-            stops,
-            exposure: Self::precalculate_exposure(stops),
+            mode,
+            ev100,
+            scale: 1.0 / (SATURATION_FACTOR * 2.0_f32.powf(ev100)),
         }
     }
 
+    pub fn ev100(self) -> f32 {
+        self.ev100
+    }
+
     pub fn expose(self, color: ColorRgb) -> ColorRgb {
-        color * self.exposure
+        color * self.scale
     }
 
-    fn precalculate_exposure(stops: f32) -> f32 {
-        1.0 / 2.0_f32.powf(stops)
+    // Meters the radiance for automatic exposure, other modes are returned
+    // unchanged. `previous_ev100` is the EV100 the previous render ended with.
+    pub(super) fn metered(self, radiance: &[ColorRgb], previous_ev100: Option<f32>) -> Self {
+        let ExposureMode::Auto {
+            metering,
+            compensation,
+            smoothing,
+        } = self.mode
+        else {
+            return self;
+        };
+        let Some(luminance) = metering.luminance(radiance) else {
+            return self;
+        };
+        let ev100 = (luminance * 100.0 / METER_CALIBRATION).log2() - compensation;
+        let ev100 = previous_ev100.map_or(ev100, |previous| {
+            lerp_scalar(ev100, previous, smoothing.clamp(0.0, 0.99))
+        });
+        Self::with_ev100(self.mode, ev100)
     }
 }
 
 impl std::fmt::Display for Exposure {
+    // Formatted in stops, regardless of the mode.
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let stops = -self.scale.log2();
         if let Some(precision) = f.precision() {
-            write!(f, "{:.precision$}", self.stops)
+            write!(f, "{stops:.precision$}")
         } else {
-            write!(f, "{}", self.stops)
+            write!(f, "{stops}")
         }
     }
 }
 
 impl GuiElement for Exposure {
     fn gui(&mut self, ui: &imgui::Ui) {
-        if ui.slider("Exposure", 0.0, 16.0, &mut self.stops) {
-            self.exposure = Self::precalculate_exposure(self.stops);
+        let mut mode = self.mode;
+        if let Some(_token) = ui.begin_combo("Exposure mode", mode.name()) {
+            for default in ExposureMode::DEFAULTS {
+                if ui.selectable(default.name()) && default.name() != mode.name() {
+                    mode = default;
+                }
+            }
+        }
+        match &mut mode {
+            ExposureMode::Manual { stops } => {
+                ui.slider("Exposure", 0.0, 16.0, stops);
+            }
+            ExposureMode::Camera {
+                iso,
+                f_number,
+                shutter_time,
+            } => {
+                ui.slider_config("ISO", 25.0, 6400.0)
+                    .flags(imgui::SliderFlags::LOGARITHMIC)
+                    .build(iso);
+                ui.slider_config("F-number", 1.0, 32.0)
+                    .flags(imgui::SliderFlags::LOGARITHMIC)
+                    .build(f_number);
+                ui.slider_config("Shutter time", 1.0 / 8000.0, 30.0)
+                    .flags(imgui::SliderFlags::LOGARITHMIC)
+                    .display_format("%.4f s")
+                    .build(shutter_time);
+                ui.text(format!("EV100 {:.02}", self.ev100));
+            }
+            ExposureMode::Auto {
+                metering,
+                compensation,
+                smoothing,
+            } => {
+                if let Some(_token) = ui.begin_combo("Metering", metering.name()) {
+                    for default in [Metering::LogAverage, Metering::Percentile(0.5)] {
+                        if ui.selectable(default.name()) && default.name() != metering.name() {
+                            *metering = default;
+                        }
+                    }
+                }
+                if let Metering::Percentile(percentile) = metering {
+                    ui.slider("Percentile", 0.0, 1.0, percentile);
+                }
+                ui.slider("Compensation", -8.0, 8.0, compensation);
+                ui.slider("Smoothing", 0.0, 0.99, smoothing);
+            }
         }
+        if mode != self.mode {
+            *self = Self::with_mode(mode);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exposure_modes() {
+        // Manual stops and the equivalent camera settings agree.
+        let manual = Exposure::new(4.0);
+        assert_abs_diff_eq!(
+            manual.expose(ColorRgb::WHITE).r(),
+            1.0 / 16.0,
+            epsilon = 1e-6
+        );
+        let camera = Exposure::with_mode(ExposureMode::Camera {
+            iso: 100.0,
+            f_number: 4.0,
+            shutter_time: 1.0,
+        });
+        assert_abs_diff_eq!(camera.ev100(), 4.0, epsilon = 1e-5);
+
+        // Metering maps the average luminance to the same exposed value,
+        // whatever the brightness of the scene.
+        let auto = Exposure::with_mode(ExposureMode::Auto {
+            metering: Metering::LogAverage,
+            compensation: 0.0,
+            smoothing: 0.0,
+        });
+        let exposed = [0.5, 500.0].map(|scale| {
+            let radiance = [0.5, 2.0].map(|l| ColorRgb::new(l, l, l) * scale);
+            let metered = auto.metered(&radiance, None);
+            metered.expose(ColorRgb::WHITE * scale).r()
+        });
+        assert_abs_diff_eq!(exposed[0], exposed[1], epsilon = 1e-4);
+        assert_abs_diff_eq!(
+            exposed[0],
+            METER_CALIBRATION / 100.0 / SATURATION_FACTOR,
+            epsilon = 1e-4
+        );
     }
 }
@@ -0,0 +1,187 @@
+use super::*;
+
+// Reconstruction filters weight the radiance samples around each pixel. They
+// are applied with filter importance sampling: the offset of the primary ray
+// from the pixel center is distributed like the absolute value of the filter,
+// and the sample is weighted by the filter divided by that pdf. This keeps
+// every sample inside its own pixel, and negative lobes become negative
+// weights. Filters are separable, so each axis is sampled on its own from a
+// tabulated distribution.
+
+//
+// Sources:
+//
+// Filter Importance Sampling
+// Ernst et al., 2006
+// https://doi.org/10.1109/RT.2006.280213
+//
+// Reconstruction Filters in Computer Graphics
+// Mitchell and Netravali, 1988
+// https://doi.org/10.1145/378456.378514
+//
+// On the Use of Windows for Harmonic Analysis with the Discrete Fourier Transform
+// Fredric J. Harris, 1978
+// https://doi.org/10.1109/PROC.1978.10837
+//
+
+const FILTER_TABLE_SIZE: usize = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FilterKind {
+    Box,
+    Tent,
+    Gaussian,
+    Mitchell,
+    BlackmanHarris,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FilterParams {
+    pub kind: FilterKind,
+    // Half width of the filter, in pixels.
+    pub radius: f32,
+}
+
+impl Default for FilterParams {
+    fn default() -> Self {
+        Self {
+            kind: FilterKind::Box,
+            radius: 0.5,
+        }
+    }
+}
+
+impl FilterKind {
+    pub const ALL: [Self; 5] = [
+        Self::Box,
+        Self::Tent,
+        Self::Gaussian,
+        Self::Mitchell,
+        Self::BlackmanHarris,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Box => "box",
+            Self::Tent => "tent",
+            Self::Gaussian => "gaussian",
+            Self::Mitchell => "mitchell",
+            Self::BlackmanHarris => "blackman-harris",
+        }
+    }
+
+    // One axis of the filter, zero outside of the radius.
+    fn eval(self, x: f32, radius: f32) -> f32 {
+        let x = x.abs();
+        if x > radius {
+            return 0.0;
+        }
+        match self {
+            Self::Box => 1.0,
+            Self::Tent => radius - x,
+            Self::Gaussian => {
+                // Shifted down so that it reaches zero at the radius.
+                let sigma = radius / 3.0;
+                let gaussian = |x: f32| (-x * x / (2.0 * sigma * sigma)).exp();
+                gaussian(x) - gaussian(radius)
+            }
+            Self::Mitchell => {
+                // B = C = 1/3, as recommended by the paper. The cubic has a
+                // support of 2, which is scaled to the radius.
+                const B: f32 = 1.0 / 3.0;
+                const C: f32 = 1.0 / 3.0;
+                let x = 2.0 * x / radius;
+                if x < 1.0 {
+                    ((12.0 - 9.0 * B - 6.0 * C) * x * x * x
+                        + (-18.0 + 12.0 * B + 6.0 * C) * x * x
+                        + (6.0 - 2.0 * B))
+                        / 6.0
+                } else {
+                    ((-B - 6.0 * C) * x * x * x
+                        + (6.0 * B + 30.0 * C) * x * x
+                        + (-12.0 * B - 48.0 * C) * x
+                        + (8.0 * B + 24.0 * C))
+                        / 6.0
+                }
+            }
+            Self::BlackmanHarris => {
+                const A: [f32; 4] = [0.358_75, 0.488_29, 0.141_28, 0.011_68];
+                let t = TAU * 0.5 * (x / radius + 1.0);
+                A[0] - A[1] * t.cos() + A[2] * (2.0 * t).cos() - A[3] * (3.0 * t).cos()
+            }
+        }
+    }
+}
+
+pub(super) struct PixelFilter {
+    kind: FilterKind,
+    radius: f32,
+    distribution: sampling::Distribution1D,
+    // Signed integral of one axis, which normalizes the weights.
+    integral: f32,
+}
+
+impl PixelFilter {
+    pub fn new(params: FilterParams) -> Self {
+        let kind = params.kind;
+        let radius = params.radius.max(1e-3);
+        let table = (0..FILTER_TABLE_SIZE)
+            .map(|index| {
+                let x = radius * (2.0 * (index as f32 + 0.5) / FILTER_TABLE_SIZE as f32 - 1.0);
+                kind.eval(x, radius)
+            })
+            .collect::<Vec<_>>();
+        let integral = table.iter().sum::<f32>() * 2.0 * radius / FILTER_TABLE_SIZE as f32;
+        Self {
+            kind,
+            radius,
+            distribution: sampling::Distribution1D::new(&table),
+            integral,
+        }
+    }
+
+    // Offset from the pixel center, and the weight of the sample.
+    pub fn sample(&self, (s, t): (f32, f32)) -> ((f32, f32), f32) {
+        let (x, wx) = self.sample_axis(s);
+        let (y, wy) = self.sample_axis(t);
+        ((x, y), wx * wy)
+    }
+
+    fn sample_axis(&self, u: f32) -> (f32, f32) {
+        let (x, pdf, _) = self.distribution.sample(u);
+        let x = self.radius * (2.0 * x - 1.0);
+        let pdf = pdf / (2.0 * self.radius);
+        let weight = if pdf > 0.0 {
+            self.kind.eval(x, self.radius) / (pdf * self.integral)
+        } else {
+            0.0
+        };
+        (x, weight)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_weights() {
+        // The expected weight is one for every filter, and the box filter is
+        // exactly one everywhere.
+        let sample_count = 4096;
+        for kind in FilterKind::ALL {
+            let filter = PixelFilter::new(FilterParams { kind, radius: 2.0 });
+            let mut sum = 0.0;
+            for index in 0..sample_count {
+                let (x, weight) = filter.sample_axis((index as f32 + 0.5) / sample_count as f32);
+                assert!(x.abs() <= 2.0, "kind={kind:?}, x={x}");
+                sum += weight;
+            }
+            let mean = sum / sample_count as f32;
+            assert_abs_diff_eq!(mean, 1.0, epsilon = 1e-2);
+        }
+        let filter = PixelFilter::new(FilterParams::default());
+        assert_ulps_eq!(filter.sample((0.3, 0.9)).1, 1.0);
+    }
+}
@@ -0,0 +1,40 @@
+// Generated by `raydiance debug ggx-albedo`, do not edit.
+
+#[rustfmt::skip]
+pub const GGX_ALBEDO: [[f32; 32]; 32] = [
+    [0.89211, 0.99974, 0.99992, 0.99990, 0.99995, 0.99997, 0.99998, 0.99998, 0.99998, 0.99998, 0.99998, 0.99998, 0.99998, 0.99998, 0.99998, 0.99998, 0.99998, 1.00000, 1.00000, 1.00000, 1.00000, 1.00000, 1.00000, 1.00000, 1.00000, 1.00000, 1.00000, 1.00000, 1.00000, 1.00000, 1.00000, 1.00000],
+    [0.89268, 0.99971, 0.99992, 0.99989, 0.99995, 0.99997, 0.99998, 0.99998, 0.99998, 0.99998, 0.99998, 0.99998, 0.99998, 0.99998, 0.99998, 0.99998, 0.99998, 0.99998, 1.00000, 1.00000, 1.00000, 1.00000, 1.00000, 1.00000, 1.00000, 1.00000, 1.00000, 1.00000, 1.00000, 1.00000, 1.00000, 1.00000],
+    [0.95423, 0.99003, 0.99812, 0.99935, 0.99970, 0.99979, 0.99984, 0.99987, 0.99990, 0.99981, 0.99983, 0.99986, 0.99988, 0.99991, 0.99994, 0.99994, 0.99994, 0.99994, 0.99994, 0.99998, 0.99998, 0.99998, 0.99998, 0.99998, 0.99998, 0.99998, 0.99998, 0.99998, 0.99998, 0.99998, 0.99998, 0.99998],
+    [0.97838, 0.95473, 0.98733, 0.99443, 0.99740, 0.99861, 0.99910, 0.99938, 0.99953, 0.99967, 0.99972, 0.99975, 0.99980, 0.99982, 0.99982, 0.99984, 0.99984, 0.99985, 0.99975, 0.99979, 0.99982, 0.99983, 0.99985, 0.99985, 0.99986, 0.99988, 0.99991, 0.99991, 0.99991, 0.99989, 0.99989, 0.99992],
+    [0.98763, 0.91124, 0.96240, 0.98209, 0.98986, 0.99347, 0.99587, 0.99721, 0.99797, 0.99852, 0.99882, 0.99905, 0.99919, 0.99929, 0.99938, 0.99944, 0.99950, 0.99955, 0.99958, 0.99961, 0.99962, 0.99965, 0.99967, 0.99967, 0.99955, 0.99959, 0.99960, 0.99966, 0.99970, 0.99970, 0.99970, 0.99972],
+    [0.99199, 0.89170, 0.92920, 0.95948, 0.97545, 0.98398, 0.98879, 0.99161, 0.99359, 0.99521, 0.99636, 0.99707, 0.99758, 0.99798, 0.99826, 0.99846, 0.99862, 0.99874, 0.99884, 0.99893, 0.99900, 0.99905, 0.99909, 0.99914, 0.99918, 0.99920, 0.99923, 0.99925, 0.99918, 0.99923, 0.99928, 0.99930],
+    [0.99438, 0.89392, 0.90291, 0.93178, 0.95366, 0.96778, 0.97673, 0.98258, 0.98648, 0.98914, 0.99102, 0.99252, 0.99381, 0.99489, 0.99567, 0.99626, 0.99673, 0.99709, 0.99737, 0.99757, 0.99775, 0.99788, 0.99800, 0.99810, 0.99818, 0.99827, 0.99833, 0.99838, 0.99842, 0.99845, 0.99844, 0.99852],
+    [0.99579, 0.90503, 0.89029, 0.90774, 0.92909, 0.94651, 0.95927, 0.96844, 0.97500, 0.97978, 0.98333, 0.98598, 0.98799, 0.98956, 0.99085, 0.99199, 0.99300, 0.99382, 0.99445, 0.99497, 0.99542, 0.99577, 0.99606, 0.99628, 0.99648, 0.99665, 0.99679, 0.99690, 0.99700, 0.99708, 0.99706, 0.99721],
+    [0.99666, 0.91745, 0.88870, 0.89228, 0.90743, 0.92399, 0.93847, 0.95011, 0.95918, 0.96621, 0.97166, 0.97593, 0.97930, 0.98198, 0.98412, 0.98583, 0.98725, 0.98848, 0.98958, 0.99057, 0.99143, 0.99214, 0.99272, 0.99321, 0.99362, 0.99396, 0.99426, 0.99450, 0.99470, 0.99487, 0.99501, 0.99512],
+    [0.99723, 0.92827, 0.89313, 0.88526, 0.89188, 0.90417, 0.91745, 0.92971, 0.94028, 0.94908, 0.95632, 0.96224, 0.96708, 0.97104, 0.97433, 0.97704, 0.97930, 0.98119, 0.98279, 0.98417, 0.98541, 0.98651, 0.98750, 0.98836, 0.98911, 0.98976, 0.99030, 0.99076, 0.99115, 0.99147, 0.99174, 0.99198],
+    [0.99759, 0.93679, 0.89973, 0.88408, 0.88266, 0.88905, 0.89895, 0.90979, 0.92027, 0.92977, 0.93813, 0.94534, 0.95151, 0.95676, 0.96121, 0.96500, 0.96825, 0.97102, 0.97341, 0.97546, 0.97724, 0.97880, 0.98018, 0.98143, 0.98255, 0.98357, 0.98447, 0.98525, 0.98593, 0.98650, 0.98699, 0.98740],
+    [0.99780, 0.94315, 0.90632, 0.88597, 0.87818, 0.87872, 0.88416, 0.89212, 0.90103, 0.90999, 0.91850, 0.92628, 0.93328, 0.93950, 0.94498, 0.94978, 0.95399, 0.95767, 0.96089, 0.96373, 0.96622, 0.96842, 0.97037, 0.97210, 0.97366, 0.97507, 0.97637, 0.97753, 0.97857, 0.97949, 0.98028, 0.98096],
+    [0.99791, 0.94761, 0.91184, 0.88894, 0.87658, 0.87211, 0.87305, 0.87743, 0.88380, 0.89114, 0.89877, 0.90628, 0.91342, 0.92006, 0.92616, 0.93169, 0.93668, 0.94116, 0.94519, 0.94879, 0.95203, 0.95492, 0.95752, 0.95986, 0.96196, 0.96386, 0.96559, 0.96718, 0.96864, 0.96995, 0.97113, 0.97217],
+    [0.99795, 0.95048, 0.91588, 0.89168, 0.87625, 0.86785, 0.86478, 0.86554, 0.86891, 0.87393, 0.87991, 0.88634, 0.89289, 0.89932, 0.90550, 0.91133, 0.91677, 0.92180, 0.92644, 0.93069, 0.93458, 0.93812, 0.94136, 0.94431, 0.94701, 0.94947, 0.95173, 0.95379, 0.95570, 0.95745, 0.95906, 0.96052],
+    [0.99793, 0.95207, 0.91837, 0.89350, 0.87601, 0.86467, 0.85828, 0.85574, 0.85608, 0.85850, 0.86236, 0.86715, 0.87249, 0.87811, 0.88380, 0.88942, 0.89486, 0.90007, 0.90501, 0.90966, 0.91401, 0.91808, 0.92185, 0.92535, 0.92859, 0.93159, 0.93437, 0.93695, 0.93934, 0.94156, 0.94363, 0.94554],
+    [0.99785, 0.95257, 0.91936, 0.89407, 0.87516, 0.86162, 0.85257, 0.84719, 0.84474, 0.84456, 0.84610, 0.84892, 0.85263, 0.85696, 0.86167, 0.86660, 0.87159, 0.87655, 0.88141, 0.88613, 0.89066, 0.89498, 0.89910, 0.90298, 0.90664, 0.91010, 0.91334, 0.91638, 0.91923, 0.92191, 0.92443, 0.92679],
+    [0.99774, 0.95214, 0.91899, 0.89326, 0.87325, 0.85803, 0.84685, 0.83909, 0.83416, 0.83155, 0.83080, 0.83152, 0.83338, 0.83611, 0.83948, 0.84330, 0.84743, 0.85174, 0.85614, 0.86056, 0.86492, 0.86921, 0.87338, 0.87741, 0.88130, 0.88502, 0.88858, 0.89197, 0.89520, 0.89826, 0.90118, 0.90393],
+    [0.99759, 0.95092, 0.91740, 0.89107, 0.87009, 0.85348, 0.84056, 0.83080, 0.82373, 0.81892, 0.81600, 0.81465, 0.81458, 0.81556, 0.81735, 0.81980, 0.82274, 0.82606, 0.82964, 0.83340, 0.83727, 0.84119, 0.84511, 0.84901, 0.85284, 0.85660, 0.86026, 0.86381, 0.86724, 0.87055, 0.87374, 0.87679],
+    [0.99742, 0.94901, 0.91469, 0.88758, 0.86561, 0.84776, 0.83334, 0.82186, 0.81292, 0.80615, 0.80125, 0.79793, 0.79597, 0.79514, 0.79526, 0.79616, 0.79770, 0.79977, 0.80225, 0.80506, 0.80811, 0.81135, 0.81472, 0.81817, 0.82167, 0.82518, 0.82867, 0.83214, 0.83555, 0.83890, 0.84218, 0.84537],
+    [0.99721, 0.94652, 0.91100, 0.88285, 0.85982, 0.84076, 0.82497, 0.81198, 0.80138, 0.79286, 0.78614, 0.78101, 0.77723, 0.77463, 0.77304, 0.77233, 0.77235, 0.77300, 0.77419, 0.77582, 0.77781, 0.78010, 0.78263, 0.78535, 0.78822, 0.79120, 0.79426, 0.79737, 0.80050, 0.80364, 0.80678, 0.80989],
+    [0.99697, 0.94352, 0.90641, 0.87701, 0.85278, 0.83249, 0.81539, 0.80097, 0.78886, 0.77873, 0.77037, 0.76355, 0.75807, 0.75378, 0.75052, 0.74819, 0.74665, 0.74582, 0.74559, 0.74588, 0.74663, 0.74777, 0.74924, 0.75099, 0.75299, 0.75518, 0.75753, 0.76001, 0.76260, 0.76527, 0.76800, 0.77077],
+    [0.99672, 0.94004, 0.90104, 0.87015, 0.84458, 0.82298, 0.80456, 0.78877, 0.77523, 0.76364, 0.75374, 0.74535, 0.73828, 0.73239, 0.72755, 0.72363, 0.72055, 0.71820, 0.71651, 0.71540, 0.71479, 0.71465, 0.71490, 0.71551, 0.71642, 0.71761, 0.71902, 0.72064, 0.72244, 0.72439, 0.72646, 0.72863],
+    [0.99645, 0.93615, 0.89497, 0.86237, 0.83530, 0.81232, 0.79254, 0.77539, 0.76047, 0.74748, 0.73615, 0.72629, 0.71774, 0.71034, 0.70398, 0.69856, 0.69399, 0.69015, 0.68700, 0.68446, 0.68247, 0.68097, 0.67991, 0.67926, 0.67897, 0.67900, 0.67931, 0.67989, 0.68069, 0.68170, 0.68290, 0.68425],
+    [0.99616, 0.93189, 0.88827, 0.85377, 0.82507, 0.80060, 0.77941, 0.76090, 0.74463, 0.73027, 0.71757, 0.70633, 0.69638, 0.68757, 0.67980, 0.67295, 0.66694, 0.66168, 0.65712, 0.65317, 0.64980, 0.64694, 0.64455, 0.64259, 0.64102, 0.63982, 0.63893, 0.63834, 0.63802, 0.63795, 0.63810, 0.63846],
+    [0.99585, 0.92729, 0.88102, 0.84445, 0.81398, 0.78793, 0.76528, 0.74537, 0.72775, 0.71206, 0.69804, 0.68548, 0.67420, 0.66407, 0.65496, 0.64678, 0.63943, 0.63283, 0.62692, 0.62165, 0.61694, 0.61276, 0.60907, 0.60581, 0.60297, 0.60050, 0.59838, 0.59658, 0.59507, 0.59384, 0.59286, 0.59210],
+    [0.99553, 0.92240, 0.87327, 0.83447, 0.80213, 0.77442, 0.75025, 0.72893, 0.70995, 0.69295, 0.67766, 0.66383, 0.65130, 0.63992, 0.62957, 0.62013, 0.61153, 0.60369, 0.59654, 0.59001, 0.58406, 0.57864, 0.57370, 0.56921, 0.56514, 0.56146, 0.55812, 0.55513, 0.55244, 0.55003, 0.54790, 0.54601],
+    [0.99518, 0.91723, 0.86507, 0.82392, 0.78961, 0.76018, 0.73445, 0.71169, 0.69136, 0.67307, 0.65652, 0.64148, 0.62777, 0.61522, 0.60370, 0.59312, 0.58337, 0.57438, 0.56608, 0.55840, 0.55132, 0.54475, 0.53868, 0.53305, 0.52785, 0.52303, 0.51858, 0.51445, 0.51064, 0.50712, 0.50388, 0.50089],
+    [0.99483, 0.91182, 0.85648, 0.81288, 0.77653, 0.74531, 0.71800, 0.69378, 0.67210, 0.65255, 0.63479, 0.61859, 0.60375, 0.59009, 0.57751, 0.56586, 0.55506, 0.54504, 0.53571, 0.52701, 0.51889, 0.51132, 0.50422, 0.49758, 0.49137, 0.48554, 0.48007, 0.47495, 0.47013, 0.46561, 0.46136, 0.45737],
+    [0.99446, 0.90620, 0.84756, 0.80141, 0.76295, 0.72993, 0.70102, 0.67535, 0.65233, 0.63154, 0.61261, 0.59530, 0.57940, 0.56473, 0.55114, 0.53853, 0.52680, 0.51584, 0.50559, 0.49600, 0.48699, 0.47852, 0.47055, 0.46304, 0.45595, 0.44926, 0.44292, 0.43693, 0.43125, 0.42587, 0.42077, 0.41593],
+    [0.99407, 0.90037, 0.83833, 0.78958, 0.74899, 0.71412, 0.68360, 0.65649, 0.63217, 0.61017, 0.59013, 0.57177, 0.55488, 0.53926, 0.52478, 0.51130, 0.49872, 0.48695, 0.47591, 0.46553, 0.45576, 0.44655, 0.43784, 0.42961, 0.42180, 0.41439, 0.40735, 0.40066, 0.39429, 0.38822, 0.38242, 0.37689],
+    [0.99367, 0.89438, 0.82885, 0.77745, 0.73468, 0.69799, 0.66586, 0.63735, 0.61175, 0.58860, 0.56749, 0.54815, 0.53034, 0.51387, 0.49857, 0.48432, 0.47101, 0.45855, 0.44683, 0.43581, 0.42541, 0.41558, 0.40629, 0.39747, 0.38910, 0.38113, 0.37356, 0.36633, 0.35943, 0.35284, 0.34654, 0.34050],
+    [0.99326, 0.88822, 0.81914, 0.76506, 0.72013, 0.68160, 0.64790, 0.61801, 0.59120, 0.56694, 0.54484, 0.52459, 0.50595, 0.48871, 0.47269, 0.45778, 0.44384, 0.43078, 0.41851, 0.40697, 0.39607, 0.38577, 0.37601, 0.36677, 0.35798, 0.34962, 0.34165, 0.33405, 0.32680, 0.31986, 0.31322, 0.30685],
+];
+
+#[rustfmt::skip]
+pub const GGX_AVERAGE_ALBEDO: [f32; 32] = [0.99999, 0.99999, 0.99992, 0.99961, 0.99885, 0.99737, 0.99489, 0.99118, 0.98598, 0.97907, 0.97027, 0.95940, 0.94634, 0.93098, 0.91328, 0.89323, 0.87089, 0.84635, 0.81977, 0.79135, 0.76133, 0.72997, 0.69759, 0.66449, 0.63100, 0.59742, 0.56407, 0.53120, 0.49907, 0.46788, 0.43781, 0.40899];
@@ -1,5 +1,14 @@
 use super::*;
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Culling {
+    // Triangles facing away from the ray are ignored.
+    Backface,
+    // Triangles are hit from both sides, used when tracing inside closed
+    // meshes.
+    None,
+}
+
 pub struct RayTriangleIntersector {
     k: Vec3u,
     s: Vec3,
@@ -39,7 +48,14 @@ impl RayTriangleIntersector {
         Self { k, s }
     }
 
-    pub fn hit(&self, ray: &Ray, triangle: &Triangle, out_t: &mut f32, out_uvw: &mut Vec3) -> bool {
+    pub fn hit(
+        &self,
+        ray: &Ray,
+        triangle: &Triangle,
+        culling: Culling,
+        out_t: &mut f32,
+        out_uvw: &mut Vec3,
+    ) -> bool {
         // Aliases.
         let k = self.k;
         let s = self.s;
@@ -76,8 +92,17 @@ impl RayTriangleIntersector {
         }
 
         // Perform edge tests.
-        if u < 0.0 || v < 0.0 || w < 0.0 {
-            return false;
+        match culling {
+            Culling::Backface => {
+                if u < 0.0 || v < 0.0 || w < 0.0 {
+                    return false;
+                }
+            }
+            Culling::None => {
+                if (u < 0.0 || v < 0.0 || w < 0.0) && (u > 0.0 || v > 0.0 || w > 0.0) {
+                    return false;
+                }
+            }
         }
 
         // Calculate determinant.
@@ -91,7 +116,11 @@ impl RayTriangleIntersector {
         let bz = s.z * b[k.z as usize];
         let cz = s.z * c[k.z as usize];
         let t = u * az + v * bz + w * cz;
-        if t < 0.0 || t > *out_t * det {
+        if det > 0.0 {
+            if t < 0.0 || t > *out_t * det {
+                return false;
+            }
+        } else if t > 0.0 || t < *out_t * det {
             return false;
         }
 
@@ -242,6 +271,7 @@ pub fn ray_bvh_hit(
     ray: &Ray,
     nodes: &[bvh::Node],
     triangles: &[Triangle],
+    culling: Culling,
     out_closest_hit: &mut f32,
     out_barycentrics: &mut Vec3,
     out_triangle_index: &mut u32,
@@ -281,7 +311,13 @@ pub fn ray_bvh_hit(
                     let mut closest_hit = best_closest_hit;
                     let mut barycentrics = vector![0.0, 0.0, 0.0];
                     stats.ray_triangle_tests += 1;
-                    if ray_triangle.hit(ray, &triangle, &mut closest_hit, &mut barycentrics) {
+                    if ray_triangle.hit(
+                        ray,
+                        &triangle,
+                        culling,
+                        &mut closest_hit,
+                        &mut barycentrics,
+                    ) {
                         stats.ray_triangle_hits += 1;
                         hit = true;
 
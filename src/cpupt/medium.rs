@@ -0,0 +1,604 @@
+use super::*;
+
+//
+// Sources:
+//
+// Monte Carlo Methods for Volumetric Light Transport Simulation
+// Jan Novák, Iliyan Georgiev, Johannes Hanika, Wojciech Jarosz, 2018
+// https://cs.dartmouth.edu/~wjarosz/publications/novak18monte.html
+//
+// Spectral and Decomposition Tracking for Rendering Heterogeneous Volumes
+// Peter Kutz, Ralf Habel, Yining Karl Li, Jan Novák, 2017
+// https://disneyanimation.com/publications/spectral-and-decomposition-tracking-for-rendering-heterogeneous-volumes/
+//
+// Practical and Controllable Subsurface Scattering for Production Path Tracing
+// Matt Jen-Yuan Chiang, Peter Kutz, Brent Burley, 2016
+// https://dl.acm.org/doi/10.1145/2897839.2927433
+//
+
+//
+// Params
+//
+
+// Coefficients are given per unit distance and scaled by `density`, which
+// makes it easy to animate the thickness of a medium without touching its
+// color.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MediumParams {
+    pub absorption: ColorRgb,
+    pub scattering: ColorRgb,
+    pub density: f32,
+    pub anisotropy: f32,
+}
+
+impl Default for MediumParams {
+    fn default() -> Self {
+        Self {
+            absorption: ColorRgb::new(0.05, 0.05, 0.05),
+            scattering: ColorRgb::new(0.5, 0.5, 0.5),
+            density: 0.1,
+            anisotropy: 0.5,
+        }
+    }
+}
+
+impl MediumParams {
+    // Config files can hold anything, the media assume valid parameters.
+    pub fn validate(&self) -> Result<()> {
+        let coefficients = [self.absorption, self.scattering];
+        ensure!(
+            coefficients
+                .iter()
+                .all(|c| c.r() >= 0.0 && c.g() >= 0.0 && c.b() >= 0.0),
+            "Medium coefficients must be non-negative, got absorption {:?} and scattering {:?}",
+            self.absorption,
+            self.scattering
+        );
+        ensure!(
+            self.density >= 0.0,
+            "Medium density must be non-negative, got {} instead",
+            self.density
+        );
+        ensure!(
+            (-1.0..=1.0).contains(&self.anisotropy),
+            "Medium anisotropy must be in [-1,1], got {} instead",
+            self.anisotropy
+        );
+        Ok(())
+    }
+}
+
+// Global fog fills the half-space below `height`.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FogParams {
+    pub medium: MediumParams,
+    pub height: f32,
+}
+
+impl Default for FogParams {
+    fn default() -> Self {
+        Self {
+            medium: MediumParams::default(),
+            height: 2.0,
+        }
+    }
+}
+
+// Fills the interior of a closed mesh. The surface of the mesh becomes an
+// index-matched boundary, which means that rays pass through it unchanged.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MeshMedium {
+    pub mesh: u32,
+    pub medium: MediumParams,
+}
+
+//
+// Media
+//
+
+// Runtime state of all media in the scene, rebuilt whenever the input changes.
+#[derive(Clone, Debug, Default)]
+pub struct Media {
+    fog: Option<Fog>,
+    meshes: Vec<Option<HomogeneousMedium>>,
+}
+
+impl Media {
+    pub fn new(fog: Option<&FogParams>, mesh_media: &[MeshMedium], mesh_count: usize) -> Self {
+        let fog = fog.map(Fog::new);
+        let mut meshes = vec![None; mesh_count];
+        for mesh_medium in mesh_media {
+            meshes[mesh_medium.mesh as usize] = Some(HomogeneousMedium::new(&mesh_medium.medium));
+        }
+        Self { fog, meshes }
+    }
+
+    pub fn fog(&self) -> Option<&Fog> {
+        self.fog.as_ref()
+    }
+
+    pub fn mesh(&self, mesh: u32) -> Option<&HomogeneousMedium> {
+        self.meshes[mesh as usize].as_ref()
+    }
+
+    // Coefficients at the sampled wavelengths.
+    pub fn spectral(&self, wavelengths: &spectrum::SampledWavelengths) -> Self {
+        Self {
+            fog: self.fog.map(|fog| fog.spectral(wavelengths)),
+            meshes: self
+                .meshes
+                .iter()
+                .map(|medium| medium.map(|medium| medium.spectral(wavelengths)))
+                .collect(),
+        }
+    }
+
+    // Coefficients in another RGB color space.
+    pub fn transform(&self, matrix: &Mat3) -> Self {
+        Self {
+            fog: self.fog.map(|fog| fog.transform(matrix)),
+            meshes: self
+                .meshes
+                .iter()
+                .map(|medium| medium.map(|medium| medium.transform(matrix)))
+                .collect(),
+        }
+    }
+}
+
+//
+// Homogeneous medium
+//
+
+pub enum MediumEvent {
+    // A real scattering collision at distance `t`.
+    Scatter { t: f32, weight: ColorRgb },
+    // The path was absorbed by the medium.
+    Absorb,
+    // The ray left the tracked interval without a real collision.
+    Pass { weight: ColorRgb },
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct HomogeneousMedium {
+    sigma_a: ColorRgb,
+    sigma_s: ColorRgb,
+    sigma_n: ColorRgb,
+    majorant: f32,
+    phase: HenyeyGreenstein,
+}
+
+impl HomogeneousMedium {
+    pub fn new(params: &MediumParams) -> Self {
+        assert!(
+            params.density >= 0.0,
+            "Medium density must be non-negative, got {} instead",
+            params.density
+        );
+        let sigma_a = params.density * params.absorption;
+        let sigma_s = params.density * params.scattering;
+        Self::from_coefficients(sigma_a, sigma_s, params.anisotropy)
+    }
+
+    // Random walk subsurface scattering. The single-scattering albedo is
+    // inverted from `color` such that the multiple-scattering result inside a
+    // semi-infinite slab comes out roughly as `color`. `radius` is the mean
+    // free path per channel.
+    pub fn subsurface(color: ColorRgb, radius: ColorRgb) -> Self {
+        let channel = |color: f32, radius: f32| {
+            let color = color.clamp(0.0, 0.999);
+            let albedo = 1.0
+                - (4.09712 + 4.20863 * color
+                    - (9.59217 + 41.6808 * color + 17.7126 * color * color).sqrt())
+                .powi(2);
+            let sigma_t = 1.0 / radius.max(1e-4);
+            (sigma_t * (1.0 - albedo), sigma_t * albedo)
+        };
+        let (r_a, r_s) = channel(color.r(), radius.r());
+        let (g_a, g_s) = channel(color.g(), radius.g());
+        let (b_a, b_s) = channel(color.b(), radius.b());
+        Self::from_coefficients(
+            ColorRgb::new(r_a, g_a, b_a),
+            ColorRgb::new(r_s, g_s, b_s),
+            0.0,
+        )
+    }
+
+    pub fn spectral(&self, wavelengths: &spectrum::SampledWavelengths) -> Self {
+        Self {
+            phase: self.phase,
+            ..Self::from_coefficients(
+                wavelengths.unbounded(self.sigma_a),
+                wavelengths.unbounded(self.sigma_s),
+                0.0,
+            )
+        }
+    }
+
+    // Wider color spaces keep the coefficients non-negative, up to rounding.
+    pub fn transform(&self, matrix: &Mat3) -> Self {
+        let transform = |color: ColorRgb| {
+            let color = color.transform(matrix);
+            ColorRgb::new(color.r().max(0.0), color.g().max(0.0), color.b().max(0.0))
+        };
+        Self {
+            phase: self.phase,
+            ..Self::from_coefficients(transform(self.sigma_a), transform(self.sigma_s), 0.0)
+        }
+    }
+
+    fn from_coefficients(sigma_a: ColorRgb, sigma_s: ColorRgb, anisotropy: f32) -> Self {
+        let sigma_t = sigma_a + sigma_s;
+
+        // The majorant is shared by all channels, the channels with smaller
+        // extinction get the rest as null-collision coefficients.
+        let majorant = sigma_t.max_element();
+        let sigma_n = ColorRgb::new(
+            majorant - sigma_t.r(),
+            majorant - sigma_t.g(),
+            majorant - sigma_t.b(),
+        );
+
+        Self {
+            sigma_a,
+            sigma_s,
+            sigma_n,
+            majorant,
+            phase: HenyeyGreenstein::new(anisotropy),
+        }
+    }
+
+    pub fn phase(&self) -> HenyeyGreenstein {
+        self.phase
+    }
+
+    // Delta tracking over `t_min..t_max`. Collision types are chosen with
+    // probabilities proportional to the averaged coefficients, the returned
+    // weights correct for the per-channel differences.
+    pub fn delta_track(&self, t_min: f32, t_max: f32, uniform: &mut UniformSampler) -> MediumEvent {
+        let mut weight = ColorRgb::WHITE;
+        if self.majorant <= 0.0 {
+            return MediumEvent::Pass { weight };
+        }
+
+        let p_absorb = self.sigma_a.mean() / self.majorant;
+        let p_scatter = self.sigma_s.mean() / self.majorant;
+        let p_null = self.sigma_n.mean() / self.majorant;
+
+        let mut t = t_min;
+        loop {
+            t -= (1.0 - uniform.sample()).ln() / self.majorant;
+            if t >= t_max {
+                return MediumEvent::Pass { weight };
+            }
+
+            let u = uniform.sample();
+            if u < p_absorb {
+                return MediumEvent::Absorb;
+            }
+            if u < p_absorb + p_scatter {
+                weight *= self.sigma_s * (1.0 / (self.majorant * p_scatter));
+                return MediumEvent::Scatter { t, weight };
+            }
+            if p_null <= 0.0 {
+                return MediumEvent::Absorb;
+            }
+            weight *= self.sigma_n * (1.0 / (self.majorant * p_null));
+        }
+    }
+
+    // Ratio tracking estimates the transmittance over `t_min..t_max` without
+    // terminating at real collisions.
+    pub fn ratio_track(&self, t_min: f32, t_max: f32, uniform: &mut UniformSampler) -> ColorRgb {
+        let mut transmittance = ColorRgb::WHITE;
+        if self.majorant <= 0.0 {
+            return transmittance;
+        }
+
+        let mut t = t_min;
+        loop {
+            t -= (1.0 - uniform.sample()).ln() / self.majorant;
+            if t >= t_max {
+                return transmittance;
+            }
+            transmittance *= self.sigma_n * (1.0 / self.majorant);
+            if transmittance.max_element() <= 0.0 {
+                return transmittance;
+            }
+        }
+    }
+}
+
+//
+// Fog
+//
+
+#[derive(Clone, Copy, Debug)]
+pub struct Fog {
+    medium: HomogeneousMedium,
+    height: f32,
+}
+
+impl Fog {
+    pub fn new(params: &FogParams) -> Self {
+        Self {
+            medium: HomogeneousMedium::new(&params.medium),
+            height: params.height,
+        }
+    }
+
+    pub fn medium(&self) -> &HomogeneousMedium {
+        &self.medium
+    }
+
+    pub fn spectral(&self, wavelengths: &spectrum::SampledWavelengths) -> Self {
+        Self {
+            medium: self.medium.spectral(wavelengths),
+            height: self.height,
+        }
+    }
+
+    pub fn transform(&self, matrix: &Mat3) -> Self {
+        Self {
+            medium: self.medium.transform(matrix),
+            height: self.height,
+        }
+    }
+
+    // Returns the part of `0..t_max` along the ray which is inside the fog.
+    pub fn interval(&self, ray: &Ray, t_max: f32) -> Option<(f32, f32)> {
+        let origin_y = ray.origin.y;
+        let dir_y = ray.dir.y;
+        let inside = origin_y <= self.height;
+        if dir_y == 0.0 {
+            return inside.then_some((0.0, t_max));
+        }
+        let t_plane = (self.height - origin_y) / dir_y;
+        if inside {
+            if dir_y > 0.0 {
+                Some((0.0, t_plane.min(t_max)))
+            } else {
+                Some((0.0, t_max))
+            }
+        } else if dir_y < 0.0 && t_plane < t_max {
+            Some((t_plane, t_max))
+        } else {
+            None
+        }
+    }
+}
+
+//
+// Phase function
+//
+
+// The angle is measured between the direction of travel before and after
+// scattering, positive anisotropy favors forward scattering.
+#[derive(Clone, Copy, Debug)]
+pub struct HenyeyGreenstein {
+    g: f32,
+}
+
+impl HenyeyGreenstein {
+    pub fn new(g: f32) -> Self {
+        assert!(
+            (-1.0..=1.0).contains(&g),
+            "Anisotropy must be in [-1,1], got {g} instead"
+        );
+        Self {
+            g: g.clamp(-0.999, 0.999),
+        }
+    }
+
+    pub fn eval(self, cos_theta: f32) -> f32 {
+        let g = self.g;
+        let denom = 1.0 + g * g - 2.0 * g * cos_theta;
+        (1.0 - g * g) / (4.0 * PI * denom * denom.max(0.0).sqrt())
+    }
+
+    pub fn sample(self, dir: &Normal, u: (f32, f32)) -> Normal {
+        let g = self.g;
+        let cos_theta = if g.abs() < 1e-3 {
+            1.0 - 2.0 * u.0
+        } else {
+            let sqr = (1.0 - g * g) / (1.0 - g + 2.0 * g * u.0);
+            (1.0 + g * g - sqr * sqr) / (2.0 * g)
+        };
+        let cos_theta = cos_theta.clamp(-1.0, 1.0);
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let phi = TAU * u.1;
+        let local = vector![sin_theta * phi.cos(), cos_theta, sin_theta * phi.sin()];
+        let onb = sampling::OrthonormalBasis::new(dir);
+        normal!(onb.world_from_local() * local)
+    }
+}
+
+//
+// Gui
+//
+
+impl GuiElement for MediumParams {
+    fn gui(&mut self, ui: &imgui::Ui) {
+        ui.color_edit3("Absorption", self.absorption.as_mut());
+        ui.color_edit3("Scattering", self.scattering.as_mut());
+        imgui::Drag::new("Density")
+            .range(0.0, 10.0)
+            .speed(0.005)
+            .build(ui, &mut self.density);
+        ui.slider("Anisotropy", -0.95, 0.95, &mut self.anisotropy);
+    }
+}
+
+impl GuiElement for FogParams {
+    fn gui(&mut self, ui: &imgui::Ui) {
+        self.medium.gui(ui);
+        imgui::Drag::new("Height")
+            .range(-10.0, 100.0)
+            .speed(0.05)
+            .build(ui, &mut self.height);
+    }
+}
+
+//
+// Tests
+//
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validate_params() {
+        assert!(MediumParams::default().validate().is_ok());
+        for params in [
+            MediumParams {
+                density: -1.0,
+                ..MediumParams::default()
+            },
+            MediumParams {
+                anisotropy: 1.5,
+                ..MediumParams::default()
+            },
+            MediumParams {
+                scattering: ColorRgb::new(0.5, -0.1, 0.5),
+                ..MediumParams::default()
+            },
+        ] {
+            assert!(params.validate().is_err());
+        }
+    }
+
+    #[test]
+    fn henyey_greenstein_normalized() {
+        let samples = 100_000;
+        for g in [-0.8, -0.3, 0.0, 0.3, 0.8] {
+            let phase = HenyeyGreenstein::new(g);
+            let mut integral = 0.0;
+            for i in 0..samples {
+                let cos_theta = 2.0 * (i as f32 + 0.5) / samples as f32 - 1.0;
+                integral += TAU * phase.eval(cos_theta) * 2.0 / samples as f32;
+            }
+            assert_abs_diff_eq!(integral, 1.0, epsilon = 0.01);
+        }
+    }
+
+    #[test]
+    fn tracking_transmittance() {
+        let medium = HomogeneousMedium::new(&MediumParams {
+            absorption: ColorRgb::new(0.1, 0.2, 0.4),
+            scattering: ColorRgb::new(0.4, 0.2, 0.1),
+            density: 1.0,
+            anisotropy: 0.0,
+        });
+        let distance = 2.0;
+        let expected = ColorRgb::new(
+            (-0.5_f32 * distance).exp(),
+            (-0.4_f32 * distance).exp(),
+            (-0.5_f32 * distance).exp(),
+        );
+
+        let samples = 200_000;
+        let mut uniform = UniformSampler::new();
+        let mut delta = ColorRgb::BLACK;
+        let mut ratio = ColorRgb::BLACK;
+        for _ in 0..samples {
+            if let MediumEvent::Pass { weight } = medium.delta_track(0.0, distance, &mut uniform) {
+                delta += weight;
+            }
+            ratio += medium.ratio_track(0.0, distance, &mut uniform);
+        }
+        let delta = delta * (1.0 / samples as f32);
+        let ratio = ratio * (1.0 / samples as f32);
+        for (estimate, expected) in [
+            (delta.r(), expected.r()),
+            (delta.g(), expected.g()),
+            (delta.b(), expected.b()),
+            (ratio.r(), expected.r()),
+            (ratio.g(), expected.g()),
+            (ratio.b(), expected.b()),
+        ] {
+            assert_abs_diff_eq!(estimate, expected, epsilon = 0.01);
+        }
+    }
+
+    #[test]
+    fn subsurface_albedo() {
+        // The inverted single-scattering albedo gives the color back as the
+        // multiple-scattering albedo of a semi-infinite slab, with the
+        // approximation of van de Hulst.
+        for color in [0.1, 0.3, 0.5, 0.7, 0.9] {
+            let medium =
+                HomogeneousMedium::subsurface(ColorRgb::new(color, color, color), ColorRgb::WHITE);
+            let albedo = medium.sigma_s.r() / (medium.sigma_a.r() + medium.sigma_s.r());
+            let s = (1.0 - albedo).sqrt();
+            let reflectance = (1.0 - s) * (1.0 - 0.139 * s) / (1.0 + 1.17 * s);
+            assert_abs_diff_eq!(reflectance, color, epsilon = 0.01);
+        }
+    }
+
+    #[test]
+    fn subsurface_walk() {
+        // Walks enter through the top of a slab with diffuse directions, the
+        // depth grows along y. Returns the reflected and transmitted weights.
+        let walk = |medium: &HomogeneousMedium, thickness: f32, samples: usize| {
+            let mut uniform = UniformSampler::new();
+            let mut reflected = ColorRgb::BLACK;
+            let mut transmitted = ColorRgb::BLACK;
+            for _ in 0..samples {
+                let cos_theta = uniform.sample().sqrt();
+                let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+                let mut dir = normal!(vector![sin_theta, cos_theta, 0.0]);
+                let mut depth = 0.0;
+                let mut throughput = ColorRgb::WHITE;
+                loop {
+                    let t_max = if dir.y > 0.0 {
+                        (thickness - depth) / dir.y
+                    } else {
+                        -depth / dir.y
+                    };
+                    match medium.delta_track(0.0, t_max, &mut uniform) {
+                        MediumEvent::Absorb => break,
+                        MediumEvent::Pass { weight } => {
+                            if dir.y > 0.0 {
+                                transmitted += throughput * weight;
+                            } else {
+                                reflected += throughput * weight;
+                            }
+                            break;
+                        }
+                        MediumEvent::Scatter { t, weight } => {
+                            depth += t * dir.y;
+                            throughput *= weight;
+                            let u = (uniform.sample(), uniform.sample());
+                            dir = medium.phase().sample(&dir, u);
+                        }
+                    }
+                }
+            }
+            let scale = 1.0 / samples as f32;
+            (reflected * scale, transmitted * scale)
+        };
+
+        // A white slab neither absorbs nor creates energy.
+        let white = HomogeneousMedium::from_coefficients(
+            ColorRgb::BLACK,
+            ColorRgb::new(2.0, 2.0, 2.0),
+            0.3,
+        );
+        let (reflected, transmitted) = walk(&white, 1.0, 20_000);
+        let total = reflected + transmitted;
+        assert_abs_diff_eq!(total.r(), 1.0, epsilon = 0.02);
+        assert_abs_diff_eq!(total.g(), 1.0, epsilon = 0.02);
+        assert_abs_diff_eq!(total.b(), 1.0, epsilon = 0.02);
+
+        // A subsurface medium reflects its color from a semi-infinite slab.
+        for color in [0.2, 0.5, 0.8] {
+            let color = ColorRgb::new(color, color, color);
+            let medium = HomogeneousMedium::subsurface(color, ColorRgb::WHITE);
+            let (reflected, _) = walk(&medium, f32::INFINITY, 20_000);
+            assert_abs_diff_eq!(reflected.r(), color.r(), epsilon = 0.02);
+        }
+    }
+}
@@ -4,14 +4,27 @@ use super::*;
 // Modules
 //
 
+mod adaptive;
+mod aov;
 mod bvh;
 pub mod bxdfs;
+mod denoiser;
+mod display;
+mod environment;
 mod exposure;
+mod filter;
+mod ggx_albedo;
 mod intersection;
+mod medium;
+mod post;
 mod ray;
 mod sampling;
+mod sheen_albedo;
 mod sky;
+mod solar;
+mod spectrum;
 mod triangle;
+mod white_balance;
 
 use ray::*;
 use triangle::*;
@@ -20,9 +33,18 @@ use triangle::*;
 // Re-exports
 //
 
-pub(crate) use exposure::Exposure;
+pub(crate) use aov::{AovKind, Aovs};
+pub(crate) use denoiser::{denoise, DenoiserParams};
+pub(crate) use display::DisplayTransform;
+pub(crate) use environment::{EnvironmentMap, EnvironmentParams, EnvironmentState};
+pub(crate) use exposure::{Exposure, ExposureMode};
+pub(crate) use filter::FilterParams;
+pub(crate) use medium::{FogParams, MediumParams, MeshMedium};
+pub(crate) use post::{post_process, PostParams};
 pub(crate) use sampling::{HemisphereSampler, UniformSampler};
-pub(crate) use sky::{SkyParams, SkyState};
+pub(crate) use sky::{Sky, SkyModelKind, SkyParams, SkySource, SkyState};
+pub(crate) use solar::SolarPosition;
+pub(crate) use white_balance::WhiteBalance;
 
 pub struct Scene {
     bvh_nodes: Vec<bvh::Node>,
@@ -37,7 +59,7 @@ impl Scene {
             .map(rds::Mesh::triangle_count)
             .sum::<u32>();
         let mut triangles = Vec::with_capacity(max_triangle_count as usize);
-        for mesh in &rds_scene.meshes {
+        for (mesh_index, mesh) in rds_scene.meshes.iter().enumerate() {
             for triangle in &mesh.triangles {
                 let position_0 = mesh.positions[triangle[0] as usize];
                 let position_1 = mesh.positions[triangle[1] as usize];
@@ -48,6 +70,9 @@ impl Scene {
                 let normal_0 = mesh.normals[triangle[0] as usize];
                 let normal_1 = mesh.normals[triangle[1] as usize];
                 let normal_2 = mesh.normals[triangle[2] as usize];
+                let color_0 = mesh.colors[triangle[0] as usize];
+                let color_1 = mesh.colors[triangle[1] as usize];
+                let color_2 = mesh.colors[triangle[2] as usize];
 
                 let position_0 = mesh.transform.transform_point(&position_0);
                 let position_1 = mesh.transform.transform_point(&position_1);
@@ -60,7 +85,9 @@ impl Scene {
                     positions: [position_0, position_1, position_2],
                     normals: [normal_0, normal_1, normal_2],
                     tex_coords: [tex_coord_0, tex_coord_1, tex_coord_2],
+                    colors: [color_0, color_1, color_2],
                     material: mesh.material,
+                    mesh: mesh_index as u32,
                 });
             }
         }
@@ -76,6 +103,17 @@ pub struct Params {
     pub samples_per_pixel: u32,
     pub max_bounce_count: u32,
     pub seed: u64,
+    // Trace paths at sampled wavelengths instead of RGB.
+    pub spectral: bool,
+    // Stop sampling tiles once their relative error is below the threshold,
+    // and spend the samples on noisier tiles instead.
+    pub adaptive_threshold: Option<f32>,
+    // Samples every pixel gets before adaptive sampling can stop.
+    pub min_samples_per_pixel: u32,
+    pub filter: FilterParams,
+    // Linear color space the radiance is computed in. Textures, the sky and
+    // media are given in sRGB and converted to it.
+    pub working_space: ColorSpace,
 }
 
 impl Default for Params {
@@ -84,6 +122,11 @@ impl Default for Params {
             samples_per_pixel: 64,
             max_bounce_count: 5,
             seed: 0,
+            spectral: false,
+            adaptive_threshold: None,
+            min_samples_per_pixel: 16,
+            filter: FilterParams::default(),
+            working_space: ColorSpace::default(),
         }
     }
 }
@@ -94,10 +137,14 @@ pub struct Input {
     pub image_size: (u32, u32),
     pub hemisphere_sampler: sampling::HemisphereSampler,
     pub dyn_scene: rds::DynamicScene,
-    pub visualize_normals: bool,
-    pub tonemapping: bool,
+    pub tonemapper: Tonemapper,
     pub exposure: Exposure,
-    pub sky_params: SkyParams,
+    pub white_balance: WhiteBalance,
+    pub denoiser: Option<DenoiserParams>,
+    pub post: PostParams,
+    pub sky: SkySource,
+    pub fog: Option<FogParams>,
+    pub mesh_media: Vec<MeshMedium>,
     pub salt: Option<u64>,
 }
 
@@ -108,10 +155,14 @@ impl Default for Input {
             image_size: (0, 0),
             hemisphere_sampler: sampling::HemisphereSampler::default(),
             dyn_scene: rds::DynamicScene::default(),
-            visualize_normals: false,
-            tonemapping: true,
+            tonemapper: Tonemapper::default(),
             exposure: Exposure::default(),
-            sky_params: SkyParams::default(),
+            white_balance: WhiteBalance::default(),
+            denoiser: None,
+            post: PostParams::default(),
+            sky: SkySource::default(),
+            fog: None,
+            mesh_media: vec![],
             salt: None,
         }
     }
@@ -131,20 +182,38 @@ impl Input {
 
 #[derive(Clone, Copy)]
 pub struct SamplingStatus {
+    // Average samples per pixel so far.
     index: u32,
     count: u32,
+    done: bool,
 }
 
 impl SamplingStatus {
     pub fn new() -> Self {
-        Self { index: 0, count: 0 }
+        Self {
+            index: 0,
+            count: 0,
+            done: false,
+        }
+    }
+
+    pub fn index(&self) -> u32 {
+        self.index
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
     }
 }
 
 impl GuiElement for SamplingStatus {
     fn gui(&mut self, ui: &imgui::Ui) {
         let style = ui.clone_style();
-        let progress = self.index as f32 / self.count as f32;
+        let progress = if self.done {
+            1.0
+        } else {
+            self.index as f32 / self.count as f32
+        };
         imgui::ProgressBar::new(progress).size([0.0, 0.0]).build(ui);
         ui.same_line();
         ui.same_line_with_spacing(0.0, style.item_inner_spacing[0]);
@@ -154,6 +223,14 @@ impl GuiElement for SamplingStatus {
 
 pub struct Output {
     pub image: Vec<ColorRgb>,
+    // Linear radiance, before post-processing, exposure and tonemapping.
+    // Denoised when the denoiser is enabled.
+    pub radiance: Vec<ColorRgb>,
+    pub aovs: Aovs,
+    // Exposure of the image, after metering.
+    pub exposure: Exposure,
+    // Color space of the radiance.
+    pub working_space: ColorSpace,
     pub image_size: (u32, u32),
     pub sampling_status: SamplingStatus,
 }
@@ -180,15 +257,25 @@ impl Raytracer {
             let terminate_recv = terminate_recv;
 
             let mut input = Input::default();
-            let mut sample_index = 0;
+            let mut samples_taken = 0_u64;
             let mut world_from_clip = Mat4::identity();
             let mut camera_position = Point3::origin();
             let mut timer = Instant::now();
             let mut ray_stats = intersection::RayBvhHitStats::default();
             let mut tiles = vec![];
+            let mut tile_sample_counts = vec![];
+            let mut active_tiles = vec![];
             let mut tile_results = vec![];
-            let mut pixel_buffer = Vec::<ColorRgb>::new();
-            let mut sky_state = SkyState::new(&SkyParams::default())?;
+            let mut pixel_buffer = Vec::<adaptive::PixelEstimate>::new();
+            let mut aov_buffer = Vec::<aov::AovAccumulator>::new();
+            let mut previous_ev100 = None;
+            let mut latest_ev100 = None;
+            let mut latest_output: Option<Instant> = None;
+            let mut sky = Sky::new(&SkySource::default())?;
+            let mut media = medium::Media::default();
+            let spectrum = params.spectral.then(spectrum::RgbToSpectrum::new);
+            let filter = filter::PixelFilter::new(params.filter);
+            let working_from_srgb = ColorSpace::conversion(ColorSpace::Srgb, params.working_space);
 
             loop {
                 // Check for termination command.
@@ -215,11 +302,16 @@ impl Raytracer {
                         input = latest_input;
 
                         // Reset sampling state.
-                        sample_index = 0;
+                        samples_taken = 0;
                         pixel_buffer.clear();
                         pixel_buffer.resize(
                             (input.image_size.0 * input.image_size.1) as usize,
-                            ColorRgb::BLACK,
+                            adaptive::PixelEstimate::EMPTY,
+                        );
+                        aov_buffer.clear();
+                        aov_buffer.resize(
+                            (input.image_size.0 * input.image_size.1) as usize,
+                            aov::AovAccumulator::EMPTY,
                         );
 
                         // Reset camera.
@@ -233,8 +325,22 @@ impl Raytracer {
                         world_from_clip = camera_transform * world_from_view * view_from_clip;
                         camera_position = camera_transform.transform_point(&camera.position());
 
+                        // Smooth automatic exposure from the last render.
+                        previous_ev100 = latest_ev100;
+
                         // Reset sky.
-                        sky_state = SkyState::new(&input.sky_params)?;
+                        sky = Sky::new(&input.sky)?;
+
+                        // Reset media. Spectral rendering converts them to
+                        // spectra instead.
+                        media = medium::Media::new(
+                            input.fog.as_ref(),
+                            &input.mesh_media,
+                            rds_scene.meshes.len(),
+                        );
+                        if spectrum.is_none() {
+                            media = media.transform(&working_from_srgb);
+                        }
 
                         // Reset stats.
                         ray_stats = intersection::RayBvhHitStats::default();
@@ -243,9 +349,14 @@ impl Raytracer {
                         tiles = PixelTiles::new(input.image_size.0, input.image_size.1)
                             .collect::<Vec<_>>();
                         tile_results.reserve(tiles.len());
+                        tile_sample_counts.clear();
+                        tile_sample_counts.resize(tiles.len(), 0_u32);
+                        active_tiles.clear();
+                        active_tiles.extend(0..tiles.len());
 
-                        // Reset timer.
+                        // Reset timers.
                         timer = Instant::now();
+                        latest_output = None;
                     }
                 }
 
@@ -255,19 +366,23 @@ impl Raytracer {
                 }
 
                 // Rendering.
-                if sample_index < params.samples_per_pixel {
+                if active_tiles.is_empty() {
+                    // Avoid busy looping.
+                    thread::sleep(Duration::from_millis(1));
+                } else {
                     // Unpack.
                     let image_size = input.image_size;
+                    let pixel_count = u64::from(image_size.0 * image_size.1);
 
-                    // Render tiles.
-                    (0..tiles.len())
-                        .into_par_iter()
-                        .map(|tile_index| {
+                    // Render tiles that still need samples.
+                    active_tiles
+                        .par_iter()
+                        .map(|&tile_index| {
                             let tile = tiles[tile_index];
-                            let (tile_radiance, tile_ray_stats) = tile_radiance(
+                            let (tile_radiance, tile_first_hits, tile_ray_stats) = tile_radiance(
                                 &tile,
                                 image_size,
-                                sample_index,
+                                tile_sample_counts[tile_index],
                                 camera_position,
                                 world_from_clip,
                                 &input,
@@ -276,37 +391,71 @@ impl Raytracer {
                                 &rds_scene,
                                 &input.dyn_scene,
                                 materials,
-                                &sky_state,
+                                &sky,
+                                &media,
+                                spectrum.as_ref(),
+                                &filter,
+                                &working_from_srgb,
                             );
-                            (tile, tile_radiance, tile_ray_stats)
+                            (tile_index, tile_radiance, tile_first_hits, tile_ray_stats)
                         })
                         .collect_into_vec(&mut tile_results);
 
                     // Accumulate results.
-                    for (tile, tile_radiance, tile_ray_stats) in &tile_results {
+                    for (tile_index, tile_radiance, tile_first_hits, tile_ray_stats) in
+                        &tile_results
+                    {
+                        let tile = &tiles[*tile_index];
                         let mut src_pixel_index = 0;
                         for pixel_y in tile.start_y..tile.end_y {
                             for pixel_x in tile.start_x..tile.end_x {
                                 let dst_pixel_index = (pixel_x + pixel_y * image_size.0) as usize;
                                 let src_pixel = tile_radiance[src_pixel_index];
-                                let dst_pixel = &mut pixel_buffer[dst_pixel_index];
-                                *dst_pixel += src_pixel;
+                                pixel_buffer[dst_pixel_index].add(src_pixel);
+                                aov_buffer[dst_pixel_index]
+                                    .add(tile_first_hits[src_pixel_index].as_ref());
                                 src_pixel_index += 1;
                             }
                         }
+                        tile_sample_counts[*tile_index] += 1;
+                        samples_taken += u64::from(tile.pixel_count());
                         ray_stats += *tile_ray_stats;
                     }
 
+                    // Find the tiles that still need samples. Without adaptive
+                    // sampling, every tile gets the same number of samples.
+                    // Otherwise converged tiles are dropped, and the rest
+                    // continue until the sample budget is spent.
+                    let budget = u64::from(params.samples_per_pixel) * pixel_count;
+                    active_tiles.retain(|&tile_index| {
+                        let count = tile_sample_counts[tile_index];
+                        if let Some(threshold) = params.adaptive_threshold {
+                            count < adaptive::MAX_SAMPLE_FACTOR * params.samples_per_pixel
+                                && samples_taken < budget
+                                && !adaptive::tile_converged(
+                                    &tiles[tile_index],
+                                    image_size.0,
+                                    &pixel_buffer,
+                                    threshold,
+                                    params.min_samples_per_pixel,
+                                )
+                        } else {
+                            count < params.samples_per_pixel
+                        }
+                    });
+
+                    // The first and the final output are always sent.
+                    let done = active_tiles.is_empty();
+                    if !done && matches!(latest_output, Some(t) if t.elapsed() < OUTPUT_INTERVAL) {
+                        continue;
+                    }
+                    latest_output = Some(Instant::now());
+
                     // Normalize the current image, send it.
-                    let normalization_factor = 1.0 / (sample_index + 1) as f32;
                     let mut max_radiance = 0.0_f32;
-                    let image = pixel_buffer
-                        .clone()
-                        .into_iter()
-                        .map(|sample| {
-                            // Averaging samples.
-                            sample * normalization_factor
-                        })
+                    let radiance = pixel_buffer
+                        .iter()
+                        .map(adaptive::PixelEstimate::mean)
                         .map(|sample| {
                             // Statistics.
                             max_radiance = max_radiance.max(sample.r());
@@ -314,42 +463,58 @@ impl Raytracer {
                             max_radiance = max_radiance.max(sample.b());
                             sample
                         })
-                        .map(|sample| {
-                            // Exposure and tonemapping.
-                            let sample = input.exposure.expose(sample);
-                            if input.tonemapping {
-                                sample.tonemap()
-                            } else {
-                                sample
-                            }
-                        })
-                        .collect();
-                    sample_index += 1;
+                        .collect::<Vec<_>>();
+                    let aovs = Aovs::resolve(&aov_buffer, &pixel_buffer);
+
+                    // Denoise.
+                    let radiance = if let Some(denoiser) = &input.denoiser {
+                        denoise(denoiser, &radiance, &aovs, image_size)
+                    } else {
+                        radiance
+                    };
+
+                    // Lens effects only go into the image, the radiance stays
+                    // un-graded for the outputs.
+                    let lens = post_process(&input.post, &radiance, image_size);
+
+                    // Exposure, white balance and tonemapping.
+                    let exposure = input.exposure.metered(&lens, previous_ev100);
+                    latest_ev100 = Some(exposure.ev100());
+                    let display = DisplayTransform::new(
+                        params.working_space,
+                        exposure,
+                        input.white_balance,
+                        input.tonemapper,
+                        DisplayEncoding::Srgb,
+                    );
+                    let image = lens.iter().map(|&sample| display.linear(sample)).collect();
                     output_send.send(Output {
                         image,
+                        radiance,
+                        aovs,
+                        exposure,
+                        working_space: params.working_space,
                         image_size,
                         sampling_status: SamplingStatus {
-                            index: sample_index,
+                            index: (samples_taken / pixel_count) as u32,
                             count: params.samples_per_pixel,
+                            done,
                         },
                     })?;
 
                     // Rendering has completed.
-                    if sample_index == params.samples_per_pixel {
+                    if done {
                         let elapsed = timer.elapsed().as_secs_f64();
                         debug!(
                             "Rendering took {:.03} s, {:.03} rays/s, \
                             {:.03} samples/s, {:.03} max radiance",
                             elapsed,
                             ray_stats.rays as f64 / elapsed,
-                            f64::from(params.samples_per_pixel) / elapsed,
+                            samples_taken as f64 / pixel_count as f64 / elapsed,
                             max_radiance
                         );
                         debug!("Stats:\n{ray_stats}");
                     }
-                } else {
-                    // Avoid busy looping.
-                    thread::sleep(Duration::from_millis(1));
                 }
             }
 
@@ -394,6 +559,41 @@ impl Raytracer {
 
 const PIXEL_TILE_SIZE: usize = 16;
 
+// Resolving the AOVs, denoising and post-processing take longer than a pass
+// over a small image, so progressive outputs are sent at most this often.
+const OUTPUT_INTERVAL: Duration = Duration::from_millis(250);
+
+// Media boundaries don't count as bounces, but we still need a limit in case a
+// ray gets stuck crossing the same surface.
+const MAX_MEDIUM_INTERFACE_COUNT: u32 = 64;
+const MEDIUM_INTERFACE_OFFSET: f32 = 1e-4;
+
+// Random walks inside subsurface materials take many steps before they leave,
+// so they get their own limit instead of eating into the bounce count.
+const MAX_SUBSURFACE_WALK_LENGTH: u32 = 256;
+
+// Blends may reference other blends, the limit guards against cycles.
+const MAX_BLEND_DEPTH: u32 = 8;
+
+// The medium the ray is currently travelling through.
+#[derive(Clone, Copy)]
+enum Interior<'a> {
+    // Inside a mesh filled with a medium.
+    Medium(&'a medium::HomogeneousMedium),
+    // Inside a subsurface material. The medium is built from the material
+    // textures at the point where the ray entered.
+    Subsurface(medium::HomogeneousMedium),
+}
+
+impl Interior<'_> {
+    fn medium(&self) -> &medium::HomogeneousMedium {
+        match self {
+            Interior::Medium(medium) => medium,
+            Interior::Subsurface(medium) => medium,
+        }
+    }
+}
+
 const fn pixel_tile_count() -> usize {
     PIXEL_TILE_SIZE * PIXEL_TILE_SIZE
 }
@@ -410,14 +610,24 @@ fn tile_radiance(
     rds_scene: &rds::Scene,
     dyn_scene: &rds::DynamicScene,
     materials: &[rds::Material],
-    sky_state: &SkyState,
-) -> ([ColorRgb; pixel_tile_count()], intersection::RayBvhHitStats) {
+    sky: &Sky,
+    media: &medium::Media,
+    spectrum: Option<&spectrum::RgbToSpectrum>,
+    filter: &filter::PixelFilter,
+    working_from_srgb: &Mat3,
+) -> (
+    [ColorRgb; pixel_tile_count()],
+    [Option<aov::FirstHit>; pixel_tile_count()],
+    intersection::RayBvhHitStats,
+) {
     let mut tile_radiance: [ColorRgb; pixel_tile_count()] = [ColorRgb::BLACK; pixel_tile_count()];
+    let mut tile_first_hits: [Option<aov::FirstHit>; pixel_tile_count()] =
+        [None; pixel_tile_count()];
     let mut tile_pixel_index = 0;
     let mut tile_ray_stats = intersection::RayBvhHitStats::default();
     for pixel_y in tile.start_y..tile.end_y {
         for pixel_x in tile.start_x..tile.end_x {
-            let (radiance, ray_stats) = radiance(
+            let (radiance, first_hit, ray_stats) = radiance(
                 (pixel_x, pixel_y),
                 image_size,
                 sample_index,
@@ -429,15 +639,20 @@ fn tile_radiance(
                 rds_scene,
                 dyn_scene,
                 materials,
-                sky_state,
+                sky,
+                media,
+                spectrum,
+                filter,
+                working_from_srgb,
             );
             tile_radiance[tile_pixel_index] = radiance;
+            tile_first_hits[tile_pixel_index] = first_hit;
             tile_pixel_index += 1;
             tile_ray_stats += ray_stats;
         }
     }
 
-    (tile_radiance, tile_ray_stats)
+    (tile_radiance, tile_first_hits, tile_ray_stats)
 }
 
 fn radiance(
@@ -452,8 +667,16 @@ fn radiance(
     rds_scene: &rds::Scene,
     dyn_scene: &rds::DynamicScene,
     materials: &[rds::Material],
-    sky_state: &SkyState,
-) -> (ColorRgb, intersection::RayBvhHitStats) {
+    sky: &Sky,
+    media: &medium::Media,
+    spectrum: Option<&spectrum::RgbToSpectrum>,
+    filter: &filter::PixelFilter,
+    working_from_srgb: &Mat3,
+) -> (
+    ColorRgb,
+    Option<aov::FirstHit>,
+    intersection::RayBvhHitStats,
+) {
     use bxdfs::Bxdf;
 
     // Unpack.
@@ -466,23 +689,59 @@ fn radiance(
     let seed = u64::from(sample_index + 1) * u64::from(pixel.0 + pixel.1 * image_size.0);
     let mut uniform = sampling::UniformSampler::new_with_seed(seed);
 
-    // Create primary ray.
+    // Create primary ray, offset from the pixel center by the filter.
+    let ((filter_x, filter_y), filter_weight) = filter.sample((uniform.sample(), uniform.sample()));
     let mut ray = {
         sampling::primary_ray(
             pixel,
             image_size,
             &camera_position,
             &world_from_clip,
-            uniform.sample(),
-            uniform.sample(),
+            0.5 + filter_x,
+            0.5 + filter_y,
         )
     };
 
+    // Sample wavelengths. Colors and media are converted to spectra at the
+    // sampled wavelengths, and the radiance back to RGB at the end.
+    let wavelengths =
+        spectrum.map(|table| spectrum::SampledWavelengths::sample(table, uniform.sample()));
+    let spectral_media;
+    let media = if let Some(wavelengths) = &wavelengths {
+        spectral_media = media.spectral(wavelengths);
+        &spectral_media
+    } else {
+        media
+    };
+
+    // Converts sRGB colors to the working space. Spectral paths convert the
+    // radiance at the end instead.
+    let path_from_srgb = if wavelengths.is_some() {
+        Mat3::identity()
+    } else {
+        *working_from_srgb
+    };
+    let to_working = |color: ColorRgb| color.transform(&path_from_srgb);
+
     // Main tracing loop.
     let mut radiance = ColorRgb::BLACK;
     let mut throughput = ColorRgb::WHITE;
-    for _ in 0..params.max_bounce_count {
-        // Hit scene.
+    let mut bounce_count = 0;
+    let mut interface_count = 0;
+    let mut walk_length = 0;
+    let mut interior: Option<Interior> = None;
+    // Pdf of the direction sampled at the previous vertex, if the sky light
+    // was sampled there as well. Hitting the light is then weighted with MIS.
+    let mut scatter_pdf: Option<f32> = None;
+    let mut first_hit = None;
+    while bounce_count < params.max_bounce_count {
+        // Hit scene. Inside a closed mesh we must also see its back faces to
+        // find where the ray leaves.
+        let culling = if interior.is_some() {
+            intersection::Culling::None
+        } else {
+            intersection::Culling::Backface
+        };
         let mut closest_hit = 0.0;
         let mut barycentrics = Vec3::zeros();
         let mut triangle_index = 0;
@@ -490,15 +749,96 @@ fn radiance(
             &ray,
             &scene.bvh_nodes,
             &scene.triangles,
+            culling,
             &mut closest_hit,
             &mut barycentrics,
             &mut triangle_index,
             &mut ray_stats,
         );
 
+        // Participating media. The interior of a mesh takes precedence over
+        // the global fog.
+        let t_max = if found_hit {
+            closest_hit
+        } else {
+            f32::INFINITY
+        };
+        let event = if let Some(interior) = &interior {
+            Some(interior.medium().delta_track(0.0, t_max, &mut uniform))
+        } else if let Some(fog) = media.fog() {
+            fog.interval(&ray, t_max)
+                .map(|(t_min, t_max)| fog.medium().delta_track(t_min, t_max, &mut uniform))
+        } else {
+            None
+        };
+        match event {
+            Some(medium::MediumEvent::Scatter { t, weight }) => {
+                let phase = if let Some(interior) = &interior {
+                    interior.medium().phase()
+                } else {
+                    media
+                        .fog()
+                        .expect("Scattered without a medium")
+                        .medium()
+                        .phase()
+                };
+                throughput *= weight;
+                ray.origin += t * ray.dir.into_inner();
+
+                // Sample the sky light. Subsurface walks stay inside closed
+                // meshes, so the sky is never visible from them.
+                let subsurface = matches!(interior, Some(Interior::Subsurface(_)));
+                let light_sample = (!subsurface && bounce_count + 1 < params.max_bounce_count)
+                    .then(|| sky.sample_light((uniform.sample(), uniform.sample())))
+                    .flatten();
+                if let Some((wi_world, light_pdf)) = light_sample {
+                    let phase_pdf = phase.eval(ray.dir.dot(&wi_world));
+                    let transmittance = shadow_transmittance(
+                        Ray {
+                            origin: ray.origin,
+                            dir: wi_world,
+                        },
+                        interior,
+                        scene,
+                        media,
+                        &mut uniform,
+                        &mut ray_stats,
+                    );
+                    let weight = sampling::power_heuristic(light_pdf, phase_pdf) / light_pdf;
+                    radiance += throughput
+                        * transmittance
+                        * to_working(sky.light_radiance(&wi_world, wavelengths.as_ref()))
+                        * (phase_pdf * weight);
+                }
+
+                let wi_world = phase.sample(&ray.dir, (uniform.sample(), uniform.sample()));
+                scatter_pdf = (!subsurface).then(|| phase.eval(ray.dir.dot(&wi_world)));
+                ray.dir = wi_world;
+                if subsurface {
+                    walk_length += 1;
+                    if walk_length > MAX_SUBSURFACE_WALK_LENGTH {
+                        break;
+                    }
+                } else {
+                    bounce_count += 1;
+                }
+                continue;
+            }
+            Some(medium::MediumEvent::Absorb) => break,
+            Some(medium::MediumEvent::Pass { weight }) => throughput *= weight,
+            None => {}
+        }
+
         // Special case: ray hit the sky.
         if !found_hit {
-            radiance += throughput * sky_state.radiance(&ray.dir);
+            let light_weight = if let Some(scatter_pdf) = scatter_pdf {
+                sampling::power_heuristic(scatter_pdf, sky.light_pdf(&ray.dir))
+            } else {
+                1.0
+            };
+            let light_radiance = light_weight * sky.light_radiance(&ray.dir, wavelengths.as_ref());
+            radiance += throughput
+                * to_working(sky.radiance(&ray.dir, wavelengths.as_ref()) + light_radiance);
             break;
         }
 
@@ -506,12 +846,90 @@ fn radiance(
         let triangle = &scene.triangles[triangle_index as usize];
         let tex_coord = triangle.interpolated_tex_coord(&barycentrics);
         let normal = triangle.interpolated_normal(&barycentrics);
+        let hit_point = ray.origin + closest_hit * ray.dir.into_inner();
+
+        // Special case: random walk reached the boundary of a subsurface
+        // material. The ray refracts diffusely out of the mesh.
+        if let Some(Interior::Subsurface(_)) = interior {
+            let outward = if ray.dir.dot(&normal) > 0.0 {
+                normal
+            } else {
+                -normal
+            };
+            let onb = sampling::OrthonormalBasis::new(&outward);
+            let wo_local = bxdfs::LocalVector::local_from_world(onb.local_from_world(), &-ray.dir);
+            let transmission = bxdfs::Lambertian::new(&bxdfs::LambertianParams {
+                hemisphere,
+                base_color: ColorRgb::WHITE,
+            });
+            let Some(sample) = transmission.sample(&wo_local, (uniform.sample(), uniform.sample()))
+            else {
+                break;
+            };
+            let wi_world = sample.wi.world_from_local(onb.world_from_local());
+            throughput *= sample.r * wi_world.dot(&outward) / sample.pdf;
+            ray.origin = hit_point + MEDIUM_INTERFACE_OFFSET * outward.into_inner();
+            ray.dir = wi_world;
+            interior = None;
+            walk_length = 0;
+            scatter_pdf = None;
+            continue;
+        }
+
+        // Special case: ray crossed the boundary of a medium. The boundary is
+        // index-matched, so the ray continues through it unchanged.
+        if let Some(mesh_medium) = media.mesh(triangle.mesh) {
+            interface_count += 1;
+            if interface_count > MAX_MEDIUM_INTERFACE_COUNT {
+                break;
+            }
+            let entering = ray.dir.dot(&normal) < 0.0;
+            interior = if entering {
+                Some(Interior::Medium(mesh_medium))
+            } else {
+                None
+            };
+            ray.origin += (closest_hit + MEDIUM_INTERFACE_OFFSET) * ray.dir.into_inner();
+            continue;
+        }
+
+        // Resolve blends.
+        let (material_index, model) = resolve_blend(
+            dyn_scene,
+            triangle.material,
+            |material_index, mask| match mask {
+                rds::BlendMask::Texture => {
+                    let blend_mask = materials[material_index as usize].blend_mask;
+                    rds::dynamic_sample(rds_scene, dyn_scene, blend_mask, tex_coord).r()
+                }
+                rds::BlendMask::VertexColor => triangle.interpolated_color(&barycentrics).r(),
+            },
+            &mut uniform,
+        );
 
         // Sample textures.
-        let material = &materials[triangle.material as usize];
-        let model = rds::dynamic_model(dyn_scene, triangle.material);
-        let base_color =
-            rds::dynamic_sample(rds_scene, dyn_scene, material.base_color, tex_coord).rgb();
+        let material = &materials[material_index as usize];
+        let base_color = to_working(
+            rds::dynamic_sample_color(rds_scene, dyn_scene, material.base_color, tex_coord).rgb(),
+        );
+
+        // Record the first surface seen by the primary ray.
+        if bounce_count == 0 && first_hit.is_none() {
+            let albedo = match model {
+                rds::MaterialModel::Conductor(preset) => to_working(preset.reflectance()),
+                _ => base_color,
+            };
+            first_hit = Some(aov::FirstHit {
+                albedo,
+                normal: normal.into_inner(),
+                position: hit_point,
+                depth: (hit_point - camera_position).norm(),
+                mesh: triangle.mesh,
+                material: material_index,
+            });
+        }
+
+        let base_color = wavelengths.map_or(base_color, |w| w.reflectance(base_color));
         let roughness =
             rds::dynamic_sample(rds_scene, dyn_scene, material.roughness, tex_coord).r();
         let metallic = rds::dynamic_sample(rds_scene, dyn_scene, material.metallic, tex_coord).r();
@@ -521,6 +939,25 @@ fn radiance(
         let sheen = rds::dynamic_sample(rds_scene, dyn_scene, material.sheen, tex_coord).r();
         let sheen_tint =
             rds::dynamic_sample(rds_scene, dyn_scene, material.sheen_tint, tex_coord).r();
+        let sheen_color = to_working(
+            rds::dynamic_sample_color(rds_scene, dyn_scene, material.sheen_color, tex_coord).rgb(),
+        );
+        let sheen_color = wavelengths.map_or(sheen_color, |w| w.reflectance(sheen_color));
+        let sheen_roughness =
+            rds::dynamic_sample(rds_scene, dyn_scene, material.sheen_roughness, tex_coord).r();
+        let thin_film = bxdfs::ThinFilm {
+            weight: rds::dynamic_sample(rds_scene, dyn_scene, material.iridescence, tex_coord).r(),
+            ior: rds::dynamic_sample(rds_scene, dyn_scene, material.iridescence_ior, tex_coord).r(),
+            thickness: rds::dynamic_sample(
+                rds_scene,
+                dyn_scene,
+                material.iridescence_thickness,
+                tex_coord,
+            )
+            .r(),
+            wavelengths: wavelengths.map(|w| w.lambdas()),
+            working_from_srgb: path_from_srgb,
+        };
         let anisotropic = 0.0;
 
         // Orthonormal basis.
@@ -531,92 +968,186 @@ fn radiance(
         let wo_local = bxdfs::LocalVector::local_from_world(onb.local_from_world(), &wo_world);
 
         // Evaluate material.
-        let bxdf_sample = match model {
+        let lambertian;
+        let oren_nayar;
+        let disney;
+        let conductor;
+        let bxdf: &dyn bxdfs::Bxdf = match model {
             rds::MaterialModel::Diffuse => {
-                let bxdf = bxdfs::Lambertian::new(&bxdfs::LambertianParams {
+                lambertian = bxdfs::Lambertian::new(&bxdfs::LambertianParams {
                     hemisphere,
                     base_color,
                 });
-                match bxdf.sample(&wo_local, (uniform.sample(), uniform.sample())) {
-                    Some(s) => s,
-                    None => break,
-                }
+                &lambertian
             }
-            rds::MaterialModel::Disney => {
-                // Todo: pre-calculate these elsewhere.
-                let diffuse_brdf = bxdfs::DisneyDiffuse::new(&bxdfs::DisneyDiffuseParams {
+            rds::MaterialModel::OrenNayar => {
+                oren_nayar = bxdfs::OrenNayar::new(&bxdfs::OrenNayarParams {
                     hemisphere,
                     base_color,
                     roughness,
                 });
-                let specular_brdf = bxdfs::DisneySpecular::new(&bxdfs::DisneySpecularParams {
+                &oren_nayar
+            }
+            rds::MaterialModel::Disney | rds::MaterialModel::Blend => {
+                // Todo: pre-calculate these elsewhere.
+                disney = bxdfs::Disney::new(&bxdfs::DisneyParams {
+                    hemisphere,
                     base_color,
                     metallic,
                     specular,
                     specular_tint,
                     roughness,
                     anisotropic,
-                });
-                let sheen_brdf = bxdfs::DisneySheen::new(&bxdfs::DisneySheenParams {
-                    hemisphere,
-                    base_color,
                     sheen,
                     sheen_tint,
+                    sheen_color,
+                    sheen_roughness,
+                    thin_film,
                 });
-
-                // Weights.
-                let diffuse_weight = (1.0 - metallic) * (1.0 - specular);
-
-                // Select which BRDF to sample from.
-                let maybe_sample = if uniform.sample() < diffuse_weight {
-                    diffuse_brdf.sample(&wo_local, (uniform.sample(), uniform.sample()))
-                } else {
-                    specular_brdf.sample(&wo_local, (uniform.sample(), uniform.sample()))
+                &disney
+            }
+            rds::MaterialModel::Subsurface => {
+                let subsurface_color = to_working(
+                    rds::dynamic_sample_color(
+                        rds_scene,
+                        dyn_scene,
+                        material.subsurface_color,
+                        tex_coord,
+                    )
+                    .rgb(),
+                );
+                let subsurface_radius = rds::dynamic_sample(
+                    rds_scene,
+                    dyn_scene,
+                    material.subsurface_radius,
+                    tex_coord,
+                )
+                .rgb();
+                let (subsurface_color, subsurface_radius) = match &wavelengths {
+                    Some(w) => (
+                        w.reflectance(subsurface_color),
+                        w.unbounded(subsurface_radius),
+                    ),
+                    None => (subsurface_color, subsurface_radius),
                 };
 
-                // Check if the sampled path had zero probability of happening,
-                // which means zero contribution. In that case we must terminate
-                // the path.
-                let Some(sample) = maybe_sample else { break };
-
-                // Sample reflectance and PDFs from all BRDFs.
-                let wi_local = sample.wi;
-                let diffuse_r = diffuse_brdf.eval(&wo_local, &wi_local);
-                let specular_r = specular_brdf.eval(&wo_local, &wi_local);
-                let sheen_r = sheen_brdf.eval(&wo_local, &wi_local);
-                let diffuse_pdf = diffuse_brdf.pdf(&wo_local, &wi_local);
-                let specular_pdf = specular_brdf.pdf(&wo_local, &wi_local);
-                let sheen_pdf = sheen_brdf.pdf(&wo_local, &wi_local);
-
-                // Weight according to Disney model.
-                let r = diffuse_r + sheen_r + specular_r;
-                let pdf = diffuse_pdf + sheen_pdf + specular_pdf;
-
-                bxdfs::Sample {
-                    wi: wi_local,
-                    r,
-                    pdf,
+                // The surface is a dielectric coat on top of the medium. Light
+                // is either reflected by the specular lobe, or refracted
+                // diffusely into the mesh, where it takes a random walk until
+                // it leaves the mesh again. Lobes are selected by Schlick's
+                // Fresnel, so the specular lobe is divided by it to keep the
+                // estimate unbiased.
+                let specular_brdf = bxdfs::DisneySpecular::new(&bxdfs::DisneySpecularParams {
+                    base_color,
+                    metallic: 0.0,
+                    specular,
+                    specular_tint,
+                    roughness,
+                    anisotropic,
+                    thin_film,
+                });
+                let f0 = 0.08 * specular;
+                let fresnel = f0 + (1.0 - f0) * (1.0 - wo_local.0.y.clamp(0.0, 1.0)).powi(5);
+                if uniform.sample() < fresnel {
+                    let Some(sample) =
+                        specular_brdf.sample(&wo_local, (uniform.sample(), uniform.sample()))
+                    else {
+                        break;
+                    };
+                    let wi_world = sample.wi.world_from_local(onb.world_from_local());
+                    let cos_theta = wi_world.dot(&normal).abs();
+                    throughput *= sample.r * cos_theta / (sample.pdf * fresnel);
+                    ray.origin += 0.999 * closest_hit * ray.dir.into_inner();
+                    ray.dir = wi_world;
+                } else {
+                    let transmission = bxdfs::Lambertian::new(&bxdfs::LambertianParams {
+                        hemisphere,
+                        base_color: ColorRgb::WHITE,
+                    });
+                    let Some(sample) =
+                        transmission.sample(&wo_local, (uniform.sample(), uniform.sample()))
+                    else {
+                        break;
+                    };
+                    let wi_world = sample.wi.world_from_local(onb.world_from_local());
+                    let cos_theta = wi_world.dot(&normal);
+                    throughput *= sample.r * cos_theta / sample.pdf;
+                    ray.origin = hit_point - MEDIUM_INTERFACE_OFFSET * normal.into_inner();
+                    ray.dir = -wi_world;
+                    interior = Some(Interior::Subsurface(medium::HomogeneousMedium::subsurface(
+                        subsurface_color,
+                        subsurface_radius,
+                    )));
                 }
+                bounce_count += 1;
+                scatter_pdf = None;
+                continue;
+            }
+            rds::MaterialModel::Conductor(preset) => {
+                let (eta, k) = match &wavelengths {
+                    Some(w) => preset.spectral_ior(w.lambdas()),
+                    None => preset.ior(),
+                };
+                conductor = bxdfs::Conductor::new(&bxdfs::ConductorParams {
+                    eta,
+                    k,
+                    roughness,
+                    anisotropic,
+                    thin_film,
+                    working_from_srgb: path_from_srgb,
+                });
+                &conductor
             }
         };
-        let wi_world = bxdf_sample.wi.world_from_local(onb.world_from_local());
 
-        // Prepare next direction, adjust closest hit to avoid spawning the next
+        // Prepare next origin, adjust closest hit to avoid spawning the next
         // ray inside the surface.
         ray.origin += 0.999 * closest_hit * ray.dir.into_inner();
+
+        // Sample the sky light.
+        let light_sample = (bounce_count + 1 < params.max_bounce_count)
+            .then(|| sky.sample_light((uniform.sample(), uniform.sample())))
+            .flatten();
+        if let Some((wi_world, light_pdf)) = light_sample {
+            let cos_theta = wi_world.dot(&normal);
+            if cos_theta > 0.0 {
+                let wi_local =
+                    bxdfs::LocalVector::local_from_world(onb.local_from_world(), &wi_world);
+                let r = bxdf.eval(&wo_local, &wi_local);
+                let bxdf_pdf = bxdf.pdf(&wo_local, &wi_local);
+                let transmittance = shadow_transmittance(
+                    Ray {
+                        origin: ray.origin,
+                        dir: wi_world,
+                    },
+                    interior,
+                    scene,
+                    media,
+                    &mut uniform,
+                    &mut ray_stats,
+                );
+                let weight = sampling::power_heuristic(light_pdf, bxdf_pdf) / light_pdf;
+                radiance += throughput
+                    * transmittance
+                    * to_working(sky.light_radiance(&wi_world, wavelengths.as_ref()))
+                    * r
+                    * (cos_theta * weight);
+            }
+        }
+
+        // Check if the sampled path had zero probability of happening, which
+        // means zero contribution. In that case we must terminate the path.
+        let Some(bxdf_sample) = bxdf.sample(&wo_local, (uniform.sample(), uniform.sample())) else {
+            break;
+        };
+        let wi_world = bxdf_sample.wi.world_from_local(onb.world_from_local());
         ray.dir = wi_world;
+        scatter_pdf = Some(bxdf_sample.pdf);
 
         // Update throughput.
+        bounce_count += 1;
         let cos_theta = wi_world.dot(&normal).abs();
-        if input.visualize_normals {
-            throughput *= ColorRgb::new(
-                0.5 * (normal.x + 1.0) * cos_theta,
-                0.5 * (normal.y + 1.0) * cos_theta,
-                0.5 * (normal.z + 1.0) * cos_theta,
-            );
-        } else {
-            throughput *= bxdf_sample.r * cos_theta / bxdf_sample.pdf;
-        }
+        throughput *= bxdf_sample.r * cos_theta / bxdf_sample.pdf;
 
         // Report invalid values.
         assert!(
@@ -632,9 +1163,105 @@ fn radiance(
             bxdf_sample
         );
     }
+    let radiance = wavelengths.map_or(radiance, |w| {
+        w.to_linear_srgb(radiance).transform(working_from_srgb)
+    });
+    let radiance = radiance * filter_weight;
     assert!(radiance.is_finite(), "radiance={radiance}");
 
-    (radiance, ray_stats)
+    (radiance, first_hit, ray_stats)
+}
+
+// Each blend picks one of its materials with the probability given by its
+// mask, so only one material is evaluated per hit. A blend that picks itself,
+// or that is still unresolved after the depth limit, is evaluated as Disney
+// with its own textures.
+fn resolve_blend(
+    dyn_scene: &rds::DynamicScene,
+    mut material_index: u32,
+    mask: impl Fn(u32, rds::BlendMask) -> f32,
+    uniform: &mut sampling::UniformSampler,
+) -> (u32, rds::MaterialModel) {
+    let mut model = rds::dynamic_model(dyn_scene, material_index);
+    for _ in 0..MAX_BLEND_DEPTH {
+        if model != rds::MaterialModel::Blend {
+            break;
+        }
+        let blend = dyn_scene.materials[material_index as usize].blend;
+        let mask = mask(material_index, blend.mask);
+        let picked = blend.materials[usize::from(uniform.sample() < mask)];
+        if picked == material_index {
+            break;
+        }
+        material_index = picked;
+        model = rds::dynamic_model(dyn_scene, material_index);
+    }
+    (material_index, model)
+}
+
+// Traces a shadow ray towards the sky. Opaque surfaces block the ray, media
+// boundaries are crossed and the media along the way attenuate it.
+fn shadow_transmittance<'a>(
+    mut ray: Ray,
+    mut interior: Option<Interior<'a>>,
+    scene: &Scene,
+    media: &'a medium::Media,
+    uniform: &mut sampling::UniformSampler,
+    ray_stats: &mut intersection::RayBvhHitStats,
+) -> ColorRgb {
+    let mut transmittance = ColorRgb::WHITE;
+    for _ in 0..MAX_MEDIUM_INTERFACE_COUNT {
+        let culling = if interior.is_some() {
+            intersection::Culling::None
+        } else {
+            intersection::Culling::Backface
+        };
+        let mut closest_hit = 0.0;
+        let mut barycentrics = Vec3::zeros();
+        let mut triangle_index = 0;
+        let found_hit = intersection::ray_bvh_hit(
+            &ray,
+            &scene.bvh_nodes,
+            &scene.triangles,
+            culling,
+            &mut closest_hit,
+            &mut barycentrics,
+            &mut triangle_index,
+            ray_stats,
+        );
+
+        // Attenuate.
+        let t_max = if found_hit {
+            closest_hit
+        } else {
+            f32::INFINITY
+        };
+        if let Some(interior) = &interior {
+            transmittance *= interior.medium().ratio_track(0.0, t_max, uniform);
+        } else if let Some(fog) = media.fog() {
+            if let Some((t_min, t_max)) = fog.interval(&ray, t_max) {
+                transmittance *= fog.medium().ratio_track(t_min, t_max, uniform);
+            }
+        }
+        if !found_hit || transmittance.max_element() <= 0.0 {
+            return transmittance;
+        }
+
+        // Cross the boundary of a medium, everything else is opaque.
+        let triangle = &scene.triangles[triangle_index as usize];
+        let Some(mesh_medium) = media.mesh(triangle.mesh) else {
+            return ColorRgb::BLACK;
+        };
+        let normal = triangle.interpolated_normal(&barycentrics);
+        let entering = ray.dir.dot(&normal) < 0.0;
+        interior = if entering {
+            Some(Interior::Medium(mesh_medium))
+        } else {
+            None
+        };
+        ray.origin += (closest_hit + MEDIUM_INTERFACE_OFFSET) * ray.dir.into_inner();
+    }
+    ColorRgb::BLACK
 }
 
 #[derive(Clone, Copy)]
@@ -653,6 +1280,12 @@ struct PixelTiles {
     tile_count: u32,
 }
 
+impl PixelTile {
+    fn pixel_count(&self) -> u32 {
+        (self.end_x - self.start_x) * (self.end_y - self.start_y)
+    }
+}
+
 impl PixelTiles {
     pub fn new(image_w: u32, image_h: u32) -> Self {
         assert!(image_w > 0, "image_w={image_w}");
@@ -714,4 +1347,65 @@ mod tests {
         }
         assert!(hit_pixels.all());
     }
+
+    #[test]
+    fn blend_selection() {
+        // Material 0 blends 1 and 2, material 3 blends itself with 0 and
+        // materials 4 and 5 blend each other.
+        let material = |model, materials| rds::DynamicMaterial {
+            model,
+            base_color: 0,
+            metallic: 0,
+            roughness: 0,
+            specular: 0,
+            specular_tint: 0,
+            sheen: 0,
+            sheen_tint: 0,
+            sheen_color: 0,
+            sheen_roughness: 0,
+            iridescence: 0,
+            iridescence_ior: 0,
+            iridescence_thickness: 0,
+            subsurface_color: 0,
+            subsurface_radius: 0,
+            blend_mask: 0,
+            blend: rds::MaterialBlend {
+                materials,
+                mask: rds::BlendMask::VertexColor,
+            },
+        };
+        let blend = |materials| material(rds::MaterialModel::Blend, materials);
+        let dyn_scene = rds::DynamicScene {
+            materials: vec![
+                blend([1, 2]),
+                material(rds::MaterialModel::Disney, [1, 1]),
+                material(rds::MaterialModel::Diffuse, [2, 2]),
+                blend([3, 0]),
+                blend([5, 5]),
+                blend([4, 4]),
+            ],
+            ..rds::DynamicScene::default()
+        };
+
+        // The mask is the probability of the second material.
+        let mut uniform = sampling::UniformSampler::new();
+        let samples = 10_000;
+        let mut picks = [0; 6];
+        for _ in 0..samples {
+            let (material, model) = resolve_blend(&dyn_scene, 0, |_, _| 0.25, &mut uniform);
+            assert_eq!(model, dyn_scene.materials[material as usize].model);
+            picks[material as usize] += 1;
+        }
+        assert_abs_diff_eq!(picks[2] as f32 / samples as f32, 0.25, epsilon = 0.02);
+        assert_eq!(picks[1] + picks[2], samples);
+
+        // Nested blends resolve to a leaf, and a blend that picks itself or
+        // never resolves stays a blend.
+        let (material, _) = resolve_blend(&dyn_scene, 3, |_, _| 1.0, &mut uniform);
+        assert_eq!(material, 2);
+        let (material, model) = resolve_blend(&dyn_scene, 3, |_, _| 0.0, &mut uniform);
+        assert_eq!((material, model), (3, rds::MaterialModel::Blend));
+        let (_, model) = resolve_blend(&dyn_scene, 4, |_, _| 0.5, &mut uniform);
+        assert_eq!(model, rds::MaterialModel::Blend);
+    }
 }
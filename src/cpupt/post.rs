@@ -0,0 +1,231 @@
+use super::*;
+
+// Lens effects on the linear radiance, applied before exposure and
+// tonemapping. In the order light goes through a camera:
+//
+// - Lateral chromatic aberration scales the image radially, red outwards and
+//   blue inwards.
+// - Vignetting follows the cos⁴ law of natural illumination falloff, where
+//   the strength is the squared tangent of the angle to the corners.
+// - Glare spreads a fraction of the energy with a point spread function
+//   made of Gaussians of increasing width, which approximates the heavy tail
+//   of scattering in the lens. Everything is spread, so no threshold is
+//   needed, and only the brightest highlights end up with visible halos.
+//
+// Each Gaussian is three box blurs, which are constant time per pixel.
+//
+
+//
+// Sources:
+//
+// Physically-Based Glare Effects for Digital Images
+// Spencer et al., 1995
+// https://doi.org/10.1145/218380.218466
+//
+// Next Generation Post Processing in Call of Duty: Advanced Warfare
+// Jorge Jimenez, 2014
+// https://www.iryoku.com/next-generation-post-processing-in-call-of-duty-advanced-warfare/
+//
+// Fast Almost-Gaussian Filtering
+// Peter Kovesi, 2010
+// https://www.peterkovesi.com/papers/FastGaussianSmoothing.pdf
+//
+
+// Relative width of each glare Gaussian to the previous one.
+const GLARE_SCALE_STEP: f32 = 4.0;
+
+// Weights of the glare Gaussians, from the narrowest to the widest.
+const GLARE_WEIGHTS: [f32; 3] = [4.0 / 7.0, 2.0 / 7.0, 1.0 / 7.0];
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PostParams {
+    // Fraction of the energy spread by glare, in [0, 1].
+    pub bloom: f32,
+    // Standard deviation of the narrowest glare Gaussian, relative to the
+    // image diagonal.
+    pub bloom_radius: f32,
+    // Squared tangent of the angle between the optical axis and the corners.
+    pub vignette: f32,
+    // Radial offset of red and blue at the corners, relative to the distance
+    // from the center.
+    pub chromatic_aberration: f32,
+}
+
+impl Default for PostParams {
+    fn default() -> Self {
+        Self {
+            bloom: 0.0,
+            bloom_radius: 0.005,
+            vignette: 0.0,
+            chromatic_aberration: 0.0,
+        }
+    }
+}
+
+impl PostParams {
+    fn is_identity(&self) -> bool {
+        self.bloom == 0.0 && self.vignette == 0.0 && self.chromatic_aberration == 0.0
+    }
+}
+
+impl GuiElement for PostParams {
+    fn gui(&mut self, ui: &imgui::Ui) {
+        ui.slider("Bloom", 0.0, 0.5, &mut self.bloom);
+        ui.slider_config("Bloom radius", 0.001, 0.05)
+            .flags(imgui::SliderFlags::LOGARITHMIC)
+            .display_format("%.4f")
+            .build(&mut self.bloom_radius);
+        ui.slider("Vignette", 0.0, 2.0, &mut self.vignette);
+        ui.slider_config("Chromatic aberration", 0.0, 0.02)
+            .display_format("%.4f")
+            .build(&mut self.chromatic_aberration);
+    }
+}
+
+pub fn post_process(
+    params: &PostParams,
+    radiance: &[ColorRgb],
+    (w, h): (u32, u32),
+) -> Vec<ColorRgb> {
+    if params.is_identity() {
+        return radiance.to_vec();
+    }
+    let (w, h) = (w as usize, h as usize);
+    let center = (w as f32 / 2.0, h as f32 / 2.0);
+    let corner_distance_sq = center.0 * center.0 + center.1 * center.1;
+
+    // Chromatic aberration and vignetting.
+    let sample = |x: f32, y: f32| {
+        let x = (x - 0.5).clamp(0.0, (w - 1) as f32);
+        let y = (y - 0.5).clamp(0.0, (h - 1) as f32);
+        let (x0, y0) = (x as usize, y as usize);
+        let (x1, y1) = ((x0 + 1).min(w - 1), (y0 + 1).min(h - 1));
+        let (tx, ty) = (x - x0 as f32, y - y0 as f32);
+        let top = lerp_color(&radiance[x0 + y0 * w], &radiance[x1 + y0 * w], tx);
+        let bottom = lerp_color(&radiance[x0 + y1 * w], &radiance[x1 + y1 * w], tx);
+        lerp_color(&top, &bottom, ty)
+    };
+    let lens = (0..w * h)
+        .into_par_iter()
+        .map(|index| {
+            let (x, y) = ((index % w) as f32 + 0.5, (index / w) as f32 + 0.5);
+            let (dx, dy) = (x - center.0, y - center.1);
+            let color = if params.chromatic_aberration == 0.0 {
+                radiance[index]
+            } else {
+                let channel_at = |scale: f32| sample(center.0 + dx * scale, center.1 + dy * scale);
+                ColorRgb::new(
+                    channel_at(1.0 - params.chromatic_aberration).r(),
+                    radiance[index].g(),
+                    channel_at(1.0 + params.chromatic_aberration).b(),
+                )
+            };
+            let r_sq = (dx * dx + dy * dy) / corner_distance_sq;
+            let cos_sq = 1.0 / (1.0 + params.vignette * r_sq);
+            color * (cos_sq * cos_sq)
+        })
+        .collect::<Vec<_>>();
+    if params.bloom == 0.0 {
+        return lens;
+    }
+
+    // Glare.
+    let diagonal = (w as f32).hypot(h as f32);
+    let bloom = params.bloom.clamp(0.0, 1.0);
+    let mut glare = vec![ColorRgb::BLACK; w * h];
+    let mut sigma = params.bloom_radius * diagonal;
+    for weight in GLARE_WEIGHTS {
+        let blurred = gaussian_blur(&lens, (w, h), sigma);
+        for (glare, blurred) in glare.iter_mut().zip(blurred) {
+            *glare += weight * blurred;
+        }
+        sigma *= GLARE_SCALE_STEP;
+    }
+    lens.into_iter()
+        .zip(glare)
+        .map(|(color, glare)| (1.0 - bloom) * color + bloom * glare)
+        .collect()
+}
+
+fn gaussian_blur(image: &[ColorRgb], (w, h): (usize, usize), sigma: f32) -> Vec<ColorRgb> {
+    // Radius of three box blurs with the same variance as the Gaussian.
+    let radius = ((4.0 * sigma * sigma + 1.0).sqrt() / 2.0 - 0.5)
+        .round()
+        .max(0.0) as usize;
+    let mut image = image.to_vec();
+    for _ in 0..3 {
+        image = box_blur_rows(&image, w, radius);
+    }
+    image = transpose(&image, (w, h));
+    for _ in 0..3 {
+        image = box_blur_rows(&image, h, radius);
+    }
+    transpose(&image, (h, w))
+}
+
+// Averages over the pixels within `radius`, the window is cut at the edges.
+fn box_blur_rows(image: &[ColorRgb], width: usize, radius: usize) -> Vec<ColorRgb> {
+    if radius == 0 {
+        return image.to_vec();
+    }
+    image
+        .par_chunks(width)
+        .flat_map_iter(|row| {
+            let mut prefix = Vec::with_capacity(width + 1);
+            let mut sum = [0.0_f64; 3];
+            prefix.push(sum);
+            for color in row {
+                sum[0] += f64::from(color.r());
+                sum[1] += f64::from(color.g());
+                sum[2] += f64::from(color.b());
+                prefix.push(sum);
+            }
+            (0..width).map(move |x| {
+                let start = x.saturating_sub(radius);
+                let end = (x + radius + 1).min(width);
+                let count = (end - start) as f64;
+                let channel = |c: usize| ((prefix[end][c] - prefix[start][c]) / count) as f32;
+                ColorRgb::new(channel(0), channel(1), channel(2))
+            })
+        })
+        .collect()
+}
+
+fn transpose(image: &[ColorRgb], (w, h): (usize, usize)) -> Vec<ColorRgb> {
+    (0..w * h)
+        .map(|index| image[index / h + (index % h) * w])
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lens_effects() {
+        let size = (64, 48);
+        let mut radiance = vec![ColorRgb::BLACK; 64 * 48];
+        radiance[32 + 24 * 64] = ColorRgb::new(1000.0, 1000.0, 1000.0);
+
+        // Glare spreads the energy of a highlight without changing it.
+        let bloom = PostParams {
+            bloom: 0.2,
+            ..PostParams::default()
+        };
+        let glare = post_process(&bloom, &radiance, size);
+        let total = glare.iter().map(ColorRgb::r).sum::<f32>();
+        assert_abs_diff_eq!(total, 1000.0, epsilon = 1.0);
+        assert!(glare[32 + 24 * 64].r() < 1000.0);
+        assert!(glare[33 + 24 * 64].r() > 0.0);
+
+        // Vignetting leaves the center alone and darkens the corners.
+        let vignette = PostParams {
+            vignette: 1.0,
+            ..PostParams::default()
+        };
+        let falloff = post_process(&vignette, &vec![ColorRgb::WHITE; 64 * 48], size);
+        assert_abs_diff_eq!(falloff[32 + 24 * 64].r(), 1.0, epsilon = 1e-3);
+        assert!(falloff[0].r() < 0.3);
+    }
+}
@@ -206,3 +206,143 @@ fn hemisphere_cosine(s: f32, t: f32) -> Vec3 {
 fn hemisphere_cosine_pdf(cos_theta: f32) -> f32 {
     cos_theta / PI
 }
+
+// Uniformly samples directions within a cone around +Y. The cone is given as
+// `1 - cos(θmax)` to keep precision with very narrow cones.
+pub fn cone_uniform(one_minus_cos_max: f32, s: f32, t: f32) -> Vec3 {
+    let one_minus_cos_theta = s * one_minus_cos_max;
+    let cos_theta = 1.0 - one_minus_cos_theta;
+    let sin_theta = f32::sqrt(f32::max(0.0, one_minus_cos_theta * (1.0 + cos_theta)));
+    let phi = TAU * t;
+    vector![sin_theta * phi.cos(), cos_theta, sin_theta * phi.sin()]
+}
+
+pub fn cone_uniform_pdf(one_minus_cos_max: f32) -> f32 {
+    1.0 / (TAU * one_minus_cos_max)
+}
+
+// Multiple importance sampling weight for a sample drawn from strategy `f`.
+pub fn power_heuristic(f_pdf: f32, g_pdf: f32) -> f32 {
+    let f2 = f_pdf * f_pdf;
+    let g2 = g_pdf * g_pdf;
+    if f2 + g2 > 0.0 {
+        f2 / (f2 + g2)
+    } else {
+        0.0
+    }
+}
+
+// Piecewise constant distribution over [0,1], sampled by inverting its CDF.
+// Based on "Physically Based Rendering", 3rd edition, chapter 13.6.7.
+#[derive(Clone)]
+pub struct Distribution1D {
+    func: Vec<f32>,
+    cdf: Vec<f32>,
+    integral: f32,
+}
+
+impl Distribution1D {
+    pub fn new(func: &[f32]) -> Self {
+        assert!(!func.is_empty(), "Distribution must not be empty");
+        let n = func.len();
+        let mut sum = 0.0_f64;
+        let mut cdf = Vec::with_capacity(n + 1);
+        cdf.push(0.0);
+        for &f in func {
+            sum += f64::from(f.abs()) / n as f64;
+            cdf.push(sum);
+        }
+        // Zero functions are sampled uniformly.
+        let integral = sum as f32;
+        let cdf = if sum > 0.0 {
+            cdf.into_iter().map(|c| (c / sum) as f32).collect()
+        } else {
+            (0..=n).map(|i| i as f32 / n as f32).collect()
+        };
+        Self {
+            func: func.iter().map(|f| f.abs()).collect(),
+            cdf,
+            integral,
+        }
+    }
+
+    pub fn integral(&self) -> f32 {
+        self.integral
+    }
+
+    // Returns the sample in [0,1], its pdf and the index of its segment.
+    pub fn sample(&self, u: f32) -> (f32, f32, usize) {
+        let n = self.func.len();
+        let index = self
+            .cdf
+            .partition_point(|&c| c <= u)
+            .saturating_sub(1)
+            .min(n - 1);
+        let width = self.cdf[index + 1] - self.cdf[index];
+        let du = if width > 0.0 {
+            (u - self.cdf[index]) / width
+        } else {
+            0.0
+        };
+        let x = (index as f32 + du) / n as f32;
+        (x, self.pdf(index), index)
+    }
+
+    pub fn pdf(&self, index: usize) -> f32 {
+        if self.integral > 0.0 {
+            self.func[index] / self.integral
+        } else {
+            1.0
+        }
+    }
+
+    pub fn index(&self, x: f32) -> usize {
+        let n = self.func.len();
+        ((x * n as f32) as usize).min(n - 1)
+    }
+}
+
+// Piecewise constant distribution over [0,1]², given row by row. Rows are
+// chosen by their marginal distribution, then a column within the row.
+#[derive(Clone)]
+pub struct Distribution2D {
+    conditionals: Vec<Distribution1D>,
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    pub fn new(func: &[f32], (w, h): (u32, u32)) -> Self {
+        assert!(
+            func.len() == (w * h) as usize,
+            "Expected {} values, got {} instead",
+            w * h,
+            func.len()
+        );
+        let conditionals = func
+            .chunks_exact(w as usize)
+            .map(Distribution1D::new)
+            .collect::<Vec<_>>();
+        let marginal = Distribution1D::new(
+            &conditionals
+                .iter()
+                .map(Distribution1D::integral)
+                .collect::<Vec<_>>(),
+        );
+        Self {
+            conditionals,
+            marginal,
+        }
+    }
+
+    pub fn sample(&self, (s, t): (f32, f32)) -> ((f32, f32), f32) {
+        let (v, v_pdf, row) = self.marginal.sample(t);
+        let (u, u_pdf, _) = self.conditionals[row].sample(s);
+        ((u, v), u_pdf * v_pdf)
+    }
+
+    pub fn pdf(&self, (u, v): (f32, f32)) -> f32 {
+        let row = self.marginal.index(v);
+        let conditional = &self.conditionals[row];
+        conditional.pdf(conditional.index(u)) * self.marginal.pdf(row)
+    }
+}
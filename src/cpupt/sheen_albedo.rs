@@ -0,0 +1,38 @@
+// Generated by `raydiance debug sheen-albedo`, do not edit.
+
+#[rustfmt::skip]
+#[allow(clippy::approx_constant)]
+pub const SHEEN_ALBEDO: [[f32; 32]; 32] = [
+    [0.00000, 0.00386, 0.02197, 0.04490, 0.05509, 0.05011, 0.03802, 0.02578, 0.01625, 0.00973, 0.00558, 0.00309, 0.00165, 0.00085, 0.00042, 0.00020, 0.00009, 0.00004, 0.00002, 0.00001, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000],
+    [0.00000, 0.00386, 0.02197, 0.04490, 0.05509, 0.05011, 0.03802, 0.02578, 0.01625, 0.00973, 0.00558, 0.00309, 0.00165, 0.00085, 0.00042, 0.00020, 0.00009, 0.00004, 0.00002, 0.00001, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000],
+    [0.00000, 0.00386, 0.02197, 0.04490, 0.05509, 0.05011, 0.03802, 0.02578, 0.01625, 0.00973, 0.00558, 0.00309, 0.00165, 0.00085, 0.00042, 0.00020, 0.00009, 0.00004, 0.00002, 0.00001, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000],
+    [0.00000, 0.00386, 0.02197, 0.04490, 0.05509, 0.05011, 0.03802, 0.02578, 0.01625, 0.00973, 0.00558, 0.00309, 0.00165, 0.00085, 0.00042, 0.00020, 0.00009, 0.00004, 0.00002, 0.00001, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000],
+    [0.00000, 0.00347, 0.02232, 0.05266, 0.07543, 0.08046, 0.07166, 0.05710, 0.04239, 0.03003, 0.02056, 0.01369, 0.00890, 0.00565, 0.00350, 0.00212, 0.00125, 0.00071, 0.00039, 0.00021, 0.00011, 0.00005, 0.00002, 0.00001, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000, 0.00000],
+    [0.00000, 0.00315, 0.02193, 0.05659, 0.08931, 0.10532, 0.10375, 0.09144, 0.07517, 0.05907, 0.04500, 0.03351, 0.02448, 0.01759, 0.01243, 0.00863, 0.00589, 0.00395, 0.00259, 0.00166, 0.00103, 0.00062, 0.00036, 0.00020, 0.00010, 0.00005, 0.00002, 0.00001, 0.00000, 0.00000, 0.00000, 0.00000],
+    [0.00000, 0.00295, 0.02147, 0.05845, 0.09784, 0.12265, 0.12855, 0.12055, 0.10546, 0.08828, 0.07177, 0.05714, 0.04477, 0.03461, 0.02642, 0.01992, 0.01482, 0.01089, 0.00788, 0.00561, 0.00392, 0.00267, 0.00178, 0.00114, 0.00071, 0.00042, 0.00023, 0.00012, 0.00005, 0.00002, 0.00001, 0.00000],
+    [0.00000, 0.00282, 0.02118, 0.05964, 0.10368, 0.13528, 0.14770, 0.14433, 0.13162, 0.11491, 0.09751, 0.08115, 0.06657, 0.05399, 0.04333, 0.03444, 0.02710, 0.02113, 0.01631, 0.01243, 0.00934, 0.00690, 0.00499, 0.00353, 0.00242, 0.00160, 0.00101, 0.00060, 0.00033, 0.00016, 0.00006, 0.00001],
+    [0.00000, 0.00277, 0.02113, 0.06080, 0.10837, 0.14528, 0.16315, 0.16408, 0.15405, 0.13853, 0.12117, 0.10402, 0.08813, 0.07391, 0.06144, 0.05066, 0.04145, 0.03367, 0.02714, 0.02168, 0.01712, 0.01335, 0.01025, 0.00772, 0.00568, 0.00407, 0.00281, 0.00185, 0.00114, 0.00063, 0.00029, 0.00009],
+    [0.00000, 0.00277, 0.02134, 0.06223, 0.11276, 0.15399, 0.17640, 0.18110, 0.17368, 0.15960, 0.14274, 0.12538, 0.10878, 0.09349, 0.07974, 0.06755, 0.05685, 0.04759, 0.03960, 0.03272, 0.02680, 0.02172, 0.01739, 0.01371, 0.01062, 0.00804, 0.00591, 0.00418, 0.00281, 0.00175, 0.00095, 0.00038],
+    [0.00000, 0.00282, 0.02180, 0.06407, 0.11732, 0.16225, 0.18849, 0.19645, 0.19139, 0.17877, 0.16259, 0.14533, 0.12838, 0.11243, 0.09779, 0.08455, 0.07270, 0.06225, 0.05306, 0.04497, 0.03785, 0.03159, 0.02610, 0.02130, 0.01713, 0.01352, 0.01042, 0.00779, 0.00558, 0.00376, 0.00229, 0.00113],
+    [0.00000, 0.00291, 0.02252, 0.06640, 0.12233, 0.17058, 0.20013, 0.21088, 0.20788, 0.19661, 0.18115, 0.16413, 0.14705, 0.13069, 0.11542, 0.10140, 0.08866, 0.07728, 0.06711, 0.05802, 0.04988, 0.04258, 0.03605, 0.03021, 0.02500, 0.02038, 0.01628, 0.01269, 0.00955, 0.00685, 0.00455, 0.00262],
+    [0.00000, 0.00304, 0.02349, 0.06928, 0.12797, 0.17932, 0.21177, 0.22492, 0.22370, 0.21360, 0.19883, 0.18209, 0.16497, 0.14834, 0.13263, 0.11802, 0.10458, 0.09245, 0.08150, 0.07158, 0.06258, 0.05440, 0.04695, 0.04018, 0.03402, 0.02844, 0.02338, 0.01883, 0.01475, 0.01110, 0.00788, 0.00506],
+    [0.00000, 0.00322, 0.02472, 0.07272, 0.13433, 0.18867, 0.22373, 0.23894, 0.23921, 0.23011, 0.21592, 0.19944, 0.18233, 0.16551, 0.14946, 0.13438, 0.12039, 0.10765, 0.09605, 0.08545, 0.07573, 0.06680, 0.05856, 0.05097, 0.04397, 0.03751, 0.03156, 0.02610, 0.02109, 0.01652, 0.01236, 0.00860],
+    [0.00000, 0.00344, 0.02621, 0.07674, 0.14148, 0.19875, 0.23620, 0.25318, 0.25469, 0.24639, 0.23266, 0.21638, 0.19928, 0.18231, 0.16597, 0.15052, 0.13606, 0.12282, 0.11069, 0.09952, 0.08919, 0.07961, 0.07070, 0.06240, 0.05465, 0.04741, 0.04066, 0.03435, 0.02848, 0.02302, 0.01796, 0.01328],
+    [0.00000, 0.00371, 0.02797, 0.08136, 0.14944, 0.20964, 0.24928, 0.26778, 0.27029, 0.26261, 0.24922, 0.23307, 0.21593, 0.19881, 0.18223, 0.16644, 0.15159, 0.13792, 0.12533, 0.11368, 0.10283, 0.09270, 0.08321, 0.07429, 0.06589, 0.05797, 0.05049, 0.04344, 0.03679, 0.03052, 0.02462, 0.01907],
+    [0.00000, 0.00402, 0.02999, 0.08657, 0.15821, 0.22134, 0.26302, 0.28281, 0.28609, 0.27885, 0.26567, 0.24957, 0.23236, 0.21508, 0.19826, 0.18216, 0.16695, 0.15291, 0.13993, 0.12785, 0.11656, 0.10596, 0.09597, 0.08651, 0.07755, 0.06903, 0.06092, 0.05321, 0.04587, 0.03888, 0.03223, 0.02589],
+    [0.00000, 0.00437, 0.03227, 0.09237, 0.16778, 0.23384, 0.27740, 0.29826, 0.30212, 0.29515, 0.28206, 0.26592, 0.24859, 0.23112, 0.21406, 0.19767, 0.18214, 0.16776, 0.15442, 0.14198, 0.13030, 0.11929, 0.10885, 0.09894, 0.08948, 0.08044, 0.07180, 0.06351, 0.05556, 0.04794, 0.04063, 0.03361],
+    [0.00000, 0.00478, 0.03482, 0.09872, 0.17808, 0.24707, 0.29235, 0.31410, 0.31834, 0.31149, 0.29837, 0.28211, 0.26461, 0.24693, 0.22961, 0.21294, 0.19710, 0.18241, 0.16875, 0.15598, 0.14395, 0.13258, 0.12177, 0.11145, 0.10157, 0.09209, 0.08297, 0.07418, 0.06572, 0.05756, 0.04968, 0.04208],
+    [0.00000, 0.00523, 0.03762, 0.10559, 0.18905, 0.26092, 0.30778, 0.33022, 0.33467, 0.32780, 0.31454, 0.29810, 0.28038, 0.26245, 0.24487, 0.22792, 0.21178, 0.19679, 0.18285, 0.16977, 0.15745, 0.14575, 0.13461, 0.12394, 0.11369, 0.10382, 0.09430, 0.08510, 0.07619, 0.06757, 0.05922, 0.05113],
+    [0.00000, 0.00573, 0.04064, 0.11291, 0.20056, 0.27524, 0.32351, 0.34647, 0.35098, 0.34396, 0.33048, 0.31378, 0.29580, 0.27761, 0.25976, 0.24254, 0.22611, 0.21085, 0.19663, 0.18329, 0.17069, 0.15871, 0.14727, 0.13630, 0.12574, 0.11554, 0.10567, 0.09611, 0.08684, 0.07784, 0.06910, 0.06059],
+    [0.00000, 0.00626, 0.04386, 0.12059, 0.21246, 0.28985, 0.33937, 0.36268, 0.36711, 0.35984, 0.34606, 0.32906, 0.31079, 0.29232, 0.27420, 0.25670, 0.24001, 0.22449, 0.21002, 0.19643, 0.18358, 0.17136, 0.15966, 0.14843, 0.13759, 0.12711, 0.11696, 0.10710, 0.09752, 0.08821, 0.07915, 0.07032],
+    [0.00000, 0.00683, 0.04722, 0.12849, 0.22455, 0.30451, 0.35511, 0.37862, 0.38286, 0.37525, 0.36112, 0.34379, 0.32521, 0.30645, 0.28806, 0.27031, 0.25336, 0.23760, 0.22291, 0.20910, 0.19603, 0.18359, 0.17167, 0.16022, 0.14915, 0.13844, 0.12805, 0.11795, 0.10812, 0.09856, 0.08924, 0.08016],
+    [0.00000, 0.00743, 0.05066, 0.13647, 0.23660, 0.31895, 0.37046, 0.39403, 0.39799, 0.39001, 0.37549, 0.35781, 0.33892, 0.31988, 0.30124, 0.28324, 0.26606, 0.25009, 0.23520, 0.22120, 0.20795, 0.19532, 0.18322, 0.17157, 0.16032, 0.14942, 0.13884, 0.12854, 0.11852, 0.10875, 0.09924, 0.08997],
+    [0.00000, 0.00803, 0.05410, 0.14434, 0.24833, 0.33285, 0.38511, 0.40864, 0.41226, 0.40387, 0.38896, 0.37094, 0.35176, 0.33247, 0.31359, 0.29538, 0.27800, 0.26184, 0.24678, 0.23262, 0.21921, 0.20643, 0.19418, 0.18239, 0.17099, 0.15994, 0.14921, 0.13876, 0.12859, 0.11868, 0.10903, 0.09961],
+    [0.00000, 0.00862, 0.05743, 0.15186, 0.25942, 0.34587, 0.39871, 0.42214, 0.42540, 0.41660, 0.40133, 0.38300, 0.36355, 0.34403, 0.32496, 0.30658, 0.28904, 0.27274, 0.25754, 0.24326, 0.22974, 0.21684, 0.20448, 0.19258, 0.18107, 0.16991, 0.15907, 0.14853, 0.13825, 0.12824, 0.11849, 0.10899],
+    [0.00000, 0.00918, 0.06053, 0.15878, 0.26952, 0.35761, 0.41091, 0.43420, 0.43711, 0.42795, 0.41236, 0.39378, 0.37412, 0.35443, 0.33522, 0.31670, 0.29904, 0.28265, 0.26737, 0.25300, 0.23940, 0.22644, 0.21401, 0.20204, 0.19046, 0.17924, 0.16834, 0.15773, 0.14740, 0.13733, 0.12753, 0.11798],
+    [0.00000, 0.00967, 0.06326, 0.16481, 0.27823, 0.36767, 0.42131, 0.44446, 0.44708, 0.43764, 0.42183, 0.40306, 0.38327, 0.36348, 0.34418, 0.32560, 0.30789, 0.29145, 0.27613, 0.26174, 0.24811, 0.23512, 0.22267, 0.21068, 0.19908, 0.18784, 0.17692, 0.16629, 0.15594, 0.14586, 0.13606, 0.12650],
+    [0.00000, 0.01008, 0.06548, 0.16966, 0.28517, 0.37564, 0.42953, 0.45258, 0.45502, 0.44541, 0.42948, 0.41064, 0.39081, 0.37100, 0.35170, 0.33313, 0.31543, 0.29900, 0.28371, 0.26934, 0.25575, 0.24279, 0.23036, 0.21840, 0.20683, 0.19562, 0.18472, 0.17413, 0.16380, 0.15375, 0.14398, 0.13446],
+    [0.00000, 0.01038, 0.06704, 0.17301, 0.28992, 0.38108, 0.43516, 0.45820, 0.46061, 0.45099, 0.43508, 0.41629, 0.39654, 0.37681, 0.35760, 0.33912, 0.32151, 0.30518, 0.28998, 0.27571, 0.26220, 0.24933, 0.23699, 0.22511, 0.21363, 0.20249, 0.19167, 0.18115, 0.17090, 0.16092, 0.15121, 0.14177],
+    [0.00000, 0.01052, 0.06778, 0.17458, 0.29211, 0.38358, 0.43781, 0.46097, 0.46352, 0.45408, 0.43837, 0.41979, 0.40025, 0.38073, 0.36172, 0.34343, 0.32600, 0.30984, 0.29481, 0.28071, 0.26736, 0.25464, 0.24245, 0.23071, 0.21937, 0.20836, 0.19768, 0.18727, 0.17714, 0.16728, 0.15769, 0.14837],
+    [0.00000, 0.01049, 0.06759, 0.17409, 0.29137, 0.38276, 0.43711, 0.46056, 0.46347, 0.45442, 0.43910, 0.42091, 0.40173, 0.38255, 0.36387, 0.34587, 0.32873, 0.31284, 0.29807, 0.28421, 0.27110, 0.25860, 0.24664, 0.23511, 0.22396, 0.21315, 0.20264, 0.19242, 0.18246, 0.17276, 0.16333, 0.15417],
+];
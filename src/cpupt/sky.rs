@@ -2,8 +2,73 @@ use super::*;
 
 use hw_skymodel::rgb as HosekWilkie;
 
+//
+// Sources:
+//
+// A Practical Analytic Model for Daylight
+// A. J. Preetham, Peter Shirley, Brian Smits, 1999
+// https://www2.cs.utah.edu/~shirley/papers/sunsky/sunsky.pdf
+//
+// Revised optical air mass tables and approximation formula
+// Fritz Kasten, Andrew T. Young, 1989
+// https://doi.org/10.1364/AO.28.004735
+//
+// Display of the Earth Taking into Account Atmospheric Scattering
+// Tomoyuki Nishita, Takao Sirai, Katsumi Tadamura, Eihachiro Nakamae, 1993
+// https://doi.org/10.1145/166117.166140
+//
+// A Scalable and Production Ready Sky and Atmosphere Rendering Technique
+// Sébastien Hillaire, 2020
+// https://sebh.github.io/publications/egsr2020.pdf
+//
+
+// Mean angular radius of the solar disc seen from the Earth, 0.2667°.
+const SUN_ANGULAR_RADIUS: f32 = 0.004_654;
+
+// Solar illuminance at the top of the atmosphere. The RGB version of the sky
+// model is in photometric units divided by 683 lm/W, so the sun is too.
+const SUN_ILLUMINANCE: f32 = 128_000.0 / 683.0;
+
+// Linear limb darkening coefficient in the visible range.
+const SUN_LIMB_DARKENING: f32 = 0.6;
+
+// Representative wavelengths of the RGB channels in micrometers.
+const SUN_WAVELENGTHS: [f32; 3] = [0.680, 0.550, 0.440];
+
+// Elevation where civil twilight ends and the sky has faded to black.
+const TWILIGHT_ELEVATION: f32 = -0.104_72;
+
+// Resolution of the integral of the sky irradiance on the ground.
+const GROUND_IRRADIANCE_THETA_STEPS: u32 = 16;
+const GROUND_IRRADIANCE_PHI_STEPS: u32 = 32;
+
+//
+// Params
+//
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum SkyModelKind {
+    #[default]
+    HosekWilkie,
+    Preetham,
+    Nishita,
+}
+
+impl SkyModelKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            SkyModelKind::HosekWilkie => "Hosek-Wilkie",
+            SkyModelKind::Preetham => "Preetham",
+            SkyModelKind::Nishita => "Nishita",
+        }
+    }
+}
+
+// The albedo is the reflectance of the ground. Directions below the horizon
+// see the ground lit by the sun and the sky.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct SkyParams {
+    pub model: SkyModelKind,
     pub elevation: f32,
     pub azimuth: f32,
     pub turbidity: f32,
@@ -13,6 +78,7 @@ pub struct SkyParams {
 impl Default for SkyParams {
     fn default() -> Self {
         Self {
+            model: SkyModelKind::default(),
             elevation: 45.0_f32.to_radians(),
             azimuth: 0.0,
             turbidity: 3.0,
@@ -21,11 +87,405 @@ impl Default for SkyParams {
     }
 }
 
-pub struct SkyState {
+//
+// Models
+//
+
+// Radiance of the clear sky without the solar disc. Only directions above the
+// horizon are queried. Models without a spectral formulation upsample their RGB
+// radiance.
+pub trait SkyModel: Send + Sync {
+    fn radiance(&self, ray_dir: &Normal) -> ColorRgb;
+
+    fn spectral_radiance(
+        &self,
+        ray_dir: &Normal,
+        wavelengths: &spectrum::SampledWavelengths,
+    ) -> ColorRgb {
+        wavelengths.illuminant(self.radiance(ray_dir))
+    }
+}
+
+pub struct HosekWilkieModel {
     state: HosekWilkie::SkyState,
     sun_dir: Normal,
 }
 
+impl HosekWilkieModel {
+    pub fn new(params: &SkyParams, sun_dir: Normal) -> Result<Self> {
+        let state = HosekWilkie::SkyState::new(&HosekWilkie::SkyParams {
+            elevation: params.elevation,
+            turbidity: params.turbidity,
+            albedo: params.albedo.into(),
+        })?;
+        Ok(Self { state, sun_dir })
+    }
+}
+
+impl SkyModel for HosekWilkieModel {
+    fn radiance(&self, ray_dir: &Normal) -> ColorRgb {
+        let theta = ray_dir.y.clamp(0.0, 1.0).acos();
+        let cos_gamma = ray_dir.dot(&self.sun_dir).clamp(-1.0, 1.0);
+        let gamma = cos_gamma.acos();
+        ColorRgb::new(
+            self.state.radiance(theta, gamma, HosekWilkie::Channel::R),
+            self.state.radiance(theta, gamma, HosekWilkie::Channel::G),
+            self.state.radiance(theta, gamma, HosekWilkie::Channel::B),
+        )
+    }
+}
+
+// Perez functions for the luminance and the chromaticity, fitted to turbidity
+// and the solar zenith angle.
+pub struct PreethamModel {
+    sun_dir: Normal,
+    // Luminance and chromaticity coordinates, in that order.
+    zenith: [f32; 3],
+    perez: [[f32; 5]; 3],
+}
+
+impl PreethamModel {
+    pub fn new(params: &SkyParams, sun_dir: Normal) -> Self {
+        let t = params.turbidity;
+        let theta_s = 0.5 * PI - params.elevation;
+        let theta_s2 = theta_s * theta_s;
+        let theta_s3 = theta_s2 * theta_s;
+
+        // Zenith luminance in kcd/m², converted to the units of the sun.
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta_s);
+        let zenith_y = (4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192;
+        let zenith_y = zenith_y * 1000.0 / 683.0;
+        let chromaticity = |c: [[f32; 4]; 3]| {
+            let [c2, c1, c0] = c.map(|c| c[0] * theta_s3 + c[1] * theta_s2 + c[2] * theta_s + c[3]);
+            c2 * t * t + c1 * t + c0
+        };
+        let zenith_x = chromaticity([
+            [0.00166, -0.00375, 0.00209, 0.0],
+            [-0.02903, 0.06377, -0.03202, 0.00394],
+            [0.11693, -0.21196, 0.06052, 0.25886],
+        ]);
+        let zenith_y_chroma = chromaticity([
+            [0.00275, -0.00610, 0.00317, 0.0],
+            [-0.04214, 0.08970, -0.04153, 0.00516],
+            [0.15346, -0.26756, 0.06670, 0.26688],
+        ]);
+
+        let perez = [
+            [
+                0.1787 * t - 1.4630,
+                -0.3554 * t + 0.4275,
+                -0.0227 * t + 5.3251,
+                0.1206 * t - 2.5771,
+                -0.0670 * t + 0.3703,
+            ],
+            [
+                -0.0193 * t - 0.2592,
+                -0.0665 * t + 0.0008,
+                -0.0004 * t + 0.2125,
+                -0.0641 * t - 0.8989,
+                -0.0033 * t + 0.0452,
+            ],
+            [
+                -0.0167 * t - 0.2608,
+                -0.0950 * t + 0.0092,
+                -0.0079 * t + 0.2102,
+                -0.0441 * t - 1.6537,
+                -0.0109 * t + 0.0529,
+            ],
+        ];
+
+        // Normalize the distributions to their zenith values.
+        let zenith = [zenith_y, zenith_x, zenith_y_chroma];
+        let zenith = [0, 1, 2].map(|i| zenith[i] / perez_function(&perez[i], 1.0, theta_s));
+        Self {
+            sun_dir,
+            zenith,
+            perez,
+        }
+    }
+}
+
+impl SkyModel for PreethamModel {
+    fn radiance(&self, ray_dir: &Normal) -> ColorRgb {
+        let cos_theta = ray_dir.y.max(1e-3);
+        let gamma = ray_dir.dot(&self.sun_dir).clamp(-1.0, 1.0).acos();
+        let [luminance, x, y] =
+            [0, 1, 2].map(|i| self.zenith[i] * perez_function(&self.perez[i], cos_theta, gamma));
+        color_from_xyy(x, y, luminance)
+    }
+}
+
+fn perez_function([a, b, c, d, e]: &[f32; 5], cos_theta: f32, gamma: f32) -> f32 {
+    (1.0 + a * (b / cos_theta).exp()) * (1.0 + c * (d * gamma).exp() + e * gamma.cos().powi(2))
+}
+
+fn color_from_xyy(x: f32, y: f32, luminance: f32) -> ColorRgb {
+    if y <= 0.0 {
+        return ColorRgb::BLACK;
+    }
+    let rgb = spectrum::linear_srgb_from_xyz([
+        x / y * luminance,
+        luminance,
+        (1.0 - x - y) / y * luminance,
+    ]);
+    ColorRgb::new(rgb.r().max(0.0), rgb.g().max(0.0), rgb.b().max(0.0))
+}
+
+// Single scattering in a spherical atmosphere with exponentially decreasing
+// Rayleigh and Mie densities. The integral is too slow to evaluate per ray, so
+// it is tabulated over the elevation of the view and its azimuth relative to
+// the sun. Elevations are packed towards the horizon like in Hillaire 2020.
+// The spectral table has bins across the visible range, which are
+// interpolated between their centers.
+pub struct NishitaModel {
+    sun_dir: Normal,
+    table: Vec<ColorRgb>,
+    spectral_table: Vec<[f32; NISHITA_SPECTRAL_BINS]>,
+}
+
+const NISHITA_EARTH_RADIUS: f32 = 6_360e3;
+const NISHITA_ATMOSPHERE_RADIUS: f32 = 6_420e3;
+const NISHITA_VIEWER_HEIGHT: f32 = 1.0;
+const NISHITA_RAYLEIGH_SCATTERING: [f32; 3] = [5.802e-6, 13.558e-6, 33.1e-6];
+const NISHITA_RAYLEIGH_REFERENCE: (f32, f32) = (550.0, 13.558e-6);
+const NISHITA_RAYLEIGH_SCALE_HEIGHT: f32 = 8_000.0;
+const NISHITA_MIE_SCATTERING: f32 = 21e-6;
+const NISHITA_MIE_EXTINCTION: f32 = 1.1 * NISHITA_MIE_SCATTERING;
+const NISHITA_MIE_SCALE_HEIGHT: f32 = 1_200.0;
+const NISHITA_MIE_ASYMMETRY: f32 = 0.76;
+const NISHITA_VIEW_STEPS: u32 = 32;
+const NISHITA_LIGHT_STEPS: u32 = 8;
+const NISHITA_TABLE_ELEVATIONS: u32 = 64;
+const NISHITA_TABLE_AZIMUTHS: u32 = 64;
+const NISHITA_SPECTRAL_BINS: usize = 16;
+
+impl NishitaModel {
+    pub fn new(params: &SkyParams, sun_dir: Normal) -> Self {
+        // Turbidity scales the aerosols, with the reference density at 3.
+        let mie_scale = aerosol_beta(params.turbidity) / aerosol_beta(3.0);
+
+        // Rayleigh scattering falls off with the fourth power of the
+        // wavelength. The sun has the spectrum of D65 in the spectral table.
+        let bin_width =
+            (spectrum::LAMBDA_MAX - spectrum::LAMBDA_MIN) / NISHITA_SPECTRAL_BINS as f32;
+        let bin_lambdas: [f32; NISHITA_SPECTRAL_BINS] =
+            std::array::from_fn(|bin| spectrum::LAMBDA_MIN + (bin as f32 + 0.5) * bin_width);
+        let (reference_lambda, reference_beta) = NISHITA_RAYLEIGH_REFERENCE;
+        let bin_rayleigh =
+            bin_lambdas.map(|lambda| reference_beta * (reference_lambda / lambda).powi(4));
+        let d65_luminance = spectrum::d65_luminance();
+        let bin_sun = bin_lambdas.map(|lambda| spectrum::d65(lambda) / d65_luminance);
+
+        // The sun lies in the XY-plane while tabulating.
+        let (sin_elevation, cos_elevation) = params.elevation.sin_cos();
+        let table_sun_dir = vector![cos_elevation, sin_elevation, 0.0];
+        let table_len = (NISHITA_TABLE_ELEVATIONS * NISHITA_TABLE_AZIMUTHS) as usize;
+        let mut table = Vec::with_capacity(table_len);
+        let mut spectral_table = Vec::with_capacity(table_len);
+        for elevation_index in 0..NISHITA_TABLE_ELEVATIONS {
+            let v = elevation_index as f32 / (NISHITA_TABLE_ELEVATIONS - 1) as f32;
+            let elevation = 0.5 * PI * v * v;
+            for azimuth_index in 0..NISHITA_TABLE_AZIMUTHS {
+                let azimuth = PI * azimuth_index as f32 / (NISHITA_TABLE_AZIMUTHS - 1) as f32;
+                let view_dir = vector![
+                    elevation.cos() * azimuth.cos(),
+                    elevation.sin(),
+                    elevation.cos() * azimuth.sin()
+                ];
+                let [r, g, b] = nishita_single_scattering(
+                    &view_dir,
+                    &table_sun_dir,
+                    mie_scale,
+                    NISHITA_RAYLEIGH_SCATTERING,
+                );
+                table.push(ColorRgb::new(r, g, b));
+                let mut bins =
+                    nishita_single_scattering(&view_dir, &table_sun_dir, mie_scale, bin_rayleigh);
+                for (value, sun) in bins.iter_mut().zip(bin_sun) {
+                    *value *= sun;
+                }
+                spectral_table.push(bins);
+            }
+        }
+
+        Self {
+            sun_dir,
+            table,
+            spectral_table,
+        }
+    }
+
+    // Bilinear interpolation weights of the table texels around a direction.
+    fn texel_weights(&self, ray_dir: &Normal) -> [(usize, f32); 4] {
+        // Table coordinates.
+        let elevation = ray_dir.y.clamp(0.0, 1.0).asin();
+        let v = (elevation / (0.5 * PI)).sqrt();
+        let dot = ray_dir.x * self.sun_dir.x + ray_dir.z * self.sun_dir.z;
+        let cross = ray_dir.x * self.sun_dir.z - ray_dir.z * self.sun_dir.x;
+        let azimuth = cross.atan2(dot).abs();
+        let u = azimuth / PI;
+
+        let x = u * (NISHITA_TABLE_AZIMUTHS - 1) as f32;
+        let y = v * (NISHITA_TABLE_ELEVATIONS - 1) as f32;
+        let x0 = (x as u32).min(NISHITA_TABLE_AZIMUTHS - 2);
+        let y0 = (y as u32).min(NISHITA_TABLE_ELEVATIONS - 2);
+        let fx = x - x0 as f32;
+        let fy = y - y0 as f32;
+        let index = |x, y| (x + y * NISHITA_TABLE_AZIMUTHS) as usize;
+        [
+            (index(x0, y0), (1.0 - fx) * (1.0 - fy)),
+            (index(x0 + 1, y0), fx * (1.0 - fy)),
+            (index(x0, y0 + 1), (1.0 - fx) * fy),
+            (index(x0 + 1, y0 + 1), fx * fy),
+        ]
+    }
+}
+
+impl SkyModel for NishitaModel {
+    fn radiance(&self, ray_dir: &Normal) -> ColorRgb {
+        self.texel_weights(ray_dir)
+            .into_iter()
+            .fold(ColorRgb::BLACK, |sum, (index, weight)| {
+                sum + weight * self.table[index]
+            })
+    }
+
+    fn spectral_radiance(
+        &self,
+        ray_dir: &Normal,
+        wavelengths: &spectrum::SampledWavelengths,
+    ) -> ColorRgb {
+        let texel_weights = self.texel_weights(ray_dir);
+        let bin_width =
+            (spectrum::LAMBDA_MAX - spectrum::LAMBDA_MIN) / NISHITA_SPECTRAL_BINS as f32;
+        let [r, g, b] = wavelengths.lambdas().map(|lambda| {
+            let x = ((lambda - spectrum::LAMBDA_MIN) / bin_width - 0.5)
+                .clamp(0.0, (NISHITA_SPECTRAL_BINS - 1) as f32);
+            let bin = (x as usize).min(NISHITA_SPECTRAL_BINS - 2);
+            let f = x - bin as f32;
+            texel_weights.iter().fold(0.0, |sum, &(index, weight)| {
+                let bins = &self.spectral_table[index];
+                sum + weight * ((1.0 - f) * bins[bin] + f * bins[bin + 1])
+            })
+        });
+        ColorRgb::new(r, g, b)
+    }
+}
+
+// Radiance for each of the given Rayleigh scattering coefficients. Aerosols
+// scatter all wavelengths equally.
+fn nishita_single_scattering<const N: usize>(
+    view_dir: &Vec3,
+    sun_dir: &Vec3,
+    mie_scale: f32,
+    rayleigh: [f32; N],
+) -> [f32; N] {
+    let mie_scattering = mie_scale * NISHITA_MIE_SCATTERING;
+    let mie_extinction = mie_scale * NISHITA_MIE_EXTINCTION;
+
+    // Phase functions, Mie with the Cornette-Shanks approximation.
+    let mu = view_dir.dot(sun_dir);
+    let rayleigh_phase = 3.0 / (16.0 * PI) * (1.0 + mu * mu);
+    let g = NISHITA_MIE_ASYMMETRY;
+    let mie_phase = 3.0 / (8.0 * PI) * ((1.0 - g * g) * (1.0 + mu * mu))
+        / ((2.0 + g * g) * (1.0 + g * g - 2.0 * g * mu).powf(1.5));
+
+    // March from the viewer to the top of the atmosphere. The steps grow
+    // quadratically, because the density and the transmittance fall off fast
+    // along rays near the horizon.
+    let origin = vector![0.0, NISHITA_EARTH_RADIUS + NISHITA_VIEWER_HEIGHT, 0.0];
+    let Some(view_length) = sphere_exit(&origin, view_dir, NISHITA_ATMOSPHERE_RADIUS) else {
+        return [0.0; N];
+    };
+    let view_steps = NISHITA_VIEW_STEPS as f32;
+    let mut view_rayleigh_depth = 0.0;
+    let mut view_mie_depth = 0.0;
+    let mut rayleigh_sum = [0.0; N];
+    let mut mie_sum = [0.0; N];
+    for view_index in 0..NISHITA_VIEW_STEPS {
+        let i = view_index as f32;
+        let view_step = view_length * (2.0 * i + 1.0) / (view_steps * view_steps);
+        let t = view_length * ((i + 0.5) / view_steps).powi(2);
+        let point = origin + t * view_dir;
+        let height = point.norm() - NISHITA_EARTH_RADIUS;
+        let rayleigh_density = (-height / NISHITA_RAYLEIGH_SCALE_HEIGHT).exp() * view_step;
+        let mie_density = (-height / NISHITA_MIE_SCALE_HEIGHT).exp() * view_step;
+        let point_rayleigh_depth = view_rayleigh_depth + 0.5 * rayleigh_density;
+        let point_mie_depth = view_mie_depth + 0.5 * mie_density;
+        view_rayleigh_depth += rayleigh_density;
+        view_mie_depth += mie_density;
+
+        // Points in the shadow of the Earth receive no sunlight.
+        if sphere_entry(&point, sun_dir, NISHITA_EARTH_RADIUS).is_some() {
+            continue;
+        }
+        let Some(light_length) = sphere_exit(&point, sun_dir, NISHITA_ATMOSPHERE_RADIUS) else {
+            continue;
+        };
+        let light_step = light_length / NISHITA_LIGHT_STEPS as f32;
+        let mut light_rayleigh_depth = 0.0;
+        let mut light_mie_depth = 0.0;
+        for light_index in 0..NISHITA_LIGHT_STEPS {
+            let light_point = point + (light_index as f32 + 0.5) * light_step * sun_dir;
+            let light_height = light_point.norm() - NISHITA_EARTH_RADIUS;
+            light_rayleigh_depth +=
+                (-light_height / NISHITA_RAYLEIGH_SCALE_HEIGHT).exp() * light_step;
+            light_mie_depth += (-light_height / NISHITA_MIE_SCALE_HEIGHT).exp() * light_step;
+        }
+
+        let rayleigh_depth = point_rayleigh_depth + light_rayleigh_depth;
+        let mie_depth = point_mie_depth + light_mie_depth;
+        for i in 0..N {
+            let attenuation = (-(rayleigh[i] * rayleigh_depth + mie_extinction * mie_depth)).exp();
+            rayleigh_sum[i] += attenuation * rayleigh_density;
+            mie_sum[i] += attenuation * mie_density;
+        }
+    }
+
+    std::array::from_fn(|i| {
+        SUN_ILLUMINANCE
+            * (rayleigh_sum[i] * rayleigh[i] * rayleigh_phase
+                + mie_sum[i] * mie_scattering * mie_phase)
+    })
+}
+
+// Distance to where the ray leaves a sphere centered at the origin.
+fn sphere_exit(origin: &Vec3, dir: &Vec3, radius: f32) -> Option<f32> {
+    let b = origin.dot(dir);
+    let c = origin.norm_squared() - radius * radius;
+    let discriminant = b * b - c;
+    (discriminant >= 0.0).then(|| -b + discriminant.sqrt())
+}
+
+// Distance to where the ray enters a sphere centered at the origin, if it
+// starts outside of it.
+fn sphere_entry(origin: &Vec3, dir: &Vec3, radius: f32) -> Option<f32> {
+    let b = origin.dot(dir);
+    let c = origin.norm_squared() - radius * radius;
+    let discriminant = b * b - c;
+    if c <= 0.0 || b >= 0.0 || discriminant < 0.0 {
+        return None;
+    }
+    Some(-b - discriminant.sqrt())
+}
+
+//
+// State
+//
+
+pub struct SkyState {
+    model: Box<dyn SkyModel>,
+    ground_radiance: ColorRgb,
+    sun_dir: Normal,
+    sun_onb: sampling::OrthonormalBasis,
+    sun_radiance: ColorRgb,
+    sun_one_minus_cos_max: f32,
+    sun_elevation: f32,
+    sun_visibility: f32,
+    model_scale: f32,
+    turbidity: f32,
+}
+
 impl SkyState {
     pub fn new(params: &SkyParams) -> Result<Self> {
         // Validate.
@@ -35,13 +495,6 @@ impl SkyState {
             params.azimuth
         );
 
-        // Init state.
-        let state = HosekWilkie::SkyState::new(&HosekWilkie::SkyParams {
-            elevation: params.elevation,
-            turbidity: params.turbidity,
-            albedo: params.albedo.into(),
-        })?;
-
         // Pre-compute sun direction.
         let sun_angle_xz = params.azimuth;
         let sun_angle_y = 0.5 * PI - params.elevation;
@@ -51,29 +504,267 @@ impl SkyState {
             sun_angle_y.sin() * sun_angle_xz.sin()
         ];
 
-        Ok(Self { state, sun_dir })
+        // Solar disc. The radiance is the average over the disc, limb darkening
+        // redistributes it towards the center. The disc sets behind the
+        // horizon, where the transmittance stays at its value at the horizon.
+        let sun_one_minus_cos_max = 2.0 * (0.5 * SUN_ANGULAR_RADIUS).sin().powi(2);
+        let sun_solid_angle = TAU * sun_one_minus_cos_max;
+        let sun_elevation = params.elevation.max(0.0);
+        let sun_visibility =
+            ((params.elevation + SUN_ANGULAR_RADIUS) / (2.0 * SUN_ANGULAR_RADIUS)).clamp(0.0, 1.0);
+        let sun_irradiance =
+            sun_transmittance(sun_elevation, params.turbidity) * (sun_visibility * SUN_ILLUMINANCE);
+        let sun_radiance = sun_irradiance * (1.0 / sun_solid_angle);
+
+        // Init model. Hosek-Wilkie and Preetham are fitted to the sun above
+        // the horizon, so they are evaluated at sunrise and fade out over
+        // civil twilight. Nishita has the shadow of the Earth built in.
+        let model_params = SkyParams {
+            elevation: sun_elevation,
+            ..*params
+        };
+        let twilight = (1.0 - params.elevation / TWILIGHT_ELEVATION).clamp(0.0, 1.0);
+        let (model, model_scale): (Box<dyn SkyModel>, f32) = match params.model {
+            SkyModelKind::HosekWilkie => (
+                Box::new(HosekWilkieModel::new(&model_params, sun_dir)?),
+                twilight,
+            ),
+            SkyModelKind::Preetham => (
+                Box::new(PreethamModel::new(&model_params, sun_dir)),
+                twilight,
+            ),
+            SkyModelKind::Nishita => (Box::new(NishitaModel::new(params, sun_dir)), 1.0),
+        };
+
+        // The ground is Lambertian, lit by the sun and the sky above it.
+        let mut ground_irradiance = sun_irradiance * params.elevation.sin().max(0.0);
+        let d_theta = 0.5 * PI / GROUND_IRRADIANCE_THETA_STEPS as f32;
+        let d_phi = TAU / GROUND_IRRADIANCE_PHI_STEPS as f32;
+        for theta_index in 0..GROUND_IRRADIANCE_THETA_STEPS {
+            let theta = (theta_index as f32 + 0.5) * d_theta;
+            let (sin_theta, cos_theta) = theta.sin_cos();
+            for phi_index in 0..GROUND_IRRADIANCE_PHI_STEPS {
+                let phi = (phi_index as f32 + 0.5) * d_phi;
+                let dir = normal![sin_theta * phi.cos(), cos_theta, sin_theta * phi.sin()];
+                ground_irradiance +=
+                    model.radiance(&dir) * (model_scale * cos_theta * sin_theta * d_theta * d_phi);
+            }
+        }
+        let ground_radiance = params.albedo * ground_irradiance * INV_PI;
+
+        Ok(Self {
+            model,
+            ground_radiance,
+            sun_dir,
+            sun_onb: sampling::OrthonormalBasis::new(&sun_dir),
+            sun_radiance,
+            sun_one_minus_cos_max,
+            sun_elevation,
+            sun_visibility,
+            model_scale,
+            turbidity: params.turbidity,
+        })
     }
 
     pub fn sun_dir(&self) -> Normal {
         self.sun_dir
     }
 
+    pub fn sun_angular_radius() -> f32 {
+        SUN_ANGULAR_RADIUS
+    }
+
+    pub fn sun_radiance(&self, ray_dir: &Normal) -> ColorRgb {
+        self.sun_limb_darkening(ray_dir)
+            .map_or(ColorRgb::BLACK, |limb| limb * self.sun_radiance)
+    }
+
+    // The sun is white above the atmosphere, with the spectrum of D65.
+    pub fn spectral_sun_radiance(
+        &self,
+        ray_dir: &Normal,
+        wavelengths: &spectrum::SampledWavelengths,
+    ) -> ColorRgb {
+        let Some(limb) = self.sun_limb_darkening(ray_dir) else {
+            return ColorRgb::BLACK;
+        };
+        let sun_solid_angle = TAU * self.sun_one_minus_cos_max;
+        let [r, g, b] = wavelengths.lambdas().map(|lambda| {
+            sun_spectral_transmittance(self.sun_elevation, self.turbidity, 1e-3 * lambda)
+        });
+        ColorRgb::new(r, g, b)
+            * wavelengths.d65()
+            * (self.sun_visibility * limb * SUN_ILLUMINANCE / sun_solid_angle)
+    }
+
+    // Ratio of the radiance to the average over the disc, or none outside of
+    // the disc.
+    fn sun_limb_darkening(&self, ray_dir: &Normal) -> Option<f32> {
+        // Use the chord length instead of the dot product, because the disc is
+        // too small for the precision of cos(γ) near one.
+        let one_minus_cos_gamma =
+            0.5 * (ray_dir.into_inner() - self.sun_dir.into_inner()).norm_squared();
+        if one_minus_cos_gamma > self.sun_one_minus_cos_max {
+            return None;
+        }
+        let sin2_gamma = one_minus_cos_gamma * (2.0 - one_minus_cos_gamma);
+        let sin2_max = self.sun_one_minus_cos_max * (2.0 - self.sun_one_minus_cos_max);
+        let mu = (1.0 - sin2_gamma / sin2_max).max(0.0).sqrt();
+        let u = SUN_LIMB_DARKENING;
+        Some((1.0 - u * (1.0 - mu)) / (1.0 - u / 3.0))
+    }
+
+    // None once the sun has set.
+    pub fn sample_sun(&self, u: (f32, f32)) -> Option<(Normal, f32)> {
+        if self.sun_visibility == 0.0 {
+            return None;
+        }
+        let local = sampling::cone_uniform(self.sun_one_minus_cos_max, u.0, u.1);
+        let pdf = sampling::cone_uniform_pdf(self.sun_one_minus_cos_max);
+        Some((normal!(self.sun_onb.world_from_local() * local), pdf))
+    }
+
+    pub fn sun_pdf(&self, ray_dir: &Normal) -> f32 {
+        let one_minus_cos_gamma =
+            0.5 * (ray_dir.into_inner() - self.sun_dir.into_inner()).norm_squared();
+        if self.sun_visibility == 0.0 || one_minus_cos_gamma > self.sun_one_minus_cos_max {
+            return 0.0;
+        }
+        sampling::cone_uniform_pdf(self.sun_one_minus_cos_max)
+    }
+
     pub fn radiance(&self, ray_dir: &Normal) -> ColorRgb {
-        let theta = ray_dir.y.acos();
-        let cos_gamma = ray_dir.dot(&self.sun_dir).clamp(-1.0, 1.0);
-        let gamma = cos_gamma.acos();
-        ColorRgb::new(
-            self.state.radiance(theta, gamma, HosekWilkie::Channel::R),
-            self.state.radiance(theta, gamma, HosekWilkie::Channel::G),
-            self.state.radiance(theta, gamma, HosekWilkie::Channel::B),
-        )
+        if ray_dir.y < 0.0 {
+            return self.ground_radiance;
+        }
+        self.model_scale * self.model.radiance(ray_dir)
+    }
+
+    pub fn spectral_radiance(
+        &self,
+        ray_dir: &Normal,
+        wavelengths: &spectrum::SampledWavelengths,
+    ) -> ColorRgb {
+        if ray_dir.y < 0.0 {
+            return wavelengths.illuminant(self.ground_radiance);
+        }
+        self.model_scale * self.model.spectral_radiance(ray_dir, wavelengths)
+    }
+}
+
+// The sky is either the analytic daylight model or a captured environment.
+#[derive(Clone, PartialEq)]
+pub enum SkySource {
+    Analytic(SkyParams),
+    Environment(EnvironmentParams),
+}
+
+impl Default for SkySource {
+    fn default() -> Self {
+        Self::Analytic(SkyParams::default())
+    }
+}
+
+// Radiance from the sky is split in two parts. The light is importance
+// sampled and weighted with MIS, the rest is only found by the paths. With
+// sampled wavelengths the radiance is returned per wavelength instead of RGB.
+pub enum Sky {
+    Analytic(Box<SkyState>),
+    Environment(EnvironmentState),
+}
+
+impl Sky {
+    pub fn new(source: &SkySource) -> Result<Self> {
+        Ok(match source {
+            SkySource::Analytic(params) => Self::Analytic(Box::new(SkyState::new(params)?)),
+            SkySource::Environment(params) => Self::Environment(EnvironmentState::new(params)),
+        })
+    }
+
+    pub fn radiance(
+        &self,
+        ray_dir: &Normal,
+        wavelengths: Option<&spectrum::SampledWavelengths>,
+    ) -> ColorRgb {
+        match (self, wavelengths) {
+            (Self::Analytic(sky), None) => sky.radiance(ray_dir),
+            (Self::Analytic(sky), Some(wavelengths)) => sky.spectral_radiance(ray_dir, wavelengths),
+            (Self::Environment(_), _) => ColorRgb::BLACK,
+        }
+    }
+
+    pub fn light_radiance(
+        &self,
+        ray_dir: &Normal,
+        wavelengths: Option<&spectrum::SampledWavelengths>,
+    ) -> ColorRgb {
+        match (self, wavelengths) {
+            (Self::Analytic(sky), None) => sky.sun_radiance(ray_dir),
+            (Self::Analytic(sky), Some(wavelengths)) => {
+                sky.spectral_sun_radiance(ray_dir, wavelengths)
+            }
+            (Self::Environment(environment), None) => environment.radiance(ray_dir),
+            (Self::Environment(environment), Some(wavelengths)) => {
+                wavelengths.illuminant(environment.radiance(ray_dir))
+            }
+        }
+    }
+
+    pub fn sample_light(&self, u: (f32, f32)) -> Option<(Normal, f32)> {
+        match self {
+            Self::Analytic(sky) => sky.sample_sun(u),
+            Self::Environment(environment) => environment.sample(u),
+        }
+    }
+
+    pub fn light_pdf(&self, ray_dir: &Normal) -> f32 {
+        match self {
+            Self::Analytic(sky) => sky.sun_pdf(ray_dir),
+            Self::Environment(environment) => environment.pdf(ray_dir),
+        }
     }
 }
 
+// Direct sunlight is attenuated by Rayleigh scattering and by aerosols, whose
+// amount is given by the turbidity. Ozone and water vapor are ignored.
+fn sun_transmittance(elevation: f32, turbidity: f32) -> ColorRgb {
+    let [r, g, b] =
+        SUN_WAVELENGTHS.map(|lambda| sun_spectral_transmittance(elevation, turbidity, lambda));
+    ColorRgb::new(r, g, b)
+}
+
+// Transmittance at a wavelength in micrometers.
+fn sun_spectral_transmittance(elevation: f32, turbidity: f32, lambda: f32) -> f32 {
+    let zenith = 0.5 * PI - elevation;
+    let air_mass = 1.0 / (zenith.cos() + 0.50572 * (96.07995 - zenith.to_degrees()).powf(-1.6364));
+    let beta = aerosol_beta(turbidity);
+    let alpha = 1.3;
+    let rayleigh = (-0.008_735 * lambda.powf(-4.08) * air_mass).exp();
+    let aerosol = (-beta * lambda.powf(-alpha) * air_mass).exp();
+    rayleigh * aerosol
+}
+
+// Ångström turbidity coefficient.
+fn aerosol_beta(turbidity: f32) -> f32 {
+    0.04608 * turbidity - 0.04586
+}
+
 impl GuiElement for SkyParams {
     fn gui(&mut self, ui: &imgui::Ui) {
+        ui.text("Sky model");
+        if let Some(_token) = ui.begin_combo("##sky_model", self.model.name()) {
+            for model in [
+                SkyModelKind::HosekWilkie,
+                SkyModelKind::Preetham,
+                SkyModelKind::Nishita,
+            ] {
+                if ui.selectable(model.name()) {
+                    self.model = model;
+                }
+            }
+        }
         imgui::AngleSlider::new("Elevation")
-            .min_degrees(0.0)
+            .min_degrees(-10.0)
             .max_degrees(90.0)
             .build(ui, &mut self.elevation);
         imgui::AngleSlider::new("Azimuth")
@@ -84,3 +775,65 @@ impl GuiElement for SkyParams {
         ui.color_edit3("Albedo", self.albedo.as_mut());
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sky_models() {
+        // The models disagree in the details, but they must describe the same
+        // sky. Compare the brightness of the zenith, low in the sky opposite
+        // the sun and the ground against Hosek-Wilkie.
+        let zenith = normal![0.0, 1.0, 0.0];
+        let (sin_horizon, cos_horizon) = 10.0_f32.to_radians().sin_cos();
+        let horizon = normal![-cos_horizon, sin_horizon, 0.0];
+        let ground = normal![0.0, -1.0, 0.0];
+        let radiances = [
+            SkyModelKind::HosekWilkie,
+            SkyModelKind::Preetham,
+            SkyModelKind::Nishita,
+        ]
+        .map(|model| {
+            let sky = SkyState::new(&SkyParams {
+                model,
+                ..SkyParams::default()
+            })
+            .unwrap();
+            [&zenith, &horizon, &ground].map(|dir| sky.radiance(dir).luminance())
+        });
+        let [reference, ..] = radiances;
+        for radiance in radiances {
+            for value in radiance {
+                assert!(value.is_finite() && value > 0.0, "value={value}");
+            }
+            // Single scattering alone gives a darker sky.
+            assert!((0.2..5.0).contains(&(radiance[0] / reference[0])));
+            assert!((0.2..5.0).contains(&(radiance[1] / reference[1])));
+            assert!((0.5..2.0).contains(&(radiance[2] / reference[2])));
+        }
+    }
+
+    #[test]
+    fn sun_irradiance() {
+        // Limb darkening must not change the total irradiance from the disc.
+        let params = SkyParams::default();
+        let sky = SkyState::new(&params).unwrap();
+        let sun_dir = sky.sun_dir();
+        let expected = sun_transmittance(params.elevation, params.turbidity) * SUN_ILLUMINANCE;
+
+        let samples = 100_000;
+        let mut uniform = UniformSampler::new();
+        let mut irradiance = ColorRgb::BLACK;
+        for _ in 0..samples {
+            let (wi, pdf) = sky
+                .sample_sun((uniform.sample(), uniform.sample()))
+                .unwrap();
+            irradiance += sky.sun_radiance(&wi) * (wi.dot(&sun_dir) / pdf);
+        }
+        let irradiance = irradiance * (1.0 / samples as f32);
+        assert_abs_diff_eq!(irradiance.r(), expected.r(), epsilon = 0.01 * expected.r());
+        assert_abs_diff_eq!(irradiance.g(), expected.g(), epsilon = 0.01 * expected.g());
+        assert_abs_diff_eq!(irradiance.b(), expected.b(), epsilon = 0.01 * expected.b());
+    }
+}
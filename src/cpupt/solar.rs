@@ -0,0 +1,188 @@
+use super::*;
+
+//
+// Sources:
+//
+// Computing the Solar Vector
+// Manuel Blanco-Muriel, Diego C. Alarcón-Padilla, Teodoro López-Moratalla,
+// Martín Coira-Lissén, 2001
+// https://doi.org/10.1016/S0038-092X(00)00156-0
+//
+
+const EARTH_MEAN_RADIUS_KM: f64 = 6371.01;
+const ASTRONOMICAL_UNIT_KM: f64 = 149_597_890.0;
+
+// Angles are in radians like in `SkyParams`. Latitude is positive to the north
+// and longitude is positive to the east. `north_offset` is the scene azimuth
+// which points to geographic north.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SolarPosition {
+    pub latitude: f32,
+    pub longitude: f32,
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+    pub local_time: f32,
+    pub utc_offset: f32,
+    pub north_offset: f32,
+}
+
+impl Default for SolarPosition {
+    fn default() -> Self {
+        // Helsinki at noon on the summer solstice.
+        Self {
+            latitude: 60.17_f32.to_radians(),
+            longitude: 24.94_f32.to_radians(),
+            year: 2023,
+            month: 6,
+            day: 21,
+            local_time: 12.0,
+            utc_offset: 3.0,
+            north_offset: 0.0,
+        }
+    }
+}
+
+impl SolarPosition {
+    // Returns the solar elevation and azimuth in the conventions of
+    // `SkyParams`. Elevation is negative once the sun has set.
+    pub fn sky_angles(&self) -> Result<(f32, f32)> {
+        let month = time::Month::try_from(self.month)?;
+        let date = time::Date::from_calendar_date(self.year, month, self.day)?;
+
+        // Universal time. Local times past midnight are allowed, they roll
+        // over into the neighboring days.
+        let hours = f64::from(self.local_time) - f64::from(self.utc_offset);
+
+        // Days since J2000.0.
+        let julian_date = f64::from(date.to_julian_day()) - 0.5 + hours / 24.0;
+        let n = julian_date - 2_451_545.0;
+
+        // Ecliptic coordinates.
+        let omega = 2.1429 - 0.001_039_459_4 * n;
+        let mean_longitude = 4.895_063 + 0.017_202_791_698 * n;
+        let mean_anomaly = 6.240_06 + 0.017_201_969_9 * n;
+        let ecliptic_longitude = mean_longitude
+            + 0.033_416_07 * mean_anomaly.sin()
+            + 0.000_348_94 * (2.0 * mean_anomaly).sin()
+            - 0.000_113_4
+            - 0.000_020_3 * omega.sin();
+        let ecliptic_obliquity = 0.409_092_8 - 6.214e-9 * n + 0.000_039_6 * omega.cos();
+
+        // Celestial coordinates.
+        let right_ascension = f64::atan2(
+            ecliptic_obliquity.cos() * ecliptic_longitude.sin(),
+            ecliptic_longitude.cos(),
+        )
+        .rem_euclid(std::f64::consts::TAU);
+        let declination = (ecliptic_obliquity.sin() * ecliptic_longitude.sin()).asin();
+
+        // Local coordinates.
+        let greenwich_sidereal_time = 6.697_424_324_2 + 0.065_709_828_3 * n + hours;
+        let local_sidereal_time =
+            (greenwich_sidereal_time * 15.0).to_radians() + f64::from(self.longitude);
+        let hour_angle = local_sidereal_time - right_ascension;
+        let latitude = f64::from(self.latitude);
+        let zenith = (latitude.cos() * hour_angle.cos() * declination.cos()
+            + declination.sin() * latitude.sin())
+        .clamp(-1.0, 1.0)
+        .acos();
+        let azimuth = f64::atan2(
+            -hour_angle.sin(),
+            declination.tan() * latitude.cos() - latitude.sin() * hour_angle.cos(),
+        );
+
+        // Parallax correction.
+        let zenith = zenith + (EARTH_MEAN_RADIUS_KM / ASTRONOMICAL_UNIT_KM) * zenith.sin();
+
+        // Geographic azimuth goes clockwise from north, which matches the
+        // direction of scene azimuth when viewed from above.
+        let elevation = 0.5 * PI - zenith as f32;
+        let azimuth = (self.north_offset + azimuth as f32).rem_euclid(TAU);
+        Ok((elevation, azimuth))
+    }
+}
+
+impl GuiElement for SolarPosition {
+    fn gui(&mut self, ui: &imgui::Ui) {
+        imgui::AngleSlider::new("Latitude")
+            .min_degrees(-90.0)
+            .max_degrees(90.0)
+            .build(ui, &mut self.latitude);
+        imgui::AngleSlider::new("Longitude")
+            .min_degrees(-180.0)
+            .max_degrees(180.0)
+            .build(ui, &mut self.longitude);
+        ui.input_int("Year", &mut self.year).build();
+        ui.slider("Month", 1, 12, &mut self.month);
+        ui.slider("Day", 1, 31, &mut self.day);
+        if let Ok(month) = time::Month::try_from(self.month) {
+            while self.day > 28
+                && time::Date::from_calendar_date(self.year, month, self.day).is_err()
+            {
+                self.day -= 1;
+            }
+        }
+        ui.slider("Local time", 0.0, 24.0, &mut self.local_time);
+        ui.slider("UTC offset", -12.0, 14.0, &mut self.utc_offset);
+        imgui::AngleSlider::new("North offset")
+            .min_degrees(0.0)
+            .max_degrees(360.0)
+            .build(ui, &mut self.north_offset);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solar_noon() {
+        // On the equinox, the sun at solar noon is due south at an elevation
+        // of 90° minus latitude.
+        let position = SolarPosition {
+            latitude: 45.0_f32.to_radians(),
+            longitude: 0.0,
+            year: 2023,
+            month: 3,
+            day: 20,
+            local_time: 12.12,
+            utc_offset: 0.0,
+            north_offset: 0.0,
+        };
+        let (elevation, azimuth) = position.sky_angles().unwrap();
+        assert_abs_diff_eq!(elevation.to_degrees(), 45.0, epsilon = 0.5);
+        assert_abs_diff_eq!(azimuth.to_degrees(), 180.0, epsilon = 1.0);
+    }
+
+    #[test]
+    fn sunrise_and_sunset() {
+        let mut position = SolarPosition {
+            local_time: 5.0,
+            ..SolarPosition::default()
+        };
+        let (elevation, azimuth) = position.sky_angles().unwrap();
+        assert!(elevation < 10.0_f32.to_radians());
+        assert!((0.0..90.0).contains(&azimuth.to_degrees()));
+
+        position.local_time = 22.0;
+        let (elevation, azimuth) = position.sky_angles().unwrap();
+        assert!(elevation < 10.0_f32.to_radians());
+        assert!((270.0..360.0).contains(&azimuth.to_degrees()));
+
+        // At night the sun is just below the horizon in the north, and the sky
+        // has no sun to sample.
+        position.local_time = 1.0;
+        let (elevation, azimuth) = position.sky_angles().unwrap();
+        assert!((-10.0..0.0).contains(&elevation.to_degrees()));
+        assert!(!(45.0..315.0).contains(&azimuth.to_degrees()));
+        let sky = SkyState::new(&SkyParams {
+            elevation,
+            azimuth,
+            ..SkyParams::default()
+        })
+        .unwrap();
+        assert!(sky.sample_sun((0.5, 0.5)).is_none());
+        assert_eq!(sky.sun_radiance(&sky.sun_dir()), ColorRgb::BLACK);
+    }
+}
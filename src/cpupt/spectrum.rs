@@ -0,0 +1,388 @@
+use super::*;
+
+//
+// Sources:
+//
+// Hero Wavelength Spectral Sampling
+// Alexander Wilkie, Sehera Nawaz, Marc Droske, Andrea Weidlich, Johannes Hanika,
+// 2014
+// https://doi.org/10.1111/cgf.12419
+//
+// A Low-Dimensional Function Space for Efficient Spectral Upsampling
+// Wenzel Jakob, Johannes Hanika, 2019
+// https://rgl.epfl.ch/publications/Jakob2019Spectral
+//
+// Simple Analytic Approximations to the CIE XYZ Color Matching Functions
+// Chris Wyman, Peter-Pike Sloan, Peter Shirley, 2013
+// https://jcgt.org/published/0002/02/01/
+//
+
+pub const LAMBDA_MIN: f32 = 380.0;
+pub const LAMBDA_MAX: f32 = 780.0;
+
+// CIE standard illuminant D65 from 380nm to 780nm in 10nm steps.
+const D65: [f32; 41] = [
+    49.9755, 54.6482, 82.7549, 91.486, 93.4318, 86.6823, 104.865, 117.008, 117.812, 114.861,
+    115.923, 108.811, 109.354, 107.802, 104.790, 107.689, 104.405, 104.046, 100.000, 96.3342,
+    95.788, 88.6856, 90.0062, 89.5991, 87.6987, 83.2886, 83.6992, 80.0268, 80.2146, 82.2778,
+    78.2842, 69.7213, 71.6091, 74.349, 61.604, 69.8856, 75.087, 63.5927, 46.4182, 66.8054, 63.3828,
+];
+
+// Spectra are integrated with the midpoint rule at this resolution.
+const INTEGRATION_STEP: f32 = 5.0;
+
+// Resolution of the coefficient table along each axis.
+const TABLE_RESOLUTION: usize = 16;
+
+//
+// Color matching
+//
+
+pub fn cie_xyz(lambda: f32) -> [f32; 3] {
+    let g = |mu: f32, sigma_lo: f32, sigma_hi: f32| {
+        let sigma = if lambda < mu { sigma_lo } else { sigma_hi };
+        (-0.5 * ((lambda - mu) / sigma).powi(2)).exp()
+    };
+    [
+        1.056 * g(599.8, 37.9, 31.0) + 0.362 * g(442.0, 16.0, 26.7) - 0.065 * g(501.1, 20.4, 26.2),
+        0.821 * g(568.8, 46.9, 40.5) + 0.286 * g(530.9, 16.3, 31.1),
+        1.217 * g(437.0, 11.8, 36.0) + 0.681 * g(459.0, 26.0, 13.8),
+    ]
+}
+
+pub fn d65(lambda: f32) -> f32 {
+    let t = ((lambda - LAMBDA_MIN) / 10.0).clamp(0.0, (D65.len() - 1) as f32);
+    let index = (t as usize).min(D65.len() - 2);
+    let f = t - index as f32;
+    (1.0 - f) * D65[index] + f * D65[index + 1]
+}
+
+// Integral of D65 against the luminance matching function. Illuminant spectra
+// are divided by it, so that a white illuminant has unit luminance.
+pub fn d65_luminance() -> f32 {
+    integration_wavelengths()
+        .map(|lambda| d65(lambda) * cie_xyz(lambda)[1] * INTEGRATION_STEP)
+        .sum()
+}
+
+pub fn linear_srgb_from_xyz([x, y, z]: [f32; 3]) -> ColorRgb {
+    ColorRgb::new(
+        3.2406 * x - 1.5372 * y - 0.4986 * z,
+        -0.9689 * x + 1.8758 * y + 0.0415 * z,
+        0.0557 * x - 0.2040 * y + 1.0570 * z,
+    )
+}
+
+fn xyz_from_linear_srgb(rgb: [f64; 3]) -> [f64; 3] {
+    let [r, g, b] = rgb;
+    [
+        0.4124 * r + 0.3576 * g + 0.1805 * b,
+        0.2126 * r + 0.7152 * g + 0.0722 * b,
+        0.0193 * r + 0.1192 * g + 0.9505 * b,
+    ]
+}
+
+fn integration_wavelengths() -> impl Iterator<Item = f32> {
+    let count = ((LAMBDA_MAX - LAMBDA_MIN) / INTEGRATION_STEP) as u32;
+    (0..count).map(|i| LAMBDA_MIN + (i as f32 + 0.5) * INTEGRATION_STEP)
+}
+
+//
+// Upsampling
+//
+
+// Reflectance spectra are sigmoids of quadratic polynomials over the visible
+// range. The coefficients are found for a grid of colors by Gauss-Newton
+// iteration in CIELAB, and interpolated in between.
+pub struct RgbToSpectrum {
+    scale: Vec<f32>,
+    coefficients: Vec<[f32; 3]>,
+    d65_luminance: f32,
+}
+
+impl RgbToSpectrum {
+    pub fn new() -> Self {
+        let res = TABLE_RESOLUTION;
+        let scale = (0..res)
+            .map(|k| smoothstep(smoothstep(k as f64 / (res - 1) as f64)))
+            .collect::<Vec<_>>();
+
+        // Tabulate the color matching functions under D65.
+        let d65_luminance = f64::from(d65_luminance());
+        let weights = integration_wavelengths()
+            .map(|lambda| {
+                let t = f64::from((lambda - LAMBDA_MIN) / (LAMBDA_MAX - LAMBDA_MIN));
+                let illuminant = f64::from(d65(lambda) * INTEGRATION_STEP) / d65_luminance;
+                let xyz = cie_xyz(lambda).map(|c| f64::from(c) * illuminant);
+                (t, xyz)
+            })
+            .collect::<Vec<_>>();
+        let spectrum_white = weights.iter().fold([0.0; 3], |acc, (_, xyz)| {
+            [acc[0] + xyz[0], acc[1] + xyz[1], acc[2] + xyz[2]]
+        });
+        let rgb_white = xyz_from_linear_srgb([1.0; 3]);
+
+        // Each table is for colors whose largest component is `l`. Solutions
+        // are continued from their neighbors along the scale axis, starting
+        // from a dark gray which converges easily.
+        let mut coefficients = vec![[0.0; 3]; 3 * res * res * res];
+        for l in 0..3 {
+            for j in 0..res {
+                let y = j as f64 / (res - 1) as f64;
+                for i in 0..res {
+                    let x = i as f64 / (res - 1) as f64;
+                    let mut solve = |k: usize, c: &mut [f64; 3]| {
+                        let z = scale[k];
+                        let mut rgb = [0.0; 3];
+                        rgb[l] = z;
+                        rgb[(l + 1) % 3] = x * z;
+                        rgb[(l + 2) % 3] = y * z;
+                        let target = lab(xyz_from_linear_srgb(rgb), rgb_white);
+                        gauss_newton(c, &weights, &target, spectrum_white);
+                        coefficients[((l * res + k) * res + j) * res + i] = c.map(|c| c as f32);
+                    };
+                    let start = res / 5;
+                    let mut c = [0.0; 3];
+                    for k in start..res {
+                        solve(k, &mut c);
+                    }
+                    let mut c = [0.0; 3];
+                    for k in (0..start).rev() {
+                        solve(k, &mut c);
+                    }
+                }
+            }
+        }
+
+        Self {
+            scale: scale.into_iter().map(|s| s as f32).collect(),
+            coefficients,
+            d65_luminance: d65_luminance as f32,
+        }
+    }
+
+    // Coefficients for a color in [0,1].
+    pub fn coefficients(&self, rgb: ColorRgb) -> [f32; 3] {
+        let rgb: [f32; 3] = rgb.into();
+        let rgb = rgb.map(|c| c.clamp(0.0, 1.0));
+
+        // Grays are constant spectra.
+        let spread = rgb[0].max(rgb[1]).max(rgb[2]) - rgb[0].min(rgb[1]).min(rgb[2]);
+        if spread <= 0.0 {
+            let v = rgb[0];
+            return [0.0, 0.0, (v - 0.5) / (v * (1.0 - v)).sqrt()];
+        }
+
+        // Table coordinates.
+        let res = TABLE_RESOLUTION;
+        let l = if rgb[0] >= rgb[1] && rgb[0] >= rgb[2] {
+            0
+        } else if rgb[1] >= rgb[2] {
+            1
+        } else {
+            2
+        };
+        let z = rgb[l];
+        let x = rgb[(l + 1) % 3] / z * (res - 1) as f32;
+        let y = rgb[(l + 2) % 3] / z * (res - 1) as f32;
+        let zi = self
+            .scale
+            .partition_point(|&s| s <= z)
+            .saturating_sub(1)
+            .min(res - 2);
+        let xi = (x as usize).min(res - 2);
+        let yi = (y as usize).min(res - 2);
+        let dz = (z - self.scale[zi]) / (self.scale[zi + 1] - self.scale[zi]);
+        let dx = x - xi as f32;
+        let dy = y - yi as f32;
+
+        // Trilinear interpolation.
+        let texel =
+            |k: usize, j: usize, i: usize| self.coefficients[((l * res + k) * res + j) * res + i];
+        let mut c = [0.0; 3];
+        for (k, wz) in [(zi, 1.0 - dz), (zi + 1, dz)] {
+            for (j, wy) in [(yi, 1.0 - dy), (yi + 1, dy)] {
+                for (i, wx) in [(xi, 1.0 - dx), (xi + 1, dx)] {
+                    let texel = texel(k, j, i);
+                    for n in 0..3 {
+                        c[n] += wz * wy * wx * texel[n];
+                    }
+                }
+            }
+        }
+        c
+    }
+}
+
+fn sigmoid_polynomial(c: &[f32; 3], lambda: f32) -> f32 {
+    let t = (lambda - LAMBDA_MIN) / (LAMBDA_MAX - LAMBDA_MIN);
+    let x = (c[0] * t + c[1]) * t + c[2];
+    if x.is_infinite() {
+        return if x > 0.0 { 1.0 } else { 0.0 };
+    }
+    0.5 + x / (2.0 * (1.0 + x * x).sqrt())
+}
+
+fn gauss_newton(c: &mut [f64; 3], weights: &[(f64, [f64; 3])], target: &[f64; 3], white: [f64; 3]) {
+    let residual = |c: &[f64; 3]| {
+        let mut xyz = [0.0; 3];
+        for (t, w) in weights {
+            let x = (c[0] * t + c[1]) * t + c[2];
+            let s = 0.5 + x / (2.0 * (1.0 + x * x).sqrt());
+            for n in 0..3 {
+                xyz[n] += s * w[n];
+            }
+        }
+        let lab = lab(xyz, white);
+        na::Vector3::new(lab[0] - target[0], lab[1] - target[1], lab[2] - target[2])
+    };
+
+    for _ in 0..15 {
+        let r = residual(c);
+        if r.norm() < 1e-6 {
+            break;
+        }
+
+        // Jacobian with forward differences.
+        let eps = 1e-5;
+        let mut jacobian = na::Matrix3::<f64>::zeros();
+        for n in 0..3 {
+            let mut c_eps = *c;
+            c_eps[n] += eps;
+            jacobian.set_column(n, &((residual(&c_eps) - r) / eps));
+        }
+        let Some(inverse) = jacobian.try_inverse() else {
+            break;
+        };
+        let step = inverse * r;
+        for n in 0..3 {
+            c[n] -= step[n];
+        }
+
+        // Keep the polynomial in a reasonable range.
+        let max = c.iter().fold(0.0_f64, |max, c| max.max(c.abs()));
+        if max > 200.0 {
+            for c in c.iter_mut() {
+                *c *= 200.0 / max;
+            }
+        }
+    }
+}
+
+fn lab(xyz: [f64; 3], white: [f64; 3]) -> [f64; 3] {
+    let f = |t: f64| {
+        let delta: f64 = 6.0 / 29.0;
+        if t > delta.powi(3) {
+            t.cbrt()
+        } else {
+            t / (3.0 * delta * delta) + 4.0 / 29.0
+        }
+    };
+    let fx = f(xyz[0] / white[0]);
+    let fy = f(xyz[1] / white[1]);
+    let fz = f(xyz[2] / white[2]);
+    [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+}
+
+fn smoothstep(x: f64) -> f64 {
+    x * x * (3.0 - 2.0 * x)
+}
+
+//
+// Sampling
+//
+
+// A path carries one sample per wavelength in the channels of `ColorRgb`. The
+// first wavelength is sampled uniformly, the rest are spaced evenly after it.
+#[derive(Clone, Copy)]
+pub struct SampledWavelengths<'a> {
+    table: &'a RgbToSpectrum,
+    lambdas: [f32; 3],
+}
+
+impl<'a> SampledWavelengths<'a> {
+    pub fn sample(table: &'a RgbToSpectrum, u: f32) -> Self {
+        let range = LAMBDA_MAX - LAMBDA_MIN;
+        let lambdas = [0.0, 1.0, 2.0].map(|i| {
+            let t = (u + i / 3.0).fract();
+            LAMBDA_MIN + t * range
+        });
+        Self { table, lambdas }
+    }
+
+    pub fn lambdas(&self) -> [f32; 3] {
+        self.lambdas
+    }
+
+    pub fn reflectance(&self, rgb: ColorRgb) -> ColorRgb {
+        let c = self.table.coefficients(rgb);
+        let [r, g, b] = self.lambdas.map(|lambda| sigmoid_polynomial(&c, lambda));
+        ColorRgb::new(r, g, b)
+    }
+
+    // Colors outside of [0,1] are scaled into the table and back.
+    pub fn unbounded(&self, rgb: ColorRgb) -> ColorRgb {
+        let rgb = ColorRgb::new(rgb.r().max(0.0), rgb.g().max(0.0), rgb.b().max(0.0));
+        let scale = 2.0 * rgb.max_element();
+        if scale <= 0.0 {
+            return ColorRgb::BLACK;
+        }
+        scale * self.reflectance(rgb * (1.0 / scale))
+    }
+
+    pub fn illuminant(&self, rgb: ColorRgb) -> ColorRgb {
+        self.unbounded(rgb) * self.d65()
+    }
+
+    // Normalized D65 at the sampled wavelengths.
+    pub fn d65(&self) -> ColorRgb {
+        let [r, g, b] = self
+            .lambdas
+            .map(|lambda| d65(lambda) / self.table.d65_luminance);
+        ColorRgb::new(r, g, b)
+    }
+
+    pub fn to_linear_srgb(self, radiance: ColorRgb) -> ColorRgb {
+        let radiance: [f32; 3] = radiance.into();
+        let inv_pdf = (LAMBDA_MAX - LAMBDA_MIN) / 3.0;
+        let mut xyz = [0.0; 3];
+        for (lambda, radiance) in self.lambdas.into_iter().zip(radiance) {
+            let cmf = cie_xyz(lambda);
+            for n in 0..3 {
+                xyz[n] += radiance * cmf[n] * inv_pdf;
+            }
+        }
+        linear_srgb_from_xyz(xyz)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spectral_round_trip() {
+        // Upsampled colors must integrate back to themselves under D65.
+        let table = RgbToSpectrum::new();
+        let colors = [
+            ColorRgb::new(0.5, 0.5, 0.5),
+            ColorRgb::new(0.8, 0.2, 0.1),
+            ColorRgb::new(0.1, 0.6, 0.3),
+            ColorRgb::new(0.2, 0.3, 0.9),
+            ColorRgb::new(0.95, 0.9, 0.4),
+        ];
+        let samples = 3000;
+        for color in colors {
+            let mut estimate = ColorRgb::BLACK;
+            for i in 0..samples {
+                let u = (i as f32 + 0.5) / samples as f32;
+                let wavelengths = SampledWavelengths::sample(&table, u);
+                let radiance = wavelengths.illuminant(color);
+                estimate += wavelengths.to_linear_srgb(radiance) * (1.0 / samples as f32);
+            }
+            assert_abs_diff_eq!(estimate.r(), color.r(), epsilon = 0.02);
+            assert_abs_diff_eq!(estimate.g(), color.g(), epsilon = 0.02);
+            assert_abs_diff_eq!(estimate.b(), color.b(), epsilon = 0.02);
+        }
+    }
+}
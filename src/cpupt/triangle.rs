@@ -5,7 +5,9 @@ pub struct Triangle {
     pub positions: [Point3; 3],
     pub tex_coords: [Point2; 3],
     pub normals: [Normal; 3],
+    pub colors: [ColorRgb; 3],
     pub material: u32,
+    pub mesh: u32,
 }
 
 impl Triangle {
@@ -24,4 +26,10 @@ impl Triangle {
                 + self.normals[2].into_inner() * barycentrics.z,
         )
     }
+
+    pub fn interpolated_color(&self, barycentrics: &Vec3) -> ColorRgb {
+        self.colors[0] * barycentrics.x
+            + self.colors[1] * barycentrics.y
+            + self.colors[2] * barycentrics.z
+    }
 }
@@ -0,0 +1,135 @@
+use super::*;
+
+// White balance adapts the image from the white of an illuminant to the white
+// of the display. The illuminant is given by its temperature on the Planckian
+// locus, and a tint which moves it perpendicular to the locus in the CIE 1960
+// UCS, positive towards green. Both whites go through the Bradford chromatic
+// adaptation transform. The display white is the locus at 6504 K, instead of
+// D65 itself, which keeps the default white balance an identity.
+
+//
+// Sources:
+//
+// Design of Advanced Color Temperature Control System for HDTV Applications
+// Kang et al., 2002
+// https://doi.org/10.3938/jkps.41.865
+//
+// Chromatic Adaptation
+// Bruce Lindbloom
+// http://www.brucelindbloom.com/index.html?Eqn_ChromAdapt.html
+//
+// Calculation of CCT and Duv and Practical Conversion Formulae
+// Yoshi Ohno, 2011
+// https://www.energy.gov/sites/prod/files/2015/02/f19/ohno_cct_duv_lightfair2011.pdf
+//
+
+const MIN_TEMPERATURE: f32 = 1667.0;
+const MAX_TEMPERATURE: f32 = 25000.0;
+const DISPLAY_TEMPERATURE: f32 = 6504.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WhiteBalance {
+    // Kelvin, of the light that should look neutral.
+    pub temperature: f32,
+    // Distance from the Planckian locus, Duv.
+    pub tint: f32,
+}
+
+impl Default for WhiteBalance {
+    fn default() -> Self {
+        Self {
+            temperature: DISPLAY_TEMPERATURE,
+            tint: 0.0,
+        }
+    }
+}
+
+impl WhiteBalance {
+    // Linear RGB to linear RGB, in the given color space.
+    pub fn transform(self, space: ColorSpace) -> Mat3 {
+        let adaptation = chromatic_adaptation(
+            white_point(self.temperature, self.tint),
+            white_point(DISPLAY_TEMPERATURE, 0.0),
+        );
+        space.rgb_from_xyz() * adaptation * space.xyz_from_rgb()
+    }
+}
+
+impl GuiElement for WhiteBalance {
+    fn gui(&mut self, ui: &imgui::Ui) {
+        ui.slider_config("Temperature", MIN_TEMPERATURE, MAX_TEMPERATURE)
+            .flags(imgui::SliderFlags::LOGARITHMIC)
+            .display_format("%.0f K")
+            .build(&mut self.temperature);
+        ui.slider_config("Tint", -0.05, 0.05)
+            .display_format("%.3f")
+            .build(&mut self.tint);
+    }
+}
+
+// CIE 1931 chromaticity of the Planckian locus.
+fn planckian_xy(temperature: f32) -> (f32, f32) {
+    let t = temperature.clamp(MIN_TEMPERATURE, MAX_TEMPERATURE);
+    let (t1, t2, t3) = (1e3 / t, 1e6 / (t * t), 1e9 / (t * t * t));
+    let x = if t <= 4000.0 {
+        -0.266_123_9 * t3 - 0.234_358_9 * t2 + 0.877_695_6 * t1 + 0.179_910
+    } else {
+        -3.025_847 * t3 + 2.107_037_9 * t2 + 0.222_634_7 * t1 + 0.240_390
+    };
+    let (x2, x3) = (x * x, x * x * x);
+    let y = if t <= 2222.0 {
+        -1.106_381_4 * x3 - 1.348_110_2 * x2 + 2.185_558_3 * x - 0.202_196_83
+    } else if t <= 4000.0 {
+        -0.954_947_6 * x3 - 1.374_185_9 * x2 + 2.091_37 * x - 0.167_488_67
+    } else {
+        3.081_758 * x3 - 5.873_387 * x2 + 3.751_13 * x - 0.370_014_83
+    };
+    (x, y)
+}
+
+// CIE XYZ of the white, with a luminance of one.
+fn white_point(temperature: f32, tint: f32) -> Vec3 {
+    let uv = |(x, y): (f32, f32)| {
+        let d = -2.0 * x + 12.0 * y + 3.0;
+        na::Vector2::new(4.0 * x / d, 6.0 * y / d)
+    };
+    let locus = uv(planckian_xy(temperature));
+
+    // Offset perpendicular to the locus, towards green.
+    let tangent = uv(planckian_xy(temperature + 10.0)) - uv(planckian_xy(temperature - 10.0));
+    let normal = na::Vector2::new(-tangent.y, tangent.x)
+        .try_normalize(1e-9)
+        .unwrap_or_else(|| na::Vector2::new(0.0, 1.0));
+    let normal = if normal.y < 0.0 { -normal } else { normal };
+    let uv = locus + tint * normal;
+
+    let (u, v) = (uv.x, uv.y);
+    let d = 2.0 * u - 8.0 * v + 4.0;
+    xyz_from_xy(3.0 * u / d, 2.0 * v / d)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn white_balance() {
+        // The default is an identity.
+        for space in ColorSpace::ALL {
+            let identity = WhiteBalance::default().transform(space);
+            assert_abs_diff_eq!(identity, Mat3::identity(), epsilon = 1e-5);
+        }
+
+        // The white of a warm light becomes neutral.
+        let srgb_from_xyz = ColorSpace::Srgb.rgb_from_xyz();
+        let display = srgb_from_xyz * white_point(DISPLAY_TEMPERATURE, 0.0);
+        for (temperature, tint) in [(3000.0, 0.0), (10000.0, 0.01)] {
+            let white = srgb_from_xyz * white_point(temperature, tint);
+            let balanced = WhiteBalance { temperature, tint }.transform(ColorSpace::Srgb) * white;
+            let ratio = balanced.component_div(&display);
+            assert_abs_diff_eq!(ratio.x, ratio.y, epsilon = 1e-3);
+            assert_abs_diff_eq!(ratio.z, ratio.y, epsilon = 1e-3);
+        }
+    }
+}
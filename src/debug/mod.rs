@@ -14,7 +14,6 @@ const ANIMATION_DELAY_NUM: u16 = 1;
 const ANIMATION_FRAME_COUNT: u32 = 60;
 
 const PLOT_COLOR_INCOMING: ColorRgb = ColorRgb::new(1.0, 0.0, 1.0);
-const PLOT_COLOR_SUN: ColorRgb = ColorRgb::new(1.0, 0.0, 1.0);
 const PLOT_COLOR_SAMPLE: ColorRgb = ColorRgb::new(1.0, 0.26225, 0.0);
 const PLOT_COLOR_TEXT: ColorRgb = ColorRgb::new(1.0, 1.0, 1.0);
 const PLOT_COLOR_POS_X: ColorRgb = ColorRgb::new(1.0, 0.0, 0.0);
@@ -118,6 +117,11 @@ fn brdf_visualizations() -> Result<()> {
         specular_tint: vz::cfg::Value<f32>,
         sheen: vz::cfg::Value<f32>,
         sheen_tint: vz::cfg::Value<f32>,
+        sheen_color: vz::cfg::Value<ColorRgb>,
+        sheen_roughness: vz::cfg::Value<f32>,
+        iridescence: vz::cfg::Value<f32>,
+        iridescence_thickness: vz::cfg::Value<f32>,
+        conductor: bxdfs::ConductorPreset,
     }
 
     impl Default for Task {
@@ -139,6 +143,11 @@ fn brdf_visualizations() -> Result<()> {
                 specular_tint: Constant(1.0),
                 sheen: Constant(0.0),
                 sheen_tint: Constant(1.0),
+                sheen_color: Constant(ColorRgb::BLACK),
+                sheen_roughness: Constant(0.5),
+                iridescence: Constant(0.0),
+                iridescence_thickness: Constant(400.0),
+                conductor: bxdfs::ConductorPreset::default(),
             }
         }
     }
@@ -156,7 +165,10 @@ fn brdf_visualizations() -> Result<()> {
 
     // Task definitions.
     let tasks = {
-        use bxdfs::Model::{DisneyDiffuse, DisneySheen, DisneySpecular, Lambertian};
+        use bxdfs::Model::{
+            CharlieSheen, Conductor, Disney, DisneyDiffuse, DisneySheen, DisneySpecular,
+            Lambertian, OrenNayar,
+        };
         use vz::cfg::Value::{Constant, Keyframes};
         use BrdfComponent::{Pdf, R};
 
@@ -229,6 +241,30 @@ fn brdf_visualizations() -> Result<()> {
                 ]
             };
         }
+        macro_rules! oren_nayar {
+            ($name: expr, $key: ident, $value: expr) => {
+                vec![
+                    Task {
+                        group: "oren-nayar",
+                        name: concat!($name, "-", "r"),
+                        model: OrenNayar,
+                        comp: R,
+                        seq: seq_sobol.clone(),
+                        $key: $value.clone(),
+                        ..Task::default()
+                    },
+                    Task {
+                        group: "oren-nayar",
+                        name: concat!($name, "-", "pdf"),
+                        model: OrenNayar,
+                        comp: Pdf,
+                        seq: seq_sobol.clone(),
+                        $key: $value.clone(),
+                        ..Task::default()
+                    },
+                ]
+            };
+        }
         macro_rules! disney_specular {
             ($name: expr, $key: ident, $value: expr) => {
                 vec![
@@ -281,12 +317,121 @@ fn brdf_visualizations() -> Result<()> {
                 ]
             };
         }
+        macro_rules! thin_film {
+            ($name: expr, $key: ident, $value: expr) => {
+                vec![
+                    Task {
+                        group: "thin-film",
+                        name: concat!($name, "-", "r"),
+                        model: DisneySpecular,
+                        comp: R,
+                        seq: seq_sobol.clone(),
+                        $key: $value.clone(),
+                        metallic: Constant(1.0),
+                        iridescence: Constant(1.0),
+                        ..Task::default()
+                    },
+                    Task {
+                        group: "thin-film",
+                        name: concat!($name, "-", "pdf"),
+                        model: DisneySpecular,
+                        comp: Pdf,
+                        seq: seq_sobol.clone(),
+                        $key: $value.clone(),
+                        metallic: Constant(1.0),
+                        iridescence: Constant(1.0),
+                        ..Task::default()
+                    },
+                ]
+            };
+        }
+        macro_rules! charlie_sheen {
+            ($name: expr, $key: ident, $value: expr) => {
+                vec![
+                    Task {
+                        group: "charlie-sheen",
+                        name: concat!($name, "-", "r"),
+                        model: CharlieSheen,
+                        comp: R,
+                        seq: seq_sobol.clone(),
+                        $key: $value.clone(),
+                        sheen_color: Constant(DEFAULT_ALT_BASE_COLOR),
+                        ..Task::default()
+                    },
+                    Task {
+                        group: "charlie-sheen",
+                        name: concat!($name, "-", "pdf"),
+                        model: CharlieSheen,
+                        comp: Pdf,
+                        seq: seq_sobol.clone(),
+                        $key: $value.clone(),
+                        sheen_color: Constant(DEFAULT_ALT_BASE_COLOR),
+                        ..Task::default()
+                    },
+                ]
+            };
+        }
+
+        macro_rules! disney {
+            ($name: expr, $key: ident, $value: expr) => {
+                vec![
+                    Task {
+                        group: "disney",
+                        name: concat!($name, "-", "r"),
+                        model: Disney,
+                        comp: R,
+                        seq: seq_sobol.clone(),
+                        $key: $value.clone(),
+                        ..Task::default()
+                    },
+                    Task {
+                        group: "disney",
+                        name: concat!($name, "-", "pdf"),
+                        model: Disney,
+                        comp: Pdf,
+                        seq: seq_sobol.clone(),
+                        $key: $value.clone(),
+                        ..Task::default()
+                    },
+                ]
+            };
+        }
+
+        macro_rules! conductor {
+            ($name: expr, $key: ident, $value: expr) => {
+                vec![
+                    Task {
+                        group: "conductor",
+                        name: concat!($name, "-", "r"),
+                        model: Conductor,
+                        comp: R,
+                        seq: seq_sobol.clone(),
+                        $key: $value.clone(),
+                        ..Task::default()
+                    },
+                    Task {
+                        group: "conductor",
+                        name: concat!($name, "-", "pdf"),
+                        model: Conductor,
+                        comp: Pdf,
+                        seq: seq_sobol.clone(),
+                        $key: $value.clone(),
+                        ..Task::default()
+                    },
+                ]
+            };
+        }
 
         let unit = Keyframes(vec![
             vz::cfg::keyframe!(0.0, 0.0, CubicInOut),
             vz::cfg::keyframe!(1.0, 1.0, CubicInOut),
         ]);
 
+        let thickness = Keyframes(vec![
+            vz::cfg::keyframe!(0.0, 0.0, CubicInOut),
+            vz::cfg::keyframe!(1.0, 1000.0, CubicInOut),
+        ]);
+
         let unit_color = Keyframes(vec![
             vz::cfg::keyframe!(0.0, DEFAULT_BASE_COLOR, CubicInOut),
             vz::cfg::keyframe!(1.0, DEFAULT_ALT_BASE_COLOR, CubicInOut),
@@ -304,6 +449,9 @@ fn brdf_visualizations() -> Result<()> {
             disney_diffuse!("incoming", incoming, unit),
             disney_diffuse!("base_color", base_color, unit_color),
             disney_diffuse!("roughness", roughness, unit),
+            oren_nayar!("incoming", incoming, unit),
+            oren_nayar!("base_color", base_color, unit_color),
+            oren_nayar!("roughness", roughness, unit),
             disney_specular!("incoming", incoming, unit),
             disney_specular!("base_color", base_color, unit_color),
             disney_specular!("roughness", roughness, unit),
@@ -313,6 +461,17 @@ fn brdf_visualizations() -> Result<()> {
             disney_specular!("anisotropic", anisotropic, unit),
             disney_sheen!("incoming", incoming, unit),
             disney_sheen!("sheen_tint", sheen_tint, unit),
+            thin_film!("incoming", incoming, unit),
+            thin_film!("iridescence_thickness", iridescence_thickness, thickness),
+            charlie_sheen!("incoming", incoming, unit),
+            charlie_sheen!("sheen_roughness", sheen_roughness, unit),
+            disney!("metallic", metallic, unit),
+            disney!("specular", specular, unit),
+            disney!("roughness", roughness, unit),
+            conductor!("incoming", incoming, unit),
+            conductor!("roughness", roughness, unit),
+            conductor!("anisotropic", anisotropic, unit),
+            conductor!("copper", conductor, bxdfs::ConductorPreset::Copper),
         ]
         .into_iter()
         .flatten()
@@ -332,6 +491,10 @@ fn brdf_visualizations() -> Result<()> {
             let specular_tint: vz::anim::Value<_> = task.specular_tint.into();
             let sheen: vz::anim::Value<_> = task.sheen.into();
             let sheen_tint: vz::anim::Value<_> = task.sheen_tint.into();
+            let sheen_color: vz::anim::Value<_> = task.sheen_color.into();
+            let sheen_roughness: vz::anim::Value<_> = task.sheen_roughness.into();
+            let iridescence: vz::anim::Value<_> = task.iridescence.into();
+            let iridescence_thickness: vz::anim::Value<_> = task.iridescence_thickness.into();
 
             let mut frames = vec![];
             for frame_index in 0..ANIMATION_FRAME_COUNT {
@@ -348,6 +511,13 @@ fn brdf_visualizations() -> Result<()> {
                 let specular_tint = specular_tint.value(time);
                 let sheen = sheen.value(time);
                 let sheen_tint = sheen_tint.value(time);
+                let sheen_color = sheen_color.value(time);
+                let sheen_roughness = sheen_roughness.value(time);
+                let thin_film = bxdfs::ThinFilm {
+                    weight: iridescence.value(time),
+                    thickness: iridescence_thickness.value(time),
+                    ..bxdfs::ThinFilm::default()
+                };
 
                 // Incoming vector.
                 let incoming_angle_theta = incoming * PI;
@@ -382,6 +552,13 @@ fn brdf_visualizations() -> Result<()> {
                             roughness,
                         }))
                     }
+                    bxdfs::Model::OrenNayar => {
+                        Box::new(bxdfs::OrenNayar::new(&bxdfs::OrenNayarParams {
+                            hemisphere,
+                            base_color,
+                            roughness,
+                        }))
+                    }
                     bxdfs::Model::DisneySpecular => {
                         Box::new(bxdfs::DisneySpecular::new(&bxdfs::DisneySpecularParams {
                             base_color,
@@ -390,6 +567,7 @@ fn brdf_visualizations() -> Result<()> {
                             specular_tint,
                             roughness,
                             anisotropic,
+                            thin_film,
                         }))
                     }
                     bxdfs::Model::DisneySheen => {
@@ -400,6 +578,38 @@ fn brdf_visualizations() -> Result<()> {
                             sheen_tint,
                         }))
                     }
+                    bxdfs::Model::CharlieSheen => {
+                        Box::new(bxdfs::CharlieSheen::new(&bxdfs::CharlieSheenParams {
+                            hemisphere,
+                            sheen_color,
+                            sheen_roughness,
+                        }))
+                    }
+                    bxdfs::Model::Disney => Box::new(bxdfs::Disney::new(&bxdfs::DisneyParams {
+                        hemisphere,
+                        base_color,
+                        metallic,
+                        specular,
+                        specular_tint,
+                        roughness,
+                        anisotropic,
+                        sheen,
+                        sheen_tint,
+                        sheen_color,
+                        sheen_roughness,
+                        thin_film,
+                    })),
+                    bxdfs::Model::Conductor => {
+                        let (eta, k) = task.conductor.ior();
+                        Box::new(bxdfs::Conductor::new(&bxdfs::ConductorParams {
+                            eta,
+                            k,
+                            roughness,
+                            anisotropic,
+                            thin_film,
+                            working_from_srgb: Mat3::identity(),
+                        }))
+                    }
                 };
 
                 // Plot.
@@ -569,6 +779,13 @@ fn sky_model_visualizations() -> Result<()> {
     // Default exposure.
     let exposure = cpupt::Exposure::default();
 
+    // Sky models.
+    let models = [
+        cpupt::SkyModelKind::HosekWilkie,
+        cpupt::SkyModelKind::Preetham,
+        cpupt::SkyModelKind::Nishita,
+    ];
+
     // Execute tasks.
     let results = tasks
         .into_par_iter()
@@ -589,25 +806,44 @@ fn sky_model_visualizations() -> Result<()> {
                 let turbidity = turbidity.value(time);
                 let albedo = albedo.value(time);
 
-                // Create sky model.
-                let sky = cpupt::SkyState::new(&cpupt::SkyParams {
-                    elevation,
-                    azimuth,
-                    turbidity,
-                    albedo,
-                })?;
+                // Plot every sky model side by side.
+                let mut images = vec![];
+                let mut max_intensities = vec![];
+                for model in models {
+                    // Create sky model.
+                    let sky = cpupt::SkyState::new(&cpupt::SkyParams {
+                        model,
+                        elevation,
+                        azimuth,
+                        turbidity,
+                        albedo,
+                    })?;
 
-                // Plot.
-                let mut plot = plot::Plot::new(|wo| {
-                    let wo = normal!(wo.0);
-                    exposure.expose(sky.radiance(&wo)).tonemap()
-                });
-                plot.draw_debug_vectors();
-                plot.draw_vector(bxdfs::LocalVector(*sky.sun_dir()), PLOT_COLOR_SUN);
+                    // Plot.
+                    let mut plot = plot::Plot::new(|wo| {
+                        let wo = normal!(wo.0);
+                        exposure
+                            .expose(sky.radiance(&wo) + sky.sun_radiance(&wo))
+                            .tonemap(Tonemapper::default())
+                    });
+                    plot.draw_debug_vectors();
+                    let sun_dir = sky.sun_dir();
+                    let sun_color = exposure
+                        .expose(sky.radiance(&sun_dir) + sky.sun_radiance(&sun_dir))
+                        .tonemap(Tonemapper::default());
+                    plot.draw_disc(
+                        bxdfs::LocalVector(*sun_dir),
+                        cpupt::SkyState::sun_angular_radius(),
+                        sun_color,
+                    );
+                    max_intensities.push(format!("{:.02}", plot.intensities().max()));
+                    images.push(plot.into_image());
+                }
 
                 // Annotate.
                 let text_box = vz::annotation::TextBox::new()
                     .line([("name", task.name)])
+                    .line([("models", models.map(cpupt::SkyModelKind::name).join(" | "))])
                     .line([
                         ("elevation", format!("{:.02}°", elevation.to_degrees())),
                         ("azimuth", format!("{:.02}°", azimuth.to_degrees())),
@@ -617,16 +853,13 @@ fn sky_model_visualizations() -> Result<()> {
                         ("albedo", format!("{albedo:.02}")),
                         ("exposure", format!("{exposure:.02}")),
                     ])
-                    .line([
-                        ("min", format!("{:.02}", plot.intensities().min())),
-                        ("max", format!("{:.02}", plot.intensities().max())),
-                    ])
+                    .line([("max", max_intensities.join(" | "))])
                     .line([
                         ("time", format!("{time:.02}")),
                         ("frame", format!("{frame_index}/{ANIMATION_FRAME_COUNT}")),
                     ])
                     .build();
-                let mut image = plot.into_image();
+                let mut image = vz::image::Rgb::hstack(&images);
                 image.draw_text(&font, PLOT_COLOR_TEXT, &text_box);
 
                 // Push.
@@ -670,11 +903,386 @@ fn sky_model_visualizations() -> Result<()> {
 }
 
 //
-// Runner
+// GGX multiple scattering
 //
 
-pub fn run() -> Result<()> {
-    brdf_visualizations()?;
-    sky_model_visualizations()?;
+const GGX_ALBEDO_SAMPLE_COUNT: u32 = 1 << 16;
+const FURNACE_SAMPLE_COUNT: u32 = 1 << 12;
+const FURNACE_ROUGHNESS_STEPS: u32 = 32;
+
+fn ggx_albedo_tables() -> Result<()> {
+    use std::fmt::Write;
+
+    let (albedo, average_albedo) = bxdfs::ggx_albedo_tables(GGX_ALBEDO_SAMPLE_COUNT);
+    let size = bxdfs::GGX_ALBEDO_SIZE;
+    let row = |values: &[f32]| {
+        let values = values
+            .iter()
+            .map(|value| format!("{value:.5}"))
+            .collect::<Vec<_>>();
+        format!("[{}]", values.join(", "))
+    };
+
+    let mut output = String::new();
+    writeln!(
+        &mut output,
+        "// Generated by `raydiance debug ggx-albedo`, do not edit."
+    )?;
+    writeln!(&mut output)?;
+    writeln!(&mut output, "#[rustfmt::skip]")?;
+    writeln!(
+        &mut output,
+        "pub const GGX_ALBEDO: [[f32; {size}]; {size}] = ["
+    )?;
+    for values in &albedo {
+        writeln!(&mut output, "    {},", row(values))?;
+    }
+    writeln!(&mut output, "];")?;
+    writeln!(&mut output)?;
+    writeln!(&mut output, "#[rustfmt::skip]")?;
+    writeln!(
+        &mut output,
+        "pub const GGX_AVERAGE_ALBEDO: [f32; {size}] = {};",
+        row(&average_albedo)
+    )?;
+
+    let path = manifest_dir().join("src/cpupt/ggx_albedo.rs");
+    std::fs::write(&path, output)?;
+    info!("Wrote to {}", path.display());
+    Ok(())
+}
+
+fn sheen_albedo_table() -> Result<()> {
+    use std::fmt::Write;
+
+    let albedo = bxdfs::sheen_albedo_table(GGX_ALBEDO_SAMPLE_COUNT);
+    let size = bxdfs::SHEEN_ALBEDO_SIZE;
+
+    let mut output = String::new();
+    writeln!(
+        &mut output,
+        "// Generated by `raydiance debug sheen-albedo`, do not edit."
+    )?;
+    writeln!(&mut output)?;
+    // Table values can happen to be close to well known constants.
+    writeln!(&mut output, "#[rustfmt::skip]")?;
+    writeln!(&mut output, "#[allow(clippy::approx_constant)]")?;
+    writeln!(
+        &mut output,
+        "pub const SHEEN_ALBEDO: [[f32; {size}]; {size}] = ["
+    )?;
+    for values in &albedo {
+        let values = values
+            .iter()
+            .map(|value| format!("{value:.5}"))
+            .collect::<Vec<_>>();
+        writeln!(&mut output, "    [{}],", values.join(", "))?;
+    }
+    writeln!(&mut output, "];")?;
+
+    let path = manifest_dir().join("src/cpupt/sheen_albedo.rs");
+    std::fs::write(&path, output)?;
+    info!("Wrote to {}", path.display());
     Ok(())
 }
+
+// White furnace test: a perfectly reflective rough metal should reflect all
+// incoming energy. Without the compensation the single scattering albedo
+// drops with roughness, especially at grazing angles.
+fn ggx_furnace_test() -> Result<()> {
+    use bxdfs::Bxdf;
+    use plotters::prelude::*;
+
+    let work_dir = work_dir();
+    let angles = [0.0_f32, 60.0, 80.0];
+    let colors = [
+        RGBColor(255, 140, 0),
+        RGBColor(0, 107, 255),
+        RGBColor(0, 160, 60),
+    ];
+
+    let roughness_values = (0..=FURNACE_ROUGHNESS_STEPS)
+        .map(|step| step as f32 / FURNACE_ROUGHNESS_STEPS as f32)
+        .collect::<Vec<_>>();
+    let curves = angles
+        .iter()
+        .map(|angle| {
+            let cos_theta = angle.to_radians().cos();
+            let wo = bxdfs::LocalVector(vector![
+                (1.0 - cos_theta * cos_theta).sqrt(),
+                cos_theta,
+                0.0
+            ]);
+            let mut single = vec![];
+            let mut compensated = vec![];
+            for &roughness in &roughness_values {
+                let ggx = bxdfs::Ggx::new(roughness, 0.0);
+                single.push((
+                    roughness,
+                    ggx.directional_albedo(cos_theta, FURNACE_SAMPLE_COUNT),
+                ));
+
+                let bxdf = bxdfs::DisneySpecular::new(&bxdfs::DisneySpecularParams {
+                    base_color: ColorRgb::WHITE,
+                    metallic: 1.0,
+                    specular: 0.5,
+                    specular_tint: 0.0,
+                    roughness,
+                    anisotropic: 0.0,
+                    thin_film: bxdfs::ThinFilm::default(),
+                });
+                let mut sum = 0.0;
+                for sample_index in 0..FURNACE_SAMPLE_COUNT {
+                    let u = SampleSequence::Sobol.sample(sample_index);
+                    if let Some(sample) = bxdf.sample(&wo, u) {
+                        sum += sample.r.mean() * sample.wi.0.y.abs() / sample.pdf;
+                    }
+                }
+                compensated.push((roughness, sum / FURNACE_SAMPLE_COUNT as f32));
+            }
+            (single, compensated)
+        })
+        .collect::<Vec<_>>();
+
+    // Create svg file.
+    let mut output_svg = String::new();
+    {
+        let root = SVGBackend::with_string(&mut output_svg, (600, 400)).into_drawing_area();
+        root.fill(&WHITE)?;
+        let root = root.margin(10, 10, 10, 10);
+        let mut chart = ChartBuilder::on(&root)
+            .x_label_area_size(40)
+            .y_label_area_size(50)
+            .build_cartesian_2d(0.0_f32..1.0_f32, 0.0_f32..1.1_f32)?;
+
+        chart
+            .configure_mesh()
+            .x_desc("Roughness")
+            .y_desc("Directional albedo")
+            .x_labels(5)
+            .y_labels(6)
+            .y_label_formatter(&|y| format!("{y:.1}"))
+            .draw()?;
+
+        for ((angle, color), (single, compensated)) in angles.iter().zip(colors).zip(curves) {
+            chart
+                .draw_series(LineSeries::new(single, color))?
+                .label(format!("θo={angle}°, single"))
+                .legend(move |(x, y)| PathElement::new([(x, y), (x + 20, y)], color));
+            chart
+                .draw_series(LineSeries::new(compensated, color.stroke_width(3)))?
+                .label(format!("θo={angle}°, compensated"))
+                .legend(move |(x, y)| {
+                    PathElement::new([(x, y), (x + 20, y)], color.stroke_width(3))
+                });
+        }
+
+        chart
+            .configure_series_labels()
+            .position(SeriesLabelPosition::LowerLeft)
+            .border_style(BLACK)
+            .background_style(WHITE.mix(0.8))
+            .draw()?;
+    }
+    let file_name = "ggx-furnace.svg";
+    let path = work_dir.join(file_name);
+    std::fs::write(path, output_svg)?;
+
+    // Website.
+    {
+        use std::io::Write;
+        let mut page = vz::page::Builder::new("ggx");
+        page.push_card("ggx", "furnace", file_name);
+        let file_name = "ggx.html";
+        let path = work_dir.join(file_name);
+        let file = File::create(&path)?;
+        let mut writer = BufWriter::new(file);
+        write!(&mut writer, "{}", page.build()?)?;
+        info!("Wrote to {}", path.display());
+        info!("Open with http://127.0.0.1:5500/work/{file_name}");
+    }
+
+    Ok(())
+}
+
+//
+// Denoiser
+//
+
+const DENOISER_IMAGE_SIZE: (u32, u32) = (160, 100);
+const DENOISER_SAMPLE_COUNTS: [u32; 3] = [4, 16, 64];
+const DENOISER_REFERENCE_SAMPLE_COUNT: u32 = 1024;
+
+// Compares noisy and denoised renders of the default scene against a high
+// sample count reference. The relative MSE is measured on the linear radiance,
+// the PSNR on the exposed and tonemapped image.
+fn denoiser_comparison() -> Result<()> {
+    let work_dir = work_dir();
+    let font = vz::font::Font::new()?;
+    let exposure = cpupt::Exposure::default();
+    let denoiser = cpupt::DenoiserParams::default();
+
+    // Scene.
+    let glb_path = manifest_dir().join("src/assets/rounded_cube.glb");
+    let (rds_scene, dyn_scene) = rds::Scene::create(
+        &std::fs::read(&glb_path)
+            .with_context(|| format!("Reading glb scene: {}", glb_path.display()))?,
+    )?;
+    let render = |samples_per_pixel: u32| {
+        let raytracer = cpupt::Raytracer::create(
+            cpupt::Params {
+                samples_per_pixel,
+                ..cpupt::Params::default()
+            },
+            rds_scene.clone(),
+        );
+        raytracer.send_input(cpupt::Input {
+            image_size: DENOISER_IMAGE_SIZE,
+            dyn_scene: dyn_scene.clone(),
+            exposure,
+            ..cpupt::Input::default()
+        })?;
+        let output = loop {
+            let output = raytracer
+                .recv_output()
+                .context("Raytracer sent no output")?;
+            if output.sampling_status.is_done() {
+                break output;
+            }
+        };
+        raytracer.terminate()?;
+        Ok::<_, anyhow::Error>(output)
+    };
+    let display = |radiance: &[ColorRgb]| {
+        radiance
+            .iter()
+            .map(|&sample| exposure.expose(sample).tonemap(Tonemapper::default()))
+            .collect::<Vec<_>>()
+    };
+    let rel_mse = |radiance: &[ColorRgb], reference: &[ColorRgb]| {
+        let sum = radiance
+            .iter()
+            .zip(reference)
+            .map(|(x, r)| {
+                let d = *x - *r;
+                let relative = |d: f32, r: f32| d * d / (r * r + 0.01);
+                relative(d.r(), r.r()) + relative(d.g(), r.g()) + relative(d.b(), r.b())
+            })
+            .sum::<f32>();
+        sum / (3 * radiance.len()) as f32
+    };
+    let psnr = |image: &[ColorRgb], reference: &[ColorRgb]| {
+        let sum = image
+            .iter()
+            .zip(reference)
+            .map(|(x, r)| {
+                let d = (*x - *r).clamp();
+                (d * d).mean()
+            })
+            .sum::<f32>();
+        let mse = sum / image.len() as f32;
+        -10.0 * mse.max(1e-10).log10()
+    };
+
+    // Reference.
+    let reference = render(DENOISER_REFERENCE_SAMPLE_COUNT)?;
+    let reference_image = display(&reference.radiance);
+
+    // Comparisons.
+    let mut page = vz::page::Builder::new("denoiser");
+    for samples_per_pixel in DENOISER_SAMPLE_COUNTS {
+        let noisy = render(samples_per_pixel)?;
+        let denoised = cpupt::denoise(&denoiser, &noisy.radiance, &noisy.aovs, noisy.image_size);
+        let noisy_image = display(&noisy.radiance);
+        let denoised_image = display(&denoised);
+
+        // Annotate.
+        let text_box = vz::annotation::TextBox::new()
+            .line([("images", "noisy | denoised | reference")])
+            .line([
+                ("spp", format!("{samples_per_pixel}")),
+                (
+                    "reference spp",
+                    format!("{DENOISER_REFERENCE_SAMPLE_COUNT}"),
+                ),
+            ])
+            .line([
+                (
+                    "relmse",
+                    format!(
+                        "{:.04} | {:.04}",
+                        rel_mse(&noisy.radiance, &reference.radiance),
+                        rel_mse(&denoised, &reference.radiance)
+                    ),
+                ),
+                (
+                    "psnr",
+                    format!(
+                        "{:.02} | {:.02} dB",
+                        psnr(&noisy_image, &reference_image),
+                        psnr(&denoised_image, &reference_image)
+                    ),
+                ),
+            ])
+            .build();
+        let mut image = vz::image::Rgb::hstack(&[
+            vz::image::Rgb::from_colors(&noisy_image, DENOISER_IMAGE_SIZE),
+            vz::image::Rgb::from_colors(&denoised_image, DENOISER_IMAGE_SIZE),
+            vz::image::Rgb::from_colors(&reference_image, DENOISER_IMAGE_SIZE),
+        ]);
+        image.draw_text(&font, PLOT_COLOR_TEXT, &text_box);
+
+        let file_name = format!("denoiser-{samples_per_pixel}spp.png");
+        image.save(&work_dir.join(&file_name))?;
+        page.push_card("denoiser", format!("{samples_per_pixel} spp"), file_name);
+    }
+
+    // Website.
+    {
+        use std::io::Write;
+        let file_name = "denoiser.html";
+        let path = work_dir.join(file_name);
+        let file = File::create(&path)?;
+        let mut writer = BufWriter::new(file);
+        write!(&mut writer, "{}", page.build()?)?;
+        info!("Wrote to {}", path.display());
+        info!("Open with http://127.0.0.1:5500/work/{file_name}");
+    }
+
+    Ok(())
+}
+
+//
+// Runner
+//
+
+#[derive(clap::Args)]
+pub struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(clap::Subcommand)]
+enum Command {
+    // Regenerate the embedded GGX directional albedo tables.
+    GgxAlbedo,
+    // Regenerate the embedded Charlie sheen directional albedo table.
+    SheenAlbedo,
+    // Compare the denoiser against a reference render.
+    Denoiser,
+}
+
+pub fn run(args: Args) -> Result<()> {
+    match args.command {
+        Some(Command::GgxAlbedo) => ggx_albedo_tables(),
+        Some(Command::SheenAlbedo) => sheen_albedo_table(),
+        Some(Command::Denoiser) => denoiser_comparison(),
+        None => {
+            brdf_visualizations()?;
+            sky_model_visualizations()?;
+            ggx_furnace_test()?;
+            denoiser_comparison()?;
+            Ok(())
+        }
+    }
+}
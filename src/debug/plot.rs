@@ -88,6 +88,31 @@ impl Plot {
         }
     }
 
+    // Fills all pixels within `angular_radius` of `center`. The center pixel is
+    // always drawn, so discs smaller than a pixel remain visible.
+    pub fn draw_disc(&mut self, center: bxdfs::LocalVector, angular_radius: f32, color: ColorRgb) {
+        let cos_max = angular_radius.cos();
+        for pixel_y in 0..HEMISPHERE_PLOT_HEIGHT {
+            for pixel_x in 0..HEMISPHERE_PLOT_WIDTH {
+                if let Some(vector) = hemisphere::vector_from_pixel(pixel_x, pixel_y) {
+                    if vector.0.dot(&center.0) >= cos_max {
+                        self.image
+                            .put_pixel(pixel_x, ANGLE_PLOT_HEIGHT + pixel_y, color);
+                    }
+                }
+            }
+        }
+        for pixel_y in 0..ANGLE_PLOT_HEIGHT {
+            for pixel_x in 0..ANGLE_PLOT_WIDTH {
+                let vector = angle::vector_from_pixel(pixel_x, pixel_y);
+                if vector.0.dot(&center.0) >= cos_max {
+                    self.image.put_pixel(pixel_x, pixel_y, color);
+                }
+            }
+        }
+        self.draw_vector(center, color);
+    }
+
     pub fn draw_debug_vectors(&mut self) {
         let mid_xy = X_AXIS + Y_AXIS;
         let mid_zy = Z_AXIS + Y_AXIS;
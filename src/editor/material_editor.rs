@@ -2,12 +2,14 @@ use super::*;
 
 pub struct MaterialEditorState {
     selected_material: usize,
+    image_path: String,
 }
 
 impl MaterialEditorState {
     pub fn new() -> Self {
         Self {
             selected_material: 0,
+            image_path: String::new(),
         }
     }
 }
@@ -50,9 +52,67 @@ impl GuiElement for MaterialEditor<'_> {
                 if ui.selectable(rds::MaterialModel::Diffuse.name()) {
                     *model = rds::MaterialModel::Diffuse;
                 }
+                if ui.selectable(rds::MaterialModel::OrenNayar.name()) {
+                    *model = rds::MaterialModel::OrenNayar;
+                }
                 if ui.selectable(rds::MaterialModel::Disney.name()) {
                     *model = rds::MaterialModel::Disney;
                 }
+                if ui.selectable(rds::MaterialModel::Subsurface.name()) {
+                    *model = rds::MaterialModel::Subsurface;
+                }
+                let conductor =
+                    rds::MaterialModel::Conductor(cpupt::bxdfs::ConductorPreset::default());
+                if ui.selectable(conductor.name())
+                    && !matches!(model, rds::MaterialModel::Conductor(_))
+                {
+                    *model = conductor;
+                }
+                if ui.selectable(rds::MaterialModel::Blend.name()) {
+                    *model = rds::MaterialModel::Blend;
+                }
+            }
+            if let rds::MaterialModel::Conductor(preset) = model {
+                if let Some(_token) = ui.begin_combo("Metal", preset.name()) {
+                    for candidate in cpupt::bxdfs::ConductorPreset::ALL {
+                        if ui.selectable(candidate.name()) {
+                            *preset = candidate;
+                        }
+                    }
+                }
+            }
+            if *model == rds::MaterialModel::Blend {
+                let blend = &mut material.blend;
+                for (slot, label) in ["Material A", "Material B"].into_iter().enumerate() {
+                    let mut index = blend.materials[slot] as usize;
+                    if ui.combo(label, &mut index, &self.rds_scene.materials, |material| {
+                        Cow::Borrowed(&material.name)
+                    }) {
+                        blend.materials[slot] = index as u32;
+                    }
+                }
+                if let Some(_token) = ui.begin_combo("Mask", blend.mask.name()) {
+                    for candidate in [rds::BlendMask::Texture, rds::BlendMask::VertexColor] {
+                        if ui.selectable(candidate.name()) {
+                            blend.mask = candidate;
+                        }
+                    }
+                }
+            }
+        }
+
+        // Images, which can replace color textures.
+        {
+            let _id = ui.push_id("image");
+            imgui::InputText::new(ui, "Image path", &mut self.state.image_path).build();
+            if ui.button("Load image") {
+                match rds::DynamicImage::load(Path::new(&self.state.image_path)) {
+                    Ok(image) => {
+                        info!("Loaded image {}", image.name);
+                        self.dyn_scene.images.push(Arc::new(image));
+                    }
+                    Err(err) => warn!("Failed to load image: {err:#}"),
+                }
             }
         }
 
@@ -66,6 +126,14 @@ impl GuiElement for MaterialEditor<'_> {
             let specular_tint = material.specular_tint;
             let sheen = material.sheen;
             let sheen_tint = material.sheen_tint;
+            let sheen_color = material.sheen_color;
+            let sheen_roughness = material.sheen_roughness;
+            let iridescence = material.iridescence;
+            let iridescence_ior = material.iridescence_ior;
+            let iridescence_thickness = material.iridescence_thickness;
+            let subsurface_color = material.subsurface_color;
+            let subsurface_radius = material.subsurface_radius;
+            let blend_mask = material.blend_mask;
             ui.table_next_row();
             ui.table_set_column_index(0);
             base_color_gui(ui, "Base color", self.dyn_scene, base_color);
@@ -75,6 +143,32 @@ impl GuiElement for MaterialEditor<'_> {
             scalar_gui(ui, "Specular tint", self.dyn_scene, specular_tint);
             scalar_gui(ui, "Sheen", self.dyn_scene, sheen);
             scalar_gui(ui, "Sheen tint", self.dyn_scene, sheen_tint);
+            base_color_gui(ui, "Sheen color", self.dyn_scene, sheen_color);
+            scalar_gui(ui, "Sheen roughness", self.dyn_scene, sheen_roughness);
+            scalar_gui(ui, "Iridescence", self.dyn_scene, iridescence);
+            ranged_scalar_gui(
+                ui,
+                "Iridescence IOR",
+                self.dyn_scene,
+                iridescence_ior,
+                (1.0, 3.0),
+            );
+            ranged_scalar_gui(
+                ui,
+                "Iridescence thickness",
+                self.dyn_scene,
+                iridescence_thickness,
+                (0.0, 1200.0),
+            );
+            base_color_gui(ui, "Subsurface color", self.dyn_scene, subsurface_color);
+            ranged_scalar_gui(
+                ui,
+                "Subsurface radius",
+                self.dyn_scene,
+                subsurface_radius,
+                (0.001, 10.0),
+            );
+            scalar_gui(ui, "Blend mask", self.dyn_scene, blend_mask);
         }
     }
 }
@@ -93,6 +187,30 @@ fn base_color_gui(
     ui.text(name);
     ui.table_next_column();
 
+    // Source, either a constant or one of the loaded images.
+    let source = match &texture {
+        rds::DynamicTexture::Image(image) => dyn_scene.images[image.image as usize].name.as_str(),
+        _ => "constant",
+    };
+    if let Some(_token) = ui.begin_combo("##source", source) {
+        if ui.selectable("constant") {
+            if let rds::DynamicTexture::Image(image) = &texture {
+                *texture = rds::DynamicTexture::Vector4(image.tint);
+                dyn_scene.replaced_textures.set(index, true);
+            }
+        }
+        for (image_index, image) in dyn_scene.images.iter().enumerate() {
+            if ui.selectable(&image.name) {
+                *texture = rds::DynamicTexture::Image(rds::ImageTexture {
+                    image: image_index as u32,
+                    tint: [1.0, 1.0, 1.0, 1.0],
+                    uv_scale: [1.0, 1.0],
+                });
+                dyn_scene.replaced_textures.set(index, true);
+            }
+        }
+    }
+
     if let rds::DynamicTexture::Vector4(ref mut value) = &mut texture {
         if ui
             .color_edit4_config("Value", value)
@@ -103,6 +221,21 @@ fn base_color_gui(
             // Convenience: replace texture when an edit has been made without extra interaction.
             dyn_scene.replaced_textures.set(index, true);
         }
+    } else if let rds::DynamicTexture::Image(ref mut image) = &mut texture {
+        let tint_changed = ui
+            .color_edit4_config("Tint", &mut image.tint)
+            .alpha(false)
+            .inputs(false)
+            .build();
+        let uv_scale_changed = imgui::Drag::new("UV scale")
+            .range(0.01, 100.0)
+            .speed(0.01)
+            .build_array(ui, &mut image.uv_scale);
+        if tint_changed || uv_scale_changed {
+            dyn_scene.replaced_textures.set(index, true);
+        }
+    } else if let rds::DynamicTexture::Procedural(procedural) = &texture {
+        ui.text(procedural.pattern.name());
     }
     ui.table_next_column();
 
@@ -121,6 +254,16 @@ fn base_color_gui(
 }
 
 fn scalar_gui(ui: &imgui::Ui, name: &str, dyn_scene: &mut rds::DynamicScene, texture_index: u32) {
+    ranged_scalar_gui(ui, name, dyn_scene, texture_index, (0.0, 1.0));
+}
+
+fn ranged_scalar_gui(
+    ui: &imgui::Ui,
+    name: &str,
+    dyn_scene: &mut rds::DynamicScene,
+    texture_index: u32,
+    (min, max): (f32, f32),
+) {
     let _id = ui.push_id(name);
     let index = texture_index as usize;
     let mut texture = &mut dyn_scene.textures[index];
@@ -129,15 +272,20 @@ fn scalar_gui(ui: &imgui::Ui, name: &str, dyn_scene: &mut rds::DynamicScene, tex
     ui.text(name);
     ui.table_next_column();
 
-    if let rds::DynamicTexture::Scalar(ref mut value) = &mut texture {
-        if imgui::Drag::new("##slider")
-            .range(0.0, 1.0)
-            .speed(0.01)
-            .build(ui, value)
-        {
-            // Convenience: replace texture when an edit has been made without extra interaction.
-            dyn_scene.replaced_textures.set(index, true);
-        }
+    let drag = imgui::Drag::new("##slider")
+        .range(min, max)
+        .speed(0.01 * (max - min));
+    let changed = match &mut texture {
+        rds::DynamicTexture::Scalar(ref mut value) => drag.build(ui, value),
+        rds::DynamicTexture::Vector3(ref mut value) => drag.build_array(ui, value),
+        _ => false,
+    };
+    if changed {
+        // Convenience: replace texture when an edit has been made without extra interaction.
+        dyn_scene.replaced_textures.set(index, true);
+    }
+    if let rds::DynamicTexture::Procedural(procedural) = &texture {
+        ui.text(procedural.pattern.name());
     }
     ui.table_next_column();
 
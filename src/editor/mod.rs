@@ -34,6 +34,16 @@ pub(crate) use window::{Window, WindowSize};
 pub struct Args {
     #[arg(long)]
     pub glb_scene: PathBuf,
+
+    #[arg(long)]
+    pub environment_map: Option<PathBuf>,
+
+    #[arg(long)]
+    pub spectral: bool,
+
+    // Enables adaptive sampling with the given relative error threshold.
+    #[arg(long)]
+    pub adaptive_threshold: Option<f32>,
 }
 
 pub fn run(args: Args) -> Result<()> {
@@ -55,7 +65,13 @@ pub fn run(args: Args) -> Result<()> {
     })?;
 
     // Init editor.
-    let mut editor = Editor::create(window, &args.glb_scene)?;
+    let mut editor = Editor::create(
+        window,
+        &args.glb_scene,
+        args.environment_map.as_deref(),
+        args.spectral,
+        args.adaptive_threshold,
+    )?;
 
     // Event loop.
     event_loop.run_return(|event, _, control_flow| {
@@ -90,18 +106,41 @@ struct Editor {
     latest_output: Option<cpupt::Output>,
     sampling_status: cpupt::SamplingStatus,
     image_name: String,
+    // Saves the linear radiance when given, instead of a PNG.
+    image_format: Option<vz::hdr::HdrFormat>,
 
     display_raytracing_image: bool,
+    // Which output is shown in place of the raytracing image, and which one
+    // was last uploaded to the renderer.
+    aov: cpupt::AovKind,
+    displayed_aov: Option<cpupt::AovKind>,
     hemisphere_sampler: cpupt::HemisphereSampler,
     material_editor_state: MaterialEditorState,
     visualize_normals: bool,
-    tonemapping: bool,
+    tonemapper: Tonemapper,
     exposure: cpupt::Exposure,
+    white_balance: cpupt::WhiteBalance,
     sky_params: cpupt::SkyParams,
+    solar_position_enabled: bool,
+    solar_position: cpupt::SolarPosition,
+    environment_enabled: bool,
+    environment_path: String,
+    environment: Option<cpupt::EnvironmentParams>,
+    fog_enabled: bool,
+    fog_params: cpupt::FogParams,
+    denoiser_enabled: bool,
+    denoiser_params: cpupt::DenoiserParams,
+    post_params: cpupt::PostParams,
 }
 
 impl Editor {
-    fn create(window: Window, glb_scene: &Path) -> Result<Self> {
+    fn create(
+        window: Window,
+        glb_scene: &Path,
+        environment_map: Option<&Path>,
+        spectral: bool,
+        adaptive_threshold: Option<f32>,
+    ) -> Result<Self> {
         let (rds_scene, dyn_scene) = rds::Scene::create(
             &std::fs::read(glb_scene)
                 .with_context(|| format!("Reading glb scene: {}", glb_scene.display()))?,
@@ -109,10 +148,15 @@ impl Editor {
         let raytracer = cpupt::Raytracer::create(
             cpupt::Params {
                 samples_per_pixel: 256,
+                spectral,
+                adaptive_threshold,
                 ..cpupt::Params::default()
             },
             rds_scene.clone(),
         );
+        let environment = environment_map
+            .map(|path| cpupt::EnvironmentMap::load(path).map(cpupt::EnvironmentParams::new))
+            .transpose()?;
         let mut gui = Gui::create(&window);
         let renderer = unsafe {
             vulkan::Renderer::create(
@@ -139,14 +183,30 @@ impl Editor {
             latest_output: None,
             sampling_status: cpupt::SamplingStatus::new(),
             image_name: String::from("image"),
+            image_format: None,
 
             display_raytracing_image: true,
+            aov: cpupt::AovKind::Radiance,
+            displayed_aov: None,
             hemisphere_sampler: cpupt::HemisphereSampler::default(),
             material_editor_state: MaterialEditorState::new(),
             visualize_normals: false,
-            tonemapping: true,
+            tonemapper: Tonemapper::default(),
             exposure: cpupt::Exposure::default(),
+            white_balance: cpupt::WhiteBalance::default(),
             sky_params: cpupt::SkyParams::default(),
+            solar_position_enabled: false,
+            solar_position: cpupt::SolarPosition::default(),
+            environment_enabled: environment.is_some(),
+            environment_path: environment_map
+                .map(|path| path.display().to_string())
+                .unwrap_or_default(),
+            environment,
+            fog_enabled: false,
+            fog_params: cpupt::FogParams::default(),
+            denoiser_enabled: false,
+            denoiser_params: cpupt::DenoiserParams::default(),
+            post_params: cpupt::PostParams::default(),
         })
     }
 
@@ -199,16 +259,36 @@ impl Editor {
         // Update camera.
         self.camera.update(&self.input_state, &self.frame_state);
 
+        // Update sun.
+        if self.solar_position_enabled {
+            match self.solar_position.sky_angles() {
+                Ok((elevation, azimuth)) => {
+                    self.sky_params.elevation = elevation;
+                    self.sky_params.azimuth = azimuth;
+                }
+                Err(err) => warn!("Invalid solar position: {err}"),
+            }
+        }
+
         // Update raytracer.
         self.raytracer.send_input(cpupt::Input {
             camera_transform: self.camera.transform(),
             image_size: self.window.size().into(),
             hemisphere_sampler: self.hemisphere_sampler,
             dyn_scene: self.dyn_scene.clone(),
-            visualize_normals: self.visualize_normals,
-            tonemapping: self.tonemapping,
+            tonemapper: self.tonemapper,
             exposure: self.exposure,
-            sky_params: self.sky_params,
+            white_balance: self.white_balance,
+            denoiser: self.denoiser_enabled.then_some(self.denoiser_params),
+            post: self.post_params,
+            sky: match &self.environment {
+                Some(environment) if self.environment_enabled => {
+                    cpupt::SkySource::Environment(environment.clone())
+                }
+                _ => cpupt::SkySource::Analytic(self.sky_params),
+            },
+            fog: self.fog_enabled.then_some(self.fog_params),
+            mesh_media: vec![],
             salt: None,
         })?;
 
@@ -258,23 +338,90 @@ impl Editor {
 
                 // Sky model.
                 self.sky_params.gui(ui);
+                ui.checkbox("Solar position", &mut self.solar_position_enabled);
+                if self.solar_position_enabled {
+                    let _id = ui.push_id("solar_position");
+                    self.solar_position.gui(ui);
+                }
+
+                ui.separator();
+
+                // Environment map.
+                ui.checkbox("Environment map", &mut self.environment_enabled);
+                if self.environment_enabled {
+                    let _id = ui.push_id("environment");
+                    imgui::InputText::new(ui, "Path", &mut self.environment_path).build();
+                    if ui.button("Load") {
+                        match cpupt::EnvironmentMap::load(Path::new(&self.environment_path)) {
+                            Ok(map) => {
+                                info!("Loaded environment map from {}", map.path().display());
+                                self.environment = Some(cpupt::EnvironmentParams::new(map));
+                            }
+                            Err(err) => warn!("Failed to load environment map: {err:#}"),
+                        }
+                    }
+                    if let Some(environment) = &mut self.environment {
+                        environment.gui(ui);
+                    }
+                }
+
+                ui.separator();
+
+                // Fog.
+                ui.checkbox("Fog", &mut self.fog_enabled);
+                if self.fog_enabled {
+                    let _id = ui.push_id("fog");
+                    self.fog_params.gui(ui);
+                }
 
                 ui.separator();
 
                 // Rendering config.
                 self.exposure.gui(ui);
+                self.white_balance.gui(ui);
                 ui.checkbox("Visualize normals", &mut self.visualize_normals);
-                ui.checkbox("Tonemapping", &mut self.tonemapping);
+                if let Some(_token) = ui.begin_combo("Tonemapper", self.tonemapper.name()) {
+                    for tonemapper in Tonemapper::ALL {
+                        if ui.selectable(tonemapper.name()) {
+                            self.tonemapper = tonemapper;
+                        }
+                    }
+                }
+                ui.checkbox("Denoiser", &mut self.denoiser_enabled);
+                if self.denoiser_enabled {
+                    let _id = ui.push_id("denoiser");
+                    self.denoiser_params.gui(ui);
+                }
+                self.post_params.gui(ui);
                 self.hemisphere_sampler.gui(ui);
 
                 ui.separator();
 
                 // Image utilities.
                 ui.checkbox("Show raytracing image", &mut self.display_raytracing_image);
+                if let Some(_token) = ui.begin_combo("AOV", self.aov.name()) {
+                    for aov in cpupt::AovKind::ALL {
+                        if ui.selectable(aov.name()) {
+                            self.aov = aov;
+                        }
+                    }
+                }
                 imgui::InputText::new(ui, "Image name", &mut self.image_name).build();
+                let format_name = self.image_format.map_or("png", vz::hdr::HdrFormat::name);
+                if let Some(_token) = ui.begin_combo("Image format", format_name) {
+                    if ui.selectable("png") {
+                        self.image_format = None;
+                    }
+                    for format in vz::hdr::HdrFormat::ALL {
+                        if ui.selectable(format.name()) {
+                            self.image_format = Some(format);
+                        }
+                    }
+                }
                 if ui.button("Save image") {
                     if let Some(output) = &self.latest_output {
-                        save_image_to_file(&self.image_name, output).expect("Saving image to file");
+                        save_image_to_file(&self.image_name, self.image_format, self.aov, output)
+                            .expect("Saving image to file");
                     }
                 }
             });
@@ -288,12 +435,21 @@ impl Editor {
         }
 
         unsafe {
-            // If we got a new output, submit it to the rasterizer.
+            // If we got a new output, or another AOV was selected, submit
+            // it to the rasterizer.
             if let Some(output) = output {
-                self.renderer
-                    .update_raytracing_image(&output.image, output.image_size)?;
                 self.sampling_status = output.sampling_status;
                 self.latest_output = Some(output);
+                self.displayed_aov = None;
+            }
+            if self.displayed_aov != Some(self.aov) {
+                if let Some(output) = &self.latest_output {
+                    let aov_image = output.aovs.visualize(self.aov);
+                    let image = aov_image.as_deref().unwrap_or(&output.image);
+                    self.renderer
+                        .update_raytracing_image(image, output.image_size)?;
+                    self.displayed_aov = Some(self.aov);
+                }
             }
 
             // Update gui.
@@ -320,17 +476,35 @@ impl Editor {
     }
 }
 
-fn save_image_to_file(image_name: &str, output: &cpupt::Output) -> Result<()> {
+fn save_image_to_file(
+    image_name: &str,
+    image_format: Option<vz::hdr::HdrFormat>,
+    aov: cpupt::AovKind,
+    output: &cpupt::Output,
+) -> Result<()> {
+    let extension = image_format.map_or("png", vz::hdr::HdrFormat::extension);
     let path = if image_name.is_empty() {
         let timestamp = utc_timestamp()?;
-        PathBuf::from(format!("{timestamp}.png"))
+        PathBuf::from(format!("{timestamp}.{extension}"))
     } else {
-        PathBuf::from(format!("{image_name}.png"))
+        PathBuf::from(format!("{image_name}.{extension}"))
     };
-    let image = vz::image::Rgb::from_colors(&output.image, output.image_size);
-    image
-        .save(&path)
-        .with_context(|| format!("Failed to save image to {}", path.display()))?;
+    if let Some(format) = image_format {
+        vz::hdr::write_to_file(
+            &path,
+            format,
+            &output.radiance,
+            output.image_size,
+            output.working_space,
+        )?;
+    } else {
+        let aov_image = output.aovs.visualize(aov);
+        let colors = aov_image.as_deref().unwrap_or(&output.image);
+        let image = vz::image::Rgb::from_colors(colors, output.image_size);
+        image
+            .save(&path)
+            .with_context(|| format!("Failed to save image to {}", path.display()))?;
+    }
     info!(
         "Wrote {}x{} image to {}",
         output.image_size.0,
@@ -98,6 +98,18 @@ pub enum AccessorType {
     Scalar,
     Vec2,
     Vec3,
+    Vec4,
+}
+
+impl AccessorType {
+    pub fn component_count(&self) -> usize {
+        match self {
+            Self::Scalar => 1,
+            Self::Vec2 => 2,
+            Self::Vec3 => 3,
+            Self::Vec4 => 4,
+        }
+    }
 }
 
 #[derive(Deserialize, Debug)]
@@ -106,14 +118,73 @@ pub enum AccessorType {
 #[allow(dead_code)]
 pub struct Accessor {
     pub buffer_view: usize,
+    #[serde(default)]
+    pub byte_offset: usize,
     pub component_type: u32,
     pub count: usize,
     #[serde(rename = "type")]
     pub ty: AccessorType,
+    #[serde(default)]
+    pub normalized: bool,
     pub min: Option<[f32; 3]>,
     pub max: Option<[f32; 3]>,
 }
 
+impl Accessor {
+    pub const UNSIGNED_BYTE: u32 = 5121;
+    pub const UNSIGNED_SHORT: u32 = 5123;
+    pub const UNSIGNED_INT: u32 = 5125;
+    pub const FLOAT: u32 = 5126;
+
+    // Bytes of each element in the binary chunk. Elements follow each other,
+    // unless the buffer view interleaves them with other attributes.
+    pub fn elements<'a>(&self, gltf: &Gltf, data: &'a [u8]) -> Result<Vec<&'a [u8]>> {
+        let component_size = match self.component_type {
+            Self::UNSIGNED_BYTE => 1,
+            Self::UNSIGNED_SHORT => 2,
+            Self::UNSIGNED_INT | Self::FLOAT => 4,
+            ty => bail!("Unsupported accessor component type {ty}"),
+        };
+        let element_size = component_size * self.ty.component_count();
+        let view = gltf
+            .buffer_views
+            .get(self.buffer_view)
+            .context("Accessor buffer view is out of bounds")?;
+        let stride = view.byte_stride.unwrap_or(element_size);
+        ensure!(
+            stride >= element_size,
+            "Buffer view stride {stride} is smaller than the element size {element_size}"
+        );
+        let view_data = data
+            .get(view.byte_offset..view.byte_offset + view.byte_length)
+            .context("Buffer view is out of bounds of the binary chunk")?;
+        (0..self.count)
+            .map(|index| {
+                let start = self.byte_offset + index * stride;
+                view_data
+                    .get(start..start + element_size)
+                    .context("Accessor is out of bounds of its buffer view")
+            })
+            .collect()
+    }
+
+    // Elements as plain values, for accessors which match the layout of `T`.
+    pub fn read<T: Pod>(&self, gltf: &Gltf, data: &[u8]) -> Result<Vec<T>> {
+        self.elements(gltf, data)?
+            .into_iter()
+            .map(|element| {
+                ensure!(
+                    element.len() == size_of::<T>(),
+                    "Accessor elements are {} bytes, expected {}",
+                    element.len(),
+                    size_of::<T>()
+                );
+                Ok(bytemuck::pod_read_unaligned(element))
+            })
+            .collect()
+    }
+}
+
 #[derive(Deserialize, Debug)]
 #[serde(deny_unknown_fields)]
 #[allow(dead_code)]
@@ -131,6 +202,7 @@ pub struct BufferView {
     pub buffer: usize,
     pub byte_length: usize,
     pub byte_offset: usize,
+    pub byte_stride: Option<usize>,
     pub target: Option<u32>,
 }
 
@@ -199,7 +271,94 @@ pub struct PbrMetallicRoughness {
 
 #[derive(Deserialize, Debug)]
 #[allow(dead_code)]
-pub struct MaterialExtensions {}
+pub struct MaterialExtensions {
+    #[serde(rename = "KHR_materials_volume")]
+    pub volume: Option<MaterialVolume>,
+    #[serde(rename = "KHR_materials_diffuse_transmission")]
+    pub diffuse_transmission: Option<MaterialDiffuseTransmission>,
+    #[serde(rename = "KHR_materials_sheen")]
+    pub sheen: Option<MaterialSheen>,
+    #[serde(rename = "KHR_materials_iridescence")]
+    pub iridescence: Option<MaterialIridescence>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+#[allow(dead_code)]
+pub struct MaterialVolume {
+    #[serde(default)]
+    pub thickness_factor: f32,
+    #[serde(default = "default_attenuation_distance")]
+    pub attenuation_distance: f32,
+    #[serde(default = "default_color_factor")]
+    pub attenuation_color: [f32; 3],
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+#[allow(dead_code)]
+pub struct MaterialDiffuseTransmission {
+    #[serde(default)]
+    pub diffuse_transmission_factor: f32,
+    #[serde(default = "default_color_factor")]
+    pub diffuse_transmission_color_factor: [f32; 3],
+}
+
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)]
+pub struct MaterialSheen {
+    #[serde(rename = "sheenColorFactor", default)]
+    pub color_factor: [f32; 3],
+    #[serde(rename = "sheenColorTexture")]
+    pub color_texture: Option<Id>,
+    #[serde(rename = "sheenRoughnessFactor", default)]
+    pub roughness_factor: f32,
+    #[serde(rename = "sheenRoughnessTexture")]
+    pub roughness_texture: Option<Id>,
+}
+
+#[derive(Deserialize, Debug)]
+#[allow(dead_code)]
+pub struct MaterialIridescence {
+    #[serde(rename = "iridescenceFactor", default)]
+    pub factor: f32,
+    #[serde(rename = "iridescenceTexture")]
+    pub texture: Option<Id>,
+    #[serde(rename = "iridescenceIor", default = "default_iridescence_ior")]
+    pub ior: f32,
+    #[serde(
+        rename = "iridescenceThicknessMinimum",
+        default = "default_iridescence_thickness_minimum"
+    )]
+    pub thickness_minimum: f32,
+    #[serde(
+        rename = "iridescenceThicknessMaximum",
+        default = "default_iridescence_thickness_maximum"
+    )]
+    pub thickness_maximum: f32,
+    #[serde(rename = "iridescenceThicknessTexture")]
+    pub thickness_texture: Option<Id>,
+}
+
+fn default_iridescence_ior() -> f32 {
+    1.3
+}
+
+fn default_iridescence_thickness_minimum() -> f32 {
+    100.0
+}
+
+fn default_iridescence_thickness_maximum() -> f32 {
+    400.0
+}
+
+fn default_attenuation_distance() -> f32 {
+    f32::INFINITY
+}
+
+fn default_color_factor() -> [f32; 3] {
+    [1.0, 1.0, 1.0]
+}
 
 #[derive(Deserialize, Debug)]
 pub struct BaseColorFactor(pub [f32; 4]);
@@ -254,6 +413,7 @@ pub struct Attributes {
     pub position: usize,
     pub texcoord_0: usize,
     pub normal: usize,
+    pub color_0: Option<usize>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -32,7 +32,7 @@ use std::{
     ops::Deref,
     path::{Path, PathBuf},
     slice,
-    sync::mpsc,
+    sync::{mpsc, Arc},
     thread,
     time::{Duration, Instant},
 };
@@ -87,7 +87,7 @@ struct CliArgs {
 enum Commands {
     Editor(editor::Args),
     Offline(offline::Args),
-    Debug,
+    Debug(debug::Args),
     BlogNew(blog::NewArgs),
     BlogBuild,
     BlogPlot,
@@ -104,7 +104,7 @@ fn main() -> Result<()> {
     match CliArgs::parse().command {
         Commands::Editor(args) => editor::run(args),
         Commands::Offline(args) => offline::run(args),
-        Commands::Debug => debug::run(),
+        Commands::Debug(args) => debug::run(args),
         Commands::BlogNew(args) => blog::new(args),
         Commands::BlogBuild => blog::build(),
         Commands::BlogPlot => blog::plot(),
@@ -22,6 +22,69 @@ pub struct Args {
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(deny_unknown_fields)]
 struct RenderConfig {
+    samples_per_pixel: NonZeroU32,
+    image_scale: NonZeroU32,
+    frame_delay_num: NonZeroU16,
+    frame_delay_den: NonZeroU16,
+    #[serde(default)]
+    tonemapper: Tonemapper,
+    #[serde(default)]
+    exposure: cpupt::ExposureMode,
+    annotations: bool,
+    #[serde(default)]
+    spectral: bool,
+    // Adaptive sampling, see `cpupt::Params`.
+    #[serde(default)]
+    adaptive_threshold: Option<f32>,
+    #[serde(default)]
+    min_samples_per_pixel: Option<NonZeroU32>,
+    #[serde(default)]
+    filter: cpupt::FilterParams,
+    // Color space of the radiance, see `cpupt::Params`.
+    #[serde(default)]
+    working_space: ColorSpace,
+    // Denoises each frame when given, before exposure and tonemapping.
+    #[serde(default)]
+    denoiser: Option<cpupt::DenoiserParams>,
+    // Lens effects, applied after denoising.
+    #[serde(default)]
+    post: cpupt::PostParams,
+    // Also writes the linear radiance of each frame when given.
+    #[serde(default)]
+    hdr_output: Option<vz::hdr::HdrFormat>,
+    // Also writes the AOVs of each frame when given.
+    #[serde(default)]
+    aov_output: Option<AovOutput>,
+    // Also writes each frame as a PNG in the given display encoding when
+    // given, next to the animation which is always sRGB.
+    #[serde(default)]
+    display_output: Option<DisplayEncoding>,
+}
+
+impl RenderConfig {
+    fn read(path: &Path) -> Result<Self> {
+        let text = std::fs::read_to_string(path)
+            .with_context(|| format!("Reading render config: {}", path.display()))?;
+        Self::parse(&text).with_context(|| format!("Parsing render config: {}", path.display()))
+    }
+
+    fn parse(text: &str) -> Result<Self> {
+        match ron::from_str::<Self>(text) {
+            Ok(config) => Ok(config),
+            // Configs from before tonemappers and exposure modes. Errors are
+            // reported for the current format.
+            Err(err) => match ron::from_str::<LegacyRenderConfig>(text) {
+                Ok(legacy) => Ok(legacy.into()),
+                Err(_) => Err(err.into()),
+            },
+        }
+    }
+}
+
+// `tonemapping` toggled the Narkowicz ACES fit, `exposure` was in stops.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LegacyRenderConfig {
     samples_per_pixel: NonZeroU32,
     image_scale: NonZeroU32,
     frame_delay_num: NonZeroU16,
@@ -31,18 +94,123 @@ struct RenderConfig {
     annotations: bool,
 }
 
+impl From<LegacyRenderConfig> for RenderConfig {
+    fn from(legacy: LegacyRenderConfig) -> Self {
+        Self {
+            samples_per_pixel: legacy.samples_per_pixel,
+            image_scale: legacy.image_scale,
+            frame_delay_num: legacy.frame_delay_num,
+            frame_delay_den: legacy.frame_delay_den,
+            tonemapper: if legacy.tonemapping {
+                Tonemapper::AcesNarkowicz
+            } else {
+                Tonemapper::Clamp
+            },
+            exposure: cpupt::ExposureMode::Manual {
+                stops: legacy.exposure,
+            },
+            annotations: legacy.annotations,
+            spectral: false,
+            adaptive_threshold: None,
+            min_samples_per_pixel: None,
+            filter: cpupt::FilterParams::default(),
+            working_space: ColorSpace::default(),
+            denoiser: None,
+            post: cpupt::PostParams::default(),
+            hdr_output: None,
+            aov_output: None,
+            display_output: None,
+        }
+    }
+}
+
+// Either a single EXR with one layer per AOV, or one PNG per AOV with the
+// visualization of the editor.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+enum AovOutput {
+    Exr(vz::hdr::ExrPixelType, vz::hdr::ExrCompression),
+    Png,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(deny_unknown_fields)]
 struct SceneConfig {
     name: String,
     material_mappings: Vec<MaterialMapping>,
+    // Images for `Image` textures in material mappings, which refer to them
+    // by their index in this list.
+    #[serde(default)]
+    images: Vec<PathBuf>,
+    #[serde(default)]
+    material_models: Vec<MaterialModelMapping>,
+    #[serde(default)]
+    material_blends: Vec<MaterialBlendMapping>,
+    #[serde(default)]
+    sky_model: cpupt::SkyModelKind,
+    #[serde(default = "default_sky_elevation_deg")]
     sky_elevation_deg: vz::cfg::Value<f32>,
+    #[serde(default = "default_sky_azimuth_deg")]
     sky_azimuth_deg: vz::cfg::Value<f32>,
+    // Replaces `sky_elevation_deg` and `sky_azimuth_deg` when given.
+    #[serde(default)]
+    sky_solar_position: Option<SolarPositionConfig>,
     sky_turbidity: vz::cfg::Value<f32>,
     sky_albedo: vz::cfg::Value<ColorRgb>,
+    // Replaces the sky model when given.
+    #[serde(default)]
+    sky_environment: Option<EnvironmentConfig>,
+    #[serde(default)]
+    fog: Option<FogConfig>,
+    #[serde(default)]
+    mesh_media: Vec<MeshMediumMapping>,
+    #[serde(default = "default_white_balance")]
+    white_balance: vz::cfg::Value<cpupt::WhiteBalance>,
     text_annotations: Vec<TextAnnotation>,
 }
 
+fn default_sky_elevation_deg() -> vz::cfg::Value<f32> {
+    vz::cfg::Value::Constant(cpupt::SkyParams::default().elevation.to_degrees())
+}
+
+fn default_sky_azimuth_deg() -> vz::cfg::Value<f32> {
+    vz::cfg::Value::Constant(cpupt::SkyParams::default().azimuth.to_degrees())
+}
+
+fn default_white_balance() -> vz::cfg::Value<cpupt::WhiteBalance> {
+    vz::cfg::Value::Constant(cpupt::WhiteBalance::default())
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+struct SolarPositionConfig {
+    latitude_deg: f32,
+    longitude_deg: f32,
+    // Year, month and day.
+    date: (i32, u8, u8),
+    utc_offset_hours: f32,
+    north_offset_deg: f32,
+    local_time_hours: vz::cfg::Value<f32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+struct EnvironmentConfig {
+    path: PathBuf,
+    rotation_deg: vz::cfg::Value<f32>,
+    intensity: vz::cfg::Value<f32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+struct FogConfig {
+    medium: vz::cfg::Value<cpupt::MediumParams>,
+    height: vz::cfg::Value<f32>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+struct MeshMediumMapping(String, vz::cfg::Value<cpupt::MediumParams>);
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(deny_unknown_fields)]
 struct MaterialMapping(
@@ -51,6 +219,18 @@ struct MaterialMapping(
     vz::cfg::Value<rds::DynamicTexture>,
 );
 
+// Replaces the model of a material, for example with `Conductor(Gold)`.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+struct MaterialModelMapping(String, rds::MaterialModel);
+
+// Turns a material into a blend of two other materials, for example
+// `("cube", "paint", "rust", Texture)`. The mask can be animated with the
+// `BlendMask` field.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(deny_unknown_fields)]
+struct MaterialBlendMapping(String, String, String, rds::BlendMask);
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(deny_unknown_fields)]
 enum TextAnnotation {
@@ -64,7 +244,7 @@ enum TextAnnotation {
 
 pub fn run(args: Args) -> Result<()> {
     // Load configs.
-    let render_config: RenderConfig = vz::cfg::read_from_file(args.render_config)?;
+    let render_config = RenderConfig::read(&args.render_config)?;
     let scene_configs: Vec<SceneConfig> = vz::cfg::read_from_file(args.scene_config)?;
 
     // Backup configs.
@@ -110,20 +290,59 @@ fn render(
 
     // Unpack render config.
     let samples_per_pixel = render_config.samples_per_pixel.get();
+    let min_samples_per_pixel = render_config.min_samples_per_pixel.map_or(
+        cpupt::Params::default().min_samples_per_pixel,
+        NonZeroU32::get,
+    );
     let image_scale = render_config.image_scale.get();
     let image_aspect = DEFAULT_ASPECT_RATIO;
     let image_size = (image_aspect.0 * image_scale, image_aspect.1 * image_scale);
     let frame_delay_num = render_config.frame_delay_num.get();
     let frame_delay_den = render_config.frame_delay_den.get();
-    let tonemapping = render_config.tonemapping;
-    let exposure = cpupt::Exposure::new(render_config.exposure);
+    let tonemapper = render_config.tonemapper;
+    let exposure = cpupt::Exposure::with_mode(render_config.exposure);
 
     // Unpack scene config.
     let material_mappings = scene_config.material_mappings;
+    let images = scene_config.images;
+    let material_models = scene_config.material_models;
+    let material_blends = scene_config.material_blends;
+    let sky_model = scene_config.sky_model;
     let sky_elevation_deg: vz::anim::Value<_> = scene_config.sky_elevation_deg.into();
     let sky_azimuth_deg: vz::anim::Value<_> = scene_config.sky_azimuth_deg.into();
+    let sky_solar_position = scene_config.sky_solar_position.map(|solar| {
+        let (year, month, day) = solar.date;
+        let position = cpupt::SolarPosition {
+            latitude: solar.latitude_deg.to_radians(),
+            longitude: solar.longitude_deg.to_radians(),
+            year,
+            month,
+            day,
+            local_time: 0.0,
+            utc_offset: solar.utc_offset_hours,
+            north_offset: solar.north_offset_deg.to_radians(),
+        };
+        let local_time: vz::anim::Value<_> = solar.local_time_hours.into();
+        (position, local_time)
+    });
     let sky_turbidity: vz::anim::Value<_> = scene_config.sky_turbidity.into();
     let sky_albedo: vz::anim::Value<_> = scene_config.sky_albedo.into();
+    let white_balance: vz::anim::Value<_> = scene_config.white_balance.into();
+    let sky_environment = scene_config
+        .sky_environment
+        .map(|environment| -> Result<_> {
+            let map = cpupt::EnvironmentMap::load(&environment.path)?;
+            let rotation_deg: vz::anim::Value<_> = environment.rotation_deg.into();
+            let intensity: vz::anim::Value<_> = environment.intensity.into();
+            Ok((cpupt::EnvironmentParams::new(map), rotation_deg, intensity))
+        })
+        .transpose()?;
+    let fog = scene_config.fog.map(|fog| {
+        let medium: vz::anim::Value<_> = fog.medium.into();
+        let height: vz::anim::Value<_> = fog.height.into();
+        (medium, height)
+    });
+    let mesh_media = scene_config.mesh_media;
     let text_annotations = scene_config.text_annotations;
 
     // Init rds scene.
@@ -132,6 +351,14 @@ fn render(
             .with_context(|| format!("Reading glb scene: {}", glb_scene.display()))?,
     )?;
 
+    // Init images. They come after the images from the glb scene.
+    let image_offset = dyn_scene.images.len() as u32;
+    for path in &images {
+        let image = rds::DynamicImage::load(path)?;
+        info!("Loaded image {} from {}", image.name, path.display());
+        dyn_scene.images.push(Arc::new(image));
+    }
+
     // Init materials.
     let material_mappings = material_mappings
         .into_iter()
@@ -148,15 +375,72 @@ fn render(
         })
         .collect::<Vec<_>>();
 
+    // Init material models.
+    for MaterialModelMapping(name, model) in material_models {
+        if let Some(material) = rds::material_by_name(&rds_scene, &name) {
+            dyn_scene.materials[material as usize].model = model;
+        } else {
+            warn!("Could not find material called {name}");
+        }
+    }
+
+    // Init material blends.
+    for MaterialBlendMapping(name, a, b, mask) in material_blends {
+        let lookup = |name: &str| {
+            let material = rds::material_by_name(&rds_scene, name);
+            if material.is_none() {
+                warn!("Could not find material called {name}");
+            }
+            material
+        };
+        if let (Some(material), Some(a), Some(b)) = (lookup(&name), lookup(&a), lookup(&b)) {
+            let material = &mut dyn_scene.materials[material as usize];
+            material.model = rds::MaterialModel::Blend;
+            material.blend = rds::MaterialBlend {
+                materials: [a, b],
+                mask,
+            };
+        }
+    }
+
+    // Init media.
+    let mesh_media = mesh_media
+        .into_iter()
+        .filter_map(|map| {
+            let MeshMediumMapping(name, value) = map;
+            if let Some(mesh) = rds::mesh_by_name(&rds_scene, &name) {
+                Some((mesh, vz::anim::Value::<_>::from(value)))
+            } else {
+                warn!("Could not find mesh called {name}");
+                None
+            }
+        })
+        .collect::<Vec<_>>();
+
     // Calculate animation length.
     let mut total_time = -f32::MAX;
     total_time = total_time.max(sky_elevation_deg.max_time());
     total_time = total_time.max(sky_azimuth_deg.max_time());
+    if let Some((_, local_time)) = &sky_solar_position {
+        total_time = total_time.max(local_time.max_time());
+    }
     total_time = total_time.max(sky_turbidity.max_time());
     total_time = total_time.max(sky_albedo.max_time());
+    total_time = total_time.max(white_balance.max_time());
+    if let Some((_, rotation_deg, intensity)) = &sky_environment {
+        total_time = total_time.max(rotation_deg.max_time());
+        total_time = total_time.max(intensity.max_time());
+    }
     for (_, value) in &material_mappings {
         total_time = total_time.max(value.max_time());
     }
+    if let Some((medium, height)) = &fog {
+        total_time = total_time.max(medium.max_time());
+        total_time = total_time.max(height.max_time());
+    }
+    for (_, value) in &mesh_media {
+        total_time = total_time.max(value.max_time());
+    }
     let frame_time = f32::from(frame_delay_num) / f32::from(frame_delay_den);
     let frame_count = (total_time / frame_time).ceil() as u32;
     info!("total_time={total_time}, frame_count={frame_count}");
@@ -165,6 +449,11 @@ fn render(
     let raytracer = cpupt::Raytracer::create(
         cpupt::Params {
             samples_per_pixel,
+            spectral: render_config.spectral,
+            adaptive_threshold: render_config.adaptive_threshold,
+            min_samples_per_pixel,
+            filter: render_config.filter,
+            working_space: render_config.working_space,
             ..cpupt::Params::default()
         },
         rds_scene.clone(),
@@ -176,7 +465,6 @@ fn render(
 
         let timer = Instant::now();
         let hemisphere_sampler = cpupt::HemisphereSampler::Cosine;
-        let visualize_normals = false;
         let pb = ProgressBar::new(u64::from(frame_count * samples_per_pixel)).with_style(
             ProgressStyle::with_template("{wide_bar} elapsed={elapsed_precise} eta={eta_precise}")?,
         );
@@ -190,14 +478,73 @@ fn render(
             let camera_transform = Mat4::from_axis_angle(&Vec3::y_axis(), camera_angle);
 
             // Sky.
-            let sky_elevation = sky_elevation_deg.value(time).to_radians();
-            let sky_azimuth = sky_azimuth_deg.value(time).to_radians();
+            let (sky_elevation, sky_azimuth) =
+                if let Some((position, local_time)) = &sky_solar_position {
+                    cpupt::SolarPosition {
+                        local_time: local_time.value(time),
+                        ..*position
+                    }
+                    .sky_angles()?
+                } else {
+                    (
+                        sky_elevation_deg.value(time).to_radians(),
+                        sky_azimuth_deg.value(time).to_radians(),
+                    )
+                };
             let sky_turbidity = sky_turbidity.value(time);
             let sky_albedo = sky_albedo.value(time);
+            let white_balance = white_balance.value(time);
+            let sky = if let Some((environment, rotation_deg, intensity)) = &sky_environment {
+                cpupt::SkySource::Environment(cpupt::EnvironmentParams {
+                    rotation: rotation_deg.value(time).to_radians(),
+                    intensity: intensity.value(time),
+                    ..environment.clone()
+                })
+            } else {
+                cpupt::SkySource::Analytic(cpupt::SkyParams {
+                    model: sky_model,
+                    elevation: sky_elevation,
+                    azimuth: sky_azimuth,
+                    turbidity: sky_turbidity,
+                    albedo: sky_albedo,
+                })
+            };
+
+            // Media.
+            let fog = fog.as_ref().map(|(medium, height)| cpupt::FogParams {
+                medium: medium.value(time),
+                height: height.value(time),
+            });
+            if let Some(fog) = &fog {
+                fog.medium.validate().context("Invalid fog medium")?;
+            }
+            let mesh_media = mesh_media
+                .iter()
+                .map(|(mesh, value)| cpupt::MeshMedium {
+                    mesh: *mesh,
+                    medium: value.value(time),
+                })
+                .collect::<Vec<_>>();
+            for mesh_medium in &mesh_media {
+                mesh_medium
+                    .medium
+                    .validate()
+                    .with_context(|| format!("Invalid medium for mesh {}", mesh_medium.mesh))?;
+            }
 
             // Materials.
             for (index, value) in &material_mappings {
-                dyn_scene.textures[*index as usize] = value.value(time);
+                let mut texture = value.value(time);
+                if let rds::DynamicTexture::Image(image) = &mut texture {
+                    ensure!(
+                        (image.image as usize) < images.len(),
+                        "Image index {} is out of bounds, the scene config has {} images",
+                        image.image,
+                        images.len()
+                    );
+                    image.image += image_offset;
+                }
+                dyn_scene.textures[*index as usize] = texture;
             }
 
             // Render.
@@ -206,27 +553,92 @@ fn render(
                 image_size,
                 hemisphere_sampler,
                 dyn_scene: dyn_scene.clone(),
-                visualize_normals,
-                tonemapping,
+                tonemapper,
                 exposure,
-                sky_params: cpupt::SkyParams {
-                    elevation: sky_elevation,
-                    azimuth: sky_azimuth,
-                    turbidity: sky_turbidity,
-                    albedo: sky_albedo,
-                },
+                white_balance,
+                denoiser: render_config.denoiser,
+                post: render_config.post,
+                sky: sky.clone(),
+                fog,
+                mesh_media,
                 salt: Some(frame_index.into()),
             })?;
-            let mut latest_frame: Option<vz::image::Rgb> = None;
-            for _ in 0..samples_per_pixel {
+            let latest_output = loop {
                 let output = raytracer.recv_output().expect("Something went wrong");
-                latest_frame = Some(vz::image::Rgb::from_colors(
-                    &output.image,
-                    output.image_size,
+                let status = output.sampling_status;
+                pb.set_position(u64::from(
+                    frame_index * samples_per_pixel + status.index().min(samples_per_pixel),
                 ));
-                pb.inc(1);
+                if status.is_done() {
+                    break output;
+                }
+            };
+            if render_config.hdr_output.is_some()
+                || render_config.aov_output.is_some()
+                || render_config.display_output.is_some()
+            {
+                let dir_name = format!("render-{render_job_name}-{}", scene_config.name);
+                let dir = work_dir().join(dir_name);
+                std::fs::create_dir_all(&dir)?;
+                if let Some(format) = render_config.hdr_output {
+                    let path = dir.join(format!("{frame_index:04}.{}", format.extension()));
+                    vz::hdr::write_to_file(
+                        &path,
+                        format,
+                        &latest_output.radiance,
+                        latest_output.image_size,
+                        latest_output.working_space,
+                    )?;
+                }
+                match render_config.aov_output {
+                    Some(AovOutput::Exr(pixel_type, compression)) => {
+                        let path = dir.join(format!("{frame_index:04}-aovs.exr"));
+                        let channels = latest_output.aovs.exr_channels(&latest_output.radiance);
+                        let bytes = vz::hdr::write_exr_channels(
+                            &channels,
+                            latest_output.image_size,
+                            latest_output.working_space,
+                            pixel_type,
+                            compression,
+                        )?;
+                        std::fs::write(&path, bytes)
+                            .with_context(|| format!("Writing {}", path.display()))?;
+                    }
+                    Some(AovOutput::Png) => {
+                        for aov in cpupt::AovKind::ALL {
+                            let Some(colors) = latest_output.aovs.visualize(aov) else {
+                                continue;
+                            };
+                            let path = dir.join(format!("{frame_index:04}-{}.png", aov.name()));
+                            vz::image::Rgb::from_colors(&colors, latest_output.image_size)
+                                .save(&path)?;
+                        }
+                    }
+                    None => {}
+                }
+                if let Some(encoding) = render_config.display_output {
+                    let path = dir.join(format!("{frame_index:04}-{}.png", encoding.name()));
+                    let display = cpupt::DisplayTransform::new(
+                        latest_output.working_space,
+                        latest_output.exposure,
+                        white_balance,
+                        tonemapper,
+                        encoding,
+                    );
+                    let lens = cpupt::post_process(
+                        &render_config.post,
+                        &latest_output.radiance,
+                        latest_output.image_size,
+                    );
+                    let signals = lens
+                        .iter()
+                        .map(|&radiance| display.encode(radiance))
+                        .collect::<Vec<_>>();
+                    vz::image::write_png(&path, &signals, latest_output.image_size, encoding)?;
+                }
             }
-            let mut latest_frame = latest_frame.unwrap();
+            let mut latest_frame =
+                vz::image::Rgb::from_colors(&latest_output.image, latest_output.image_size);
             if render_config.annotations {
                 let mut text = vz::annotation::TextBox::new();
 
@@ -239,7 +651,9 @@ fn render(
                         continue;
                     };
 
-                    let Some(material) = rds::dynamic_material_by_name(&rds_scene, &dyn_scene, name) else {
+                    let Some(material) =
+                        rds::dynamic_material_by_name(&rds_scene, &dyn_scene, name)
+                    else {
                         warn!("Could not find material called {name}");
                         continue;
                     };
@@ -251,7 +665,26 @@ fn render(
                     let specular_tint = dyn_scene.textures[material.specular_tint as usize];
                     let sheen = dyn_scene.textures[material.sheen as usize];
                     let sheen_tint = dyn_scene.textures[material.sheen_tint as usize];
+                    let sheen_color = dyn_scene.textures[material.sheen_color as usize];
+                    let sheen_roughness = dyn_scene.textures[material.sheen_roughness as usize];
+                    let iridescence = dyn_scene.textures[material.iridescence as usize];
+                    let iridescence_thickness =
+                        dyn_scene.textures[material.iridescence_thickness as usize];
                     text = text.line([("material", name.as_str())]);
+                    if let rds::MaterialModel::Conductor(preset) = material.model {
+                        text = text.line([("    conductor", preset.name())]);
+                    }
+                    if material.model == rds::MaterialModel::Blend {
+                        let [a, b] = material.blend.materials;
+                        let blend_mask = dyn_scene.textures[material.blend_mask as usize];
+                        let a = &rds_scene.materials[a as usize].name;
+                        let b = &rds_scene.materials[b as usize].name;
+                        text = text.line([("    blend", &format!("{a} / {b}"))]);
+                        text = text.line([
+                            ("    mask", material.blend.mask.name()),
+                            ("blend_mask", &format!("{blend_mask:.02}")),
+                        ]);
+                    }
                     text = text.line([("    base_color", &format!("{base_color:.02}"))]);
                     text = text.line([
                         ("    metallic", &format!("{metallic:.02}")),
@@ -265,6 +698,24 @@ fn render(
                         ("    sheen", &format!("{sheen:.02}")),
                         ("sheen_tint", &format!("{sheen_tint:.02}")),
                     ]);
+                    text = text.line([
+                        ("    sheen_color", &format!("{sheen_color:.02}")),
+                        ("sheen_roughness", &format!("{sheen_roughness:.02}")),
+                    ]);
+                    text = text.line([
+                        ("    iridescence", &format!("{iridescence:.02}")),
+                        ("thickness", &format!("{iridescence_thickness:.00}")),
+                    ]);
+                    if material.model == rds::MaterialModel::Subsurface {
+                        let subsurface_color =
+                            dyn_scene.textures[material.subsurface_color as usize];
+                        let subsurface_radius =
+                            dyn_scene.textures[material.subsurface_radius as usize];
+                        text = text
+                            .line([("    subsurface_color", &format!("{subsurface_color:.02}"))]);
+                        text = text
+                            .line([("    subsurface_radius", &format!("{subsurface_radius:.03}"))]);
+                    }
                 }
 
                 // Annotate sky parameters.
@@ -272,15 +723,39 @@ fn render(
                     .iter()
                     .any(|ta| matches!(&ta, TextAnnotation::SkyParameters))
                 {
-                    text = text
-                        .line([
-                            ("elevation", format!("{:.02}°", sky_elevation.to_degrees())),
-                            ("azimuth", format!("{:.02}°", sky_azimuth.to_degrees())),
-                        ])
-                        .line([
-                            ("turbidity", format!("{sky_turbidity:.02}")),
-                            ("albedo", format!("{sky_albedo:.02}")),
+                    if let cpupt::SkySource::Environment(environment) = &sky {
+                        let file_name = environment
+                            .map
+                            .path()
+                            .file_name()
+                            .unwrap_or_default()
+                            .to_string_lossy();
+                        text = text.line([("environment", file_name.as_ref())]).line([
+                            (
+                                "rotation",
+                                &format!("{:.02}°", environment.rotation.to_degrees()),
+                            ),
+                            ("intensity", &format!("{:.02}", environment.intensity)),
                         ]);
+                    } else {
+                        text = text
+                            .line([("model", sky_model.name())])
+                            .line([
+                                ("elevation", format!("{:.02}°", sky_elevation.to_degrees())),
+                                ("azimuth", format!("{:.02}°", sky_azimuth.to_degrees())),
+                            ])
+                            .line([
+                                ("turbidity", format!("{sky_turbidity:.02}")),
+                                ("albedo", format!("{sky_albedo:.02}")),
+                            ]);
+                        if let Some((_, local_time)) = &sky_solar_position {
+                            let minutes = (60.0 * local_time.value(time)).round() as u32;
+                            text = text.line([(
+                                "local_time",
+                                format!("{:02}:{:02}", minutes / 60, minutes % 60),
+                            )]);
+                        }
+                    }
                 }
 
                 // Render configs.
@@ -289,10 +764,51 @@ fn render(
                     ("frame", format!("{frame_index}/{frame_count}")),
                     ("res", format!("{}x{}", image_size.0, image_size.1)),
                     ("spp", format!("{samples_per_pixel}")),
+                    ("spectral", format!("{}", render_config.spectral)),
+                ]);
+                text = text.line([(
+                    "filter",
+                    format!(
+                        "{} r={}",
+                        render_config.filter.kind.name(),
+                        render_config.filter.radius
+                    ),
+                )]);
+                if let Some(threshold) = render_config.adaptive_threshold {
+                    text = text.line([(
+                        "adaptive",
+                        format!(
+                            "{threshold} min={} avg={}",
+                            min_samples_per_pixel,
+                            latest_output.sampling_status.index()
+                        ),
+                    )]);
+                }
+                text = text.line([
+                    ("tonemap", tonemapper.name().to_owned()),
+                    ("exposure", format!("{:.02}", latest_output.exposure)),
+                    ("denoiser", format!("{}", render_config.denoiser.is_some())),
                 ]);
+                if render_config.post != cpupt::PostParams::default() {
+                    let post = render_config.post;
+                    text = text.line([
+                        ("bloom", format!("{} r={}", post.bloom, post.bloom_radius)),
+                        ("vignette", format!("{}", post.vignette)),
+                        ("aberration", format!("{}", post.chromatic_aberration)),
+                    ]);
+                }
                 text = text.line([
-                    ("tonemap", format!("{tonemapping}")),
-                    ("exposure", format!("{exposure:.02}")),
+                    (
+                        "white_balance",
+                        format!(
+                            "{:.0}K {:+.03}",
+                            white_balance.temperature, white_balance.tint
+                        ),
+                    ),
+                    (
+                        "working_space",
+                        latest_output.working_space.name().to_owned(),
+                    ),
                 ]);
 
                 latest_frame.draw_text(&font, ColorRgb::WHITE, &text.build());
@@ -332,8 +848,18 @@ fn config_template() {
     use vz::cfg::{keyframe, Value};
     use Value::{Constant, Keyframes};
 
+    let checkerboard = |offset| {
+        rds::DynamicTexture::Procedural(rds::ProceduralTexture {
+            pattern: rds::ProceduralPattern::Checkerboard,
+            scale: 8.0,
+            offset,
+            low: [0.5, 0.5, 0.5, 1.0],
+            high: [1.0, 1.0, 1.0, 1.0],
+        })
+    };
     let config = SceneConfig {
         name: "roughness".to_owned(),
+        images: vec![],
         material_mappings: vec![
             MaterialMapping(
                 "cube".to_owned(),
@@ -365,14 +891,66 @@ fn config_template() {
                 ]),
             ),
             MaterialMapping("plane".to_owned(), Metallic, Constant(TS(0.75))),
+            MaterialMapping(
+                "plane".to_owned(),
+                BaseColor,
+                Keyframes(vec![
+                    keyframe!(0.0, checkerboard([0.0, 0.0]), Linear),
+                    keyframe!(3.0, checkerboard([1.0, 0.0]), Linear),
+                ]),
+            ),
         ],
+        material_models: vec![MaterialModelMapping(
+            "cube".to_owned(),
+            rds::MaterialModel::Conductor(cpupt::bxdfs::ConductorPreset::Gold),
+        )],
+        material_blends: vec![],
+        sky_model: cpupt::SkyModelKind::Nishita,
         sky_elevation_deg: Constant(45.0),
         sky_azimuth_deg: Keyframes(vec![
             keyframe!(0.0, 0.0, CubicInOut),
             keyframe!(3.0, 180.0, CubicInOut),
         ]),
+        sky_solar_position: Some(SolarPositionConfig {
+            latitude_deg: 60.17,
+            longitude_deg: 24.94,
+            date: (2023, 6, 21),
+            utc_offset_hours: 3.0,
+            north_offset_deg: 0.0,
+            local_time_hours: Keyframes(vec![
+                keyframe!(0.0, 4.0, Linear),
+                keyframe!(3.0, 22.0, Linear),
+            ]),
+        }),
         sky_turbidity: Constant(3.0),
         sky_albedo: Constant(ColorRgb::WHITE),
+        sky_environment: Some(EnvironmentConfig {
+            path: PathBuf::from("studio.hdr"),
+            rotation_deg: Keyframes(vec![
+                keyframe!(0.0, 0.0, Linear),
+                keyframe!(3.0, 360.0, Linear),
+            ]),
+            intensity: Constant(1.0),
+        }),
+        fog: Some(FogConfig {
+            medium: Constant(cpupt::MediumParams::default()),
+            height: Keyframes(vec![
+                keyframe!(0.0, 0.5, CubicInOut),
+                keyframe!(3.0, 2.0, CubicInOut),
+            ]),
+        }),
+        mesh_media: vec![],
+        white_balance: Keyframes(vec![
+            keyframe!(
+                0.0,
+                cpupt::WhiteBalance {
+                    temperature: 3000.0,
+                    tint: 0.0
+                },
+                Linear
+            ),
+            keyframe!(3.0, cpupt::WhiteBalance::default(), Linear),
+        ]),
         text_annotations: vec![
             TextAnnotation::SkyParameters,
             TextAnnotation::Material("cube".to_owned()),
@@ -384,3 +962,36 @@ fn config_template() {
         ron::ser::to_string_pretty(&vec![config], ron::ser::PrettyConfig::default()).unwrap()
     );
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn legacy_render_config() {
+        let legacy = RenderConfig::parse(
+            "(samples_per_pixel: 8, image_scale: 50, frame_delay_num: 1, frame_delay_den: 30, \
+             tonemapping: false, exposure: 2.5, annotations: true)",
+        )
+        .unwrap();
+        assert_eq!(legacy.tonemapper, Tonemapper::Clamp);
+        assert_eq!(legacy.exposure, cpupt::ExposureMode::Manual { stops: 2.5 });
+
+        // Tonemapper and exposure are optional in the current format.
+        let current = RenderConfig::parse(
+            "(samples_per_pixel: 8, image_scale: 50, frame_delay_num: 1, frame_delay_den: 30, \
+             annotations: true)",
+        )
+        .unwrap();
+        assert_eq!(current.tonemapper, Tonemapper::default());
+        assert_eq!(current.exposure, cpupt::ExposureMode::default());
+
+        // Errors refer to the current format.
+        let err = RenderConfig::parse(
+            "(samples_per_pixel: 8, image_scale: 50, frame_delay_num: 1, frame_delay_den: 30, \
+             tonemapper: Sharp, annotations: true)",
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("Sharp"), "{err}");
+    }
+}
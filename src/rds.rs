@@ -2,6 +2,10 @@ use super::*;
 
 // `rds` stands for Raydiance Scene.
 
+mod procedural;
+
+pub use procedural::{ProceduralPattern, ProceduralTexture};
+
 #[derive(Clone, Debug)]
 pub struct Scene {
     pub cameras: Vec<Camera>,
@@ -29,14 +33,35 @@ pub struct Mesh {
     pub positions: Vec<Point3>,
     pub tex_coords: Vec<Point2>,
     pub normals: Vec<Normal>,
+    pub colors: Vec<ColorRgb>,
     pub triangles: Vec<Vec3u>,
     pub material: u32,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum MaterialModel {
     Diffuse,
+    OrenNayar,
     Disney,
+    Subsurface,
+    Conductor(cpupt::bxdfs::ConductorPreset),
+    Blend,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum BlendMask {
+    #[default]
+    Texture,
+    VertexColor,
+}
+
+// Picks `materials[1]` with the probability given by the red channel of the
+// mask, and `materials[0]` otherwise. A blend that picks itself is evaluated
+// as Disney with its own textures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MaterialBlend {
+    pub materials: [u32; 2],
+    pub mask: BlendMask,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -48,6 +73,14 @@ pub enum MaterialField {
     SpecularTint,
     Sheen,
     SheenTint,
+    SheenColor,
+    SheenRoughness,
+    Iridescence,
+    IridescenceIor,
+    IridescenceThickness,
+    SubsurfaceColor,
+    SubsurfaceRadius,
+    BlendMask,
 }
 
 #[derive(Clone, Debug)]
@@ -61,6 +94,15 @@ pub struct Material {
     pub specular_tint: u32,
     pub sheen: u32,
     pub sheen_tint: u32,
+    pub sheen_color: u32,
+    pub sheen_roughness: u32,
+    pub iridescence: u32,
+    pub iridescence_ior: u32,
+    pub iridescence_thickness: u32,
+    pub subsurface_color: u32,
+    pub subsurface_radius: u32,
+    pub blend_mask: u32,
+    pub blend: MaterialBlend,
 }
 
 #[derive(Clone, Debug)]
@@ -84,6 +126,7 @@ pub struct DynamicScene {
     pub textures: Vec<DynamicTexture>,
     pub default_textures: Vec<DynamicTexture>,
     pub replaced_textures: BitVec,
+    pub images: Vec<Arc<DynamicImage>>,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -96,6 +139,15 @@ pub struct DynamicMaterial {
     pub specular_tint: u32,
     pub sheen: u32,
     pub sheen_tint: u32,
+    pub sheen_color: u32,
+    pub sheen_roughness: u32,
+    pub iridescence: u32,
+    pub iridescence_ior: u32,
+    pub iridescence_thickness: u32,
+    pub subsurface_color: u32,
+    pub subsurface_radius: u32,
+    pub blend_mask: u32,
+    pub blend: MaterialBlend,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
@@ -104,6 +156,26 @@ pub enum DynamicTexture {
     Vector2([f32; 2]),
     Vector3([f32; 3]),
     Vector4([f32; 4]),
+    Procedural(ProceduralTexture),
+    Image(ImageTexture),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ImageTexture {
+    // Index into `DynamicScene::images`.
+    pub image: u32,
+    pub tint: [f32; 4],
+    pub uv_scale: [f32; 2],
+}
+
+// Images are immutable once loaded, so they are compared by identity.
+#[derive(Debug)]
+pub struct DynamicImage {
+    pub name: String,
+    pub texture: Texture,
+    // Whether the texels are sRGB encoded, which colors decode when sampling.
+    pub srgb: bool,
 }
 
 //
@@ -183,6 +255,14 @@ impl Scene {
                 let specular_tint = &textures[material.specular_tint as usize];
                 let sheen = &textures[material.sheen as usize];
                 let sheen_tint = &textures[material.sheen_tint as usize];
+                let sheen_color = &textures[material.sheen_color as usize];
+                let sheen_roughness = &textures[material.sheen_roughness as usize];
+                let iridescence = &textures[material.iridescence as usize];
+                let iridescence_ior = &textures[material.iridescence_ior as usize];
+                let iridescence_thickness = &textures[material.iridescence_thickness as usize];
+                let subsurface_color = &textures[material.subsurface_color as usize];
+                let subsurface_radius = &textures[material.subsurface_radius as usize];
+                let blend_mask = &textures[material.blend_mask as usize];
                 let base_color = base_color.sample(Point2::new(0.5, 0.5));
                 let metallic = metallic.sample(Point2::new(0.5, 0.5)).r();
                 let roughness = roughness.sample(Point2::new(0.5, 0.5)).r();
@@ -190,6 +270,14 @@ impl Scene {
                 let specular_tint = specular_tint.sample(Point2::new(0.5, 0.5)).r();
                 let sheen = sheen.sample(Point2::new(0.5, 0.5)).r();
                 let sheen_tint = sheen_tint.sample(Point2::new(0.5, 0.5)).r();
+                let sheen_color = sheen_color.sample(Point2::new(0.5, 0.5));
+                let sheen_roughness = sheen_roughness.sample(Point2::new(0.5, 0.5)).r();
+                let iridescence = iridescence.sample(Point2::new(0.5, 0.5)).r();
+                let iridescence_ior = iridescence_ior.sample(Point2::new(0.5, 0.5)).r();
+                let iridescence_thickness = iridescence_thickness.sample(Point2::new(0.5, 0.5)).r();
+                let subsurface_color = subsurface_color.sample(Point2::new(0.5, 0.5));
+                let subsurface_radius = subsurface_radius.sample(Point2::new(0.5, 0.5));
+                let blend_mask = blend_mask.sample(Point2::new(0.5, 0.5)).r();
                 {
                     macro_rules! print_param {
                         ($mat:ident, $param:ident) => {
@@ -208,6 +296,14 @@ impl Scene {
                     print_param!(material, specular_tint);
                     print_param!(material, sheen);
                     print_param!(material, sheen_tint);
+                    print_param!(material, sheen_color);
+                    print_param!(material, sheen_roughness);
+                    print_param!(material, iridescence);
+                    print_param!(material, iridescence_ior);
+                    print_param!(material, iridescence_thickness);
+                    print_param!(material, subsurface_color);
+                    print_param!(material, subsurface_radius);
+                    print_param!(material, blend_mask);
                 }
             }
             info!("Scene contains {} textures", textures.len());
@@ -240,16 +336,39 @@ impl Scene {
                     specular_tint: material.specular_tint,
                     sheen: material.sheen,
                     sheen_tint: material.sheen_tint,
+                    sheen_color: material.sheen_color,
+                    sheen_roughness: material.sheen_roughness,
+                    iridescence: material.iridescence,
+                    iridescence_ior: material.iridescence_ior,
+                    iridescence_thickness: material.iridescence_thickness,
+                    subsurface_color: material.subsurface_color,
+                    subsurface_radius: material.subsurface_radius,
+                    blend_mask: material.blend_mask,
+                    blend: material.blend,
                 })
                 .collect();
+            let mut images = vec![];
             let textures = textures
                 .iter()
-                .map(|texture| match texture {
+                .enumerate()
+                .map(|(index, texture)| match texture {
                     Texture::Scalar(s) => DynamicTexture::Scalar(*s),
                     Texture::Vector2(v) => DynamicTexture::Vector2(*v),
                     Texture::Vector3(v) => DynamicTexture::Vector3(*v),
                     Texture::Vector4(v) => DynamicTexture::Vector4(*v),
-                    Texture::Image { .. } => DynamicTexture::Vector4([1.0, 1.0, 1.0, 1.0]),
+                    Texture::Image { .. } => {
+                        let image = images.len() as u32;
+                        images.push(Arc::new(DynamicImage {
+                            name: format!("texture {index}"),
+                            texture: texture.clone(),
+                            srgb: false,
+                        }));
+                        DynamicTexture::Image(ImageTexture {
+                            image,
+                            tint: [1.0, 1.0, 1.0, 1.0],
+                            uv_scale: [1.0, 1.0],
+                        })
+                    }
                 })
                 .collect::<Vec<_>>();
             let default_textures = textures.clone();
@@ -260,6 +379,7 @@ impl Scene {
                 textures,
                 default_textures,
                 replaced_textures,
+                images,
             }
         };
 
@@ -351,6 +471,7 @@ fn import_gltf_mesh(
     let positions = import_gltf_positions(gltf, gltf_primitive, gltf_data)?;
     let tex_coords = import_gltf_tex_coords(gltf, gltf_primitive, gltf_data)?;
     let normals = import_gltf_normals(gltf, gltf_primitive, gltf_data)?;
+    let colors = import_gltf_colors(gltf, gltf_primitive, gltf_data, positions.len())?;
     let triangles = import_gltf_triangles(gltf, gltf_primitive, gltf_data)?;
 
     // Append.
@@ -360,6 +481,7 @@ fn import_gltf_mesh(
         positions,
         tex_coords,
         normals,
+        colors,
         triangles,
         material,
     });
@@ -385,31 +507,9 @@ fn import_gltf_material(
     // Base color.
     let base_color = {
         let base_color = if let Some(id) = &pbr.base_color_texture {
-            // Image.
-            let image = {
-                let texture = &gltf.textures[id.index];
-                let image = &gltf.images[texture.source];
-                let view = &gltf.buffer_views[image.buffer_view];
-                let bytes = &gltf_data[view.byte_offset..(view.byte_offset + view.byte_length)];
-                let format = imagelib::ImageFormat::Png;
-                let image = imagelib::load_from_memory_with_format(bytes, format)?;
-                image.into_rgba32f()
-            };
-
-            // Validate.
-            let width = image.width();
-            let height = image.height();
-            let components = 4;
-            ensure!(width > 0 && width.is_power_of_two());
-            ensure!(height > 0 && height.is_power_of_two());
-            let pixels = image.into_raw();
-
-            Texture::Image {
-                width,
-                height,
-                components,
-                pixels,
-            }
+            import_gltf_image(gltf, gltf_data, id, |[r, g, b, a]| {
+                [srgb_to_linear(r), srgb_to_linear(g), srgb_to_linear(b), a]
+            })?
         } else {
             Texture::Vector4(pbr.base_color_factor.0)
         };
@@ -445,9 +545,9 @@ fn import_gltf_material(
         (specular_index, specular_tint_index)
     };
 
-    // Sheen & sheen tint.
+    // Sheen & sheen tint, for the Disney sheen. glTF has no equivalent, its
+    // sheen extension is imported as the Charlie sheen below.
     let (sheen, sheen_tint) = {
-        // Todo: Check if Blender can export both sheen and sheen tint.
         let sheen = Texture::Scalar(0.0);
         let sheen_tint = Texture::Scalar(0.5);
         let sheen_index = textures.len() as u32;
@@ -457,11 +557,132 @@ fn import_gltf_material(
         (sheen_index, sheen_tint_index)
     };
 
-    // Append.
+    // Sheen color & sheen roughness, for the Charlie sheen. The textures are
+    // scaled by their factors, and the roughness is in the alpha channel.
+    let (sheen_color, sheen_roughness) = {
+        let extensions = gltf_material.extensions.as_ref();
+        let (sheen_color, sheen_roughness) =
+            if let Some(sheen) = extensions.and_then(|e| e.sheen.as_ref()) {
+                let [r, g, b] = sheen.color_factor;
+                let sheen_color = if let Some(id) = &sheen.color_texture {
+                    import_gltf_image(gltf, gltf_data, id, |pixel| {
+                        [
+                            srgb_to_linear(pixel[0]) * r,
+                            srgb_to_linear(pixel[1]) * g,
+                            srgb_to_linear(pixel[2]) * b,
+                            1.0,
+                        ]
+                    })?
+                } else {
+                    Texture::Vector4([r, g, b, 1.0])
+                };
+                let factor = sheen.roughness_factor;
+                let sheen_roughness = if let Some(id) = &sheen.roughness_texture {
+                    import_gltf_image(gltf, gltf_data, id, |pixel| [pixel[3] * factor; 4])?
+                } else {
+                    Texture::Scalar(factor)
+                };
+                (sheen_color, sheen_roughness)
+            } else {
+                (Texture::Vector4([0.0, 0.0, 0.0, 1.0]), Texture::Scalar(0.0))
+            };
+        let sheen_color_index = textures.len() as u32;
+        let sheen_roughness_index = textures.len() as u32 + 1;
+        textures.push(sheen_color);
+        textures.push(sheen_roughness);
+        (sheen_color_index, sheen_roughness_index)
+    };
+
+    // Iridescence, iridescence ior & iridescence thickness, for the thin film.
+    // The weight is in the red channel of its texture, the thickness in
+    // nanometers is interpolated with the green channel of its texture.
+    let (iridescence, iridescence_ior, iridescence_thickness) = {
+        let extensions = gltf_material.extensions.as_ref();
+        let (iridescence, iridescence_ior, iridescence_thickness) =
+            if let Some(iridescence) = extensions.and_then(|e| e.iridescence.as_ref()) {
+                let factor = iridescence.factor;
+                let weight = if let Some(id) = &iridescence.texture {
+                    import_gltf_image(gltf, gltf_data, id, |pixel| [pixel[0] * factor; 4])?
+                } else {
+                    Texture::Scalar(factor)
+                };
+                let min = iridescence.thickness_minimum;
+                let max = iridescence.thickness_maximum;
+                let thickness = if let Some(id) = &iridescence.thickness_texture {
+                    import_gltf_image(gltf, gltf_data, id, |pixel| {
+                        [min + (max - min) * pixel[1]; 4]
+                    })?
+                } else {
+                    Texture::Scalar(max)
+                };
+                (weight, Texture::Scalar(iridescence.ior), thickness)
+            } else {
+                (
+                    Texture::Scalar(0.0),
+                    Texture::Scalar(1.3),
+                    Texture::Scalar(400.0),
+                )
+            };
+        let iridescence_index = textures.len() as u32;
+        let iridescence_ior_index = textures.len() as u32 + 1;
+        let iridescence_thickness_index = textures.len() as u32 + 2;
+        textures.push(iridescence);
+        textures.push(iridescence_ior);
+        textures.push(iridescence_thickness);
+        (
+            iridescence_index,
+            iridescence_ior_index,
+            iridescence_thickness_index,
+        )
+    };
+
+    // Subsurface color & subsurface radius. Diffuse transmission turns the
+    // material into a subsurface material, the volume extension gives the
+    // distance light travels inside it.
+    let (model, subsurface_color, subsurface_radius) = {
+        let extensions = gltf_material.extensions.as_ref();
+        let diffuse_transmission = extensions.and_then(|e| e.diffuse_transmission.as_ref());
+        let volume = extensions.and_then(|e| e.volume.as_ref());
+        let model = match diffuse_transmission {
+            Some(dt) if dt.diffuse_transmission_factor > 0.0 => MaterialModel::Subsurface,
+            _ => MaterialModel::Disney,
+        };
+        let subsurface_color = if let Some(dt) = diffuse_transmission {
+            let [r, g, b] = dt.diffuse_transmission_color_factor;
+            Texture::Vector4([r, g, b, 1.0])
+        } else {
+            Texture::Vector4(pbr.base_color_factor.0)
+        };
+        let subsurface_radius = match volume {
+            Some(volume) if volume.attenuation_distance.is_finite() => {
+                Texture::Vector3([volume.attenuation_distance; 3])
+            }
+            _ => Texture::Vector3([0.05, 0.01, 0.005]),
+        };
+        let subsurface_color_index = textures.len() as u32;
+        let subsurface_radius_index = textures.len() as u32 + 1;
+        textures.push(subsurface_color);
+        textures.push(subsurface_radius);
+        (model, subsurface_color_index, subsurface_radius_index)
+    };
+
+    // Blend mask. glTF has no blend materials, they are set up in the editor
+    // or the offline config, so both slots point at the material itself.
     let material_index = materials.len() as u32;
+    let blend = MaterialBlend {
+        materials: [material_index, material_index],
+        mask: BlendMask::Texture,
+    };
+    let blend_mask = {
+        let blend_mask_index = textures.len() as u32;
+        textures.push(Texture::Scalar(0.0));
+        blend_mask_index
+    };
+
+    // Append.
     materials.push(Material {
         name,
-        model: MaterialModel::Disney,
+        model,
         base_color,
         metallic,
         roughness,
@@ -469,11 +690,56 @@ fn import_gltf_material(
         specular_tint,
         sheen,
         sheen_tint,
+        sheen_color,
+        sheen_roughness,
+        iridescence,
+        iridescence_ior,
+        iridescence_thickness,
+        subsurface_color,
+        subsurface_radius,
+        blend_mask,
+        blend,
     });
 
     Ok(material_index)
 }
 
+fn import_gltf_image<F>(
+    gltf: &gltf::Gltf,
+    gltf_data: &[u8],
+    id: &gltf::Id,
+    pixel_fn: F,
+) -> Result<Texture>
+where
+    F: Fn([f32; 4]) -> [f32; 4],
+{
+    // Image.
+    let image = {
+        let texture = &gltf.textures[id.index];
+        let image = &gltf.images[texture.source];
+        let view = &gltf.buffer_views[image.buffer_view];
+        let bytes = &gltf_data[view.byte_offset..(view.byte_offset + view.byte_length)];
+        let format = imagelib::ImageFormat::Png;
+        let image = imagelib::load_from_memory_with_format(bytes, format)?;
+        image.into_rgba32f()
+    };
+
+    // Validate.
+    let width = image.width();
+    let height = image.height();
+    let components = 4;
+    ensure!(width > 0 && width.is_power_of_two());
+    ensure!(height > 0 && height.is_power_of_two());
+    let pixels = image.pixels().flat_map(|pixel| pixel_fn(pixel.0)).collect();
+
+    Ok(Texture::Image {
+        width,
+        height,
+        components,
+        pixels,
+    })
+}
+
 fn import_gltf_positions(
     gltf: &gltf::Gltf,
     gltf_primitive: &gltf::Primitive,
@@ -481,12 +747,9 @@ fn import_gltf_positions(
 ) -> Result<Vec<Point3>> {
     let acc = &gltf.accessors[gltf_primitive.attributes.position];
     ensure!(acc.ty == gltf::AccessorType::Vec3);
-    let view = &gltf.buffer_views[acc.buffer_view];
-    let offset = view.byte_offset;
-    let length = view.byte_length;
-    ensure!(length > 0);
-    ensure!(length % size_of::<Point3>() == 0);
-    Ok(bytemuck::cast_slice(&gltf_data[offset..(offset + length)]).to_vec())
+    ensure!(acc.component_type == gltf::Accessor::FLOAT);
+    ensure!(acc.count > 0);
+    acc.read(gltf, gltf_data)
 }
 
 fn import_gltf_tex_coords(
@@ -496,12 +759,8 @@ fn import_gltf_tex_coords(
 ) -> Result<Vec<Point2>> {
     let acc = &gltf.accessors[gltf_primitive.attributes.texcoord_0];
     ensure!(acc.ty == gltf::AccessorType::Vec2);
-    let view = &gltf.buffer_views[acc.buffer_view];
-    let offset = view.byte_offset;
-    let length = view.byte_length;
-    ensure!(length > 0);
-    ensure!(length % size_of::<Point2>() == 0);
-    Ok(bytemuck::cast_slice(&gltf_data[offset..(offset + length)]).to_vec())
+    ensure!(acc.component_type == gltf::Accessor::FLOAT);
+    acc.read(gltf, gltf_data)
 }
 
 fn import_gltf_normals(
@@ -511,12 +770,57 @@ fn import_gltf_normals(
 ) -> Result<Vec<Normal>> {
     let acc = &gltf.accessors[gltf_primitive.attributes.normal];
     ensure!(acc.ty == gltf::AccessorType::Vec3);
-    let view = &gltf.buffer_views[acc.buffer_view];
-    let offset = view.byte_offset;
-    let length = view.byte_length;
-    ensure!(length > 0);
-    ensure!(length % size_of::<Normal>() == 0);
-    Ok(bytemuck::cast_slice(&gltf_data[offset..(offset + length)]).to_vec())
+    ensure!(acc.component_type == gltf::Accessor::FLOAT);
+    acc.read(gltf, gltf_data)
+}
+
+fn import_gltf_colors(
+    gltf: &gltf::Gltf,
+    gltf_primitive: &gltf::Primitive,
+    gltf_data: &[u8],
+    vertex_count: usize,
+) -> Result<Vec<ColorRgb>> {
+    // Meshes without vertex colors are white, like in glTF.
+    let Some(color_0) = gltf_primitive.attributes.color_0 else {
+        return Ok(vec![ColorRgb::WHITE; vertex_count]);
+    };
+    let acc = &gltf.accessors[color_0];
+    ensure!(
+        matches!(acc.ty, gltf::AccessorType::Vec3 | gltf::AccessorType::Vec4),
+        "Vertex colors must be VEC3 or VEC4, got {:?}",
+        acc.ty
+    );
+    ensure!(acc.count == vertex_count);
+    let decode: fn(&[u8]) -> Vec<f32> = match acc.component_type {
+        gltf::Accessor::UNSIGNED_BYTE => {
+            ensure!(acc.normalized);
+            |element| element.iter().map(|&v| f32::from(v) / 255.0).collect()
+        }
+        gltf::Accessor::UNSIGNED_SHORT => {
+            ensure!(acc.normalized);
+            |element| {
+                element
+                    .chunks_exact(2)
+                    .map(|v| f32::from(u16::from_le_bytes([v[0], v[1]])) / 65535.0)
+                    .collect()
+            }
+        }
+        gltf::Accessor::FLOAT => |element| {
+            element
+                .chunks_exact(4)
+                .map(|v| f32::from_le_bytes([v[0], v[1], v[2], v[3]]))
+                .collect()
+        },
+        ty => bail!("Unsupported vertex color component type {ty}"),
+    };
+    Ok(acc
+        .elements(gltf, gltf_data)?
+        .into_iter()
+        .map(|element| {
+            let values = decode(element);
+            ColorRgb::new(values[0], values[1], values[2])
+        })
+        .collect())
 }
 
 fn import_gltf_triangles(
@@ -524,38 +828,22 @@ fn import_gltf_triangles(
     gltf_primitive: &gltf::Primitive,
     gltf_data: &[u8],
 ) -> Result<Vec<Vec3u>> {
-    const UNSIGNED_SHORT: u32 = 5123;
-    const UNSIGNED_INT: u32 = 5125;
-
     let acc = &gltf.accessors[gltf_primitive.indices];
     ensure!(acc.ty == gltf::AccessorType::Scalar);
-    let view = &gltf.buffer_views[acc.buffer_view];
-    let offset = view.byte_offset;
-    let length = view.byte_length;
-    ensure!(length > 0);
-
-    let slice_u8 = &gltf_data[offset..(offset + length)];
-    Ok(match acc.component_type {
-        UNSIGNED_SHORT => {
-            ensure!(length % 3 * size_of::<u16>() == 0);
-            let slice_u16: &[u16] = bytemuck::cast_slice(slice_u8);
-            slice_u16
-                .chunks_exact(3)
-                .map(|chunk| {
-                    vector![
-                        u32::from(chunk[0]),
-                        u32::from(chunk[1]),
-                        u32::from(chunk[2])
-                    ]
-                })
-                .collect::<Vec<_>>()
-        }
-        UNSIGNED_INT => {
-            ensure!(length % 3 * size_of::<u32>() == 0);
-            bytemuck::cast_slice(slice_u8).to_vec()
-        }
-        _ => unreachable!(),
-    })
+    ensure!(acc.count > 0 && acc.count % 3 == 0);
+    let indices = match acc.component_type {
+        gltf::Accessor::UNSIGNED_SHORT => acc
+            .read::<u16>(gltf, gltf_data)?
+            .into_iter()
+            .map(u32::from)
+            .collect(),
+        gltf::Accessor::UNSIGNED_INT => acc.read::<u32>(gltf, gltf_data)?,
+        ty => bail!("Unsupported index component type {ty}"),
+    };
+    Ok(indices
+        .chunks_exact(3)
+        .map(|chunk| vector![chunk[0], chunk[1], chunk[2]])
+        .collect())
 }
 
 //
@@ -599,7 +887,20 @@ impl MaterialModel {
     pub fn name(self) -> &'static str {
         match self {
             Self::Diffuse => "diffuse",
+            Self::OrenNayar => "oren-nayar",
             Self::Disney => "disney",
+            Self::Subsurface => "subsurface",
+            Self::Conductor(_) => "conductor",
+            Self::Blend => "blend",
+        }
+    }
+}
+
+impl BlendMask {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Texture => "texture",
+            Self::VertexColor => "vertex-color",
         }
     }
 }
@@ -620,6 +921,14 @@ impl DynamicMaterial {
             MaterialField::SpecularTint => self.specular_tint,
             MaterialField::Sheen => self.sheen,
             MaterialField::SheenTint => self.sheen_tint,
+            MaterialField::SheenColor => self.sheen_color,
+            MaterialField::SheenRoughness => self.sheen_roughness,
+            MaterialField::Iridescence => self.iridescence,
+            MaterialField::IridescenceIor => self.iridescence_ior,
+            MaterialField::IridescenceThickness => self.iridescence_thickness,
+            MaterialField::SubsurfaceColor => self.subsurface_color,
+            MaterialField::SubsurfaceRadius => self.subsurface_radius,
+            MaterialField::BlendMask => self.blend_mask,
         }
     }
 }
@@ -670,16 +979,116 @@ impl Texture {
 }
 
 impl DynamicTexture {
-    pub fn sample(&self) -> ColorRgba {
+    pub fn sample(&self, images: &[Arc<DynamicImage>], tex_coord: Point2) -> ColorRgba {
+        self.sample_decoded(images, tex_coord, false)
+    }
+
+    // Like `sample`, but sRGB images are decoded to linear values first.
+    pub fn sample_color(&self, images: &[Arc<DynamicImage>], tex_coord: Point2) -> ColorRgba {
+        self.sample_decoded(images, tex_coord, true)
+    }
+
+    fn sample_decoded(
+        &self,
+        images: &[Arc<DynamicImage>],
+        tex_coord: Point2,
+        color: bool,
+    ) -> ColorRgba {
         match self {
             Self::Scalar(s) => ColorRgba::new(*s, 0.0, 0.0, 0.0),
             Self::Vector2(v) => ColorRgba::new(v[0], v[1], 0.0, 0.0),
             Self::Vector3(v) => ColorRgba::new(v[0], v[1], v[2], 0.0),
             Self::Vector4(v) => ColorRgba::new(v[0], v[1], v[2], v[3]),
+            Self::Procedural(p) => p.sample(tex_coord),
+            Self::Image(i) => {
+                // Repeat the image, `Texture::sample` clamps to the edges.
+                let tex_coord = Point2::new(
+                    (i.uv_scale[0] * tex_coord.x).rem_euclid(1.0),
+                    (i.uv_scale[1] * tex_coord.y).rem_euclid(1.0),
+                );
+                let image = &images[i.image as usize];
+                let sample = image.texture.sample(tex_coord);
+                let sample = if color && image.srgb {
+                    ColorRgba::new(
+                        srgb_to_linear(sample.r()),
+                        srgb_to_linear(sample.g()),
+                        srgb_to_linear(sample.b()),
+                        sample.a(),
+                    )
+                } else {
+                    sample
+                };
+                let [r, g, b, a] = i.tint;
+                ColorRgba::new(
+                    r * sample.r(),
+                    g * sample.g(),
+                    b * sample.b(),
+                    a * sample.a(),
+                )
+            }
         }
     }
 }
 
+impl DynamicImage {
+    // PNG images are loaded as is and decoded by the color textures that use
+    // them, other textures read them as linear data. HDR and PFM images are
+    // linear.
+    pub fn load(path: &Path) -> Result<Self> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(str::to_ascii_lowercase);
+        let srgb = extension.as_deref() == Some("png");
+        let (width, height, components, pixels) = if srgb {
+            let image = imagelib::open(path)
+                .with_context(|| format!("Reading image: {}", path.display()))?
+                .into_rgba32f();
+            (image.width(), image.height(), 4, image.into_raw())
+        } else {
+            let (texels, (width, height)) = vz::hdr::read_from_file(path)?;
+            (width, height, 3, bytemuck::cast_slice(&texels).to_vec())
+        };
+
+        // `Texture::sample` indexes the pixels without checks.
+        ensure!(
+            width > 0 && height > 0,
+            "Image {} is empty, its size is {width}x{height}",
+            path.display()
+        );
+        let expected_len = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|len| len.checked_mul(components as usize));
+        ensure!(
+            expected_len == Some(pixels.len()),
+            "Image {} has {} values, expected {width}x{height} pixels with {components} channels",
+            path.display(),
+            pixels.len()
+        );
+        let texture = Texture::Image {
+            width,
+            height,
+            components,
+            pixels,
+        };
+        let name = path.file_name().map_or_else(
+            || path.display().to_string(),
+            |name| name.to_string_lossy().into(),
+        );
+        Ok(Self {
+            name,
+            texture,
+            srgb,
+        })
+    }
+}
+
+impl PartialEq for DynamicImage {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
 struct DisplayArray<'a, const LEN: usize>(&'a [f32; LEN]);
 
 impl<'a, const LEN: usize> std::fmt::Display for DisplayArray<'a, LEN> {
@@ -706,6 +1115,8 @@ impl std::fmt::Display for DynamicTexture {
                 DynamicTexture::Vector2(v) => write!(f, "{:.precision$}", DisplayArray(v)),
                 DynamicTexture::Vector3(v) => write!(f, "{:.precision$}", DisplayArray(v)),
                 DynamicTexture::Vector4(v) => write!(f, "{:.precision$}", DisplayArray(v)),
+                DynamicTexture::Procedural(p) => write!(f, "{}", p.pattern.name()),
+                DynamicTexture::Image(i) => write!(f, "image {}", i.image),
             }
         } else {
             match self {
@@ -713,6 +1124,8 @@ impl std::fmt::Display for DynamicTexture {
                 DynamicTexture::Vector2(v) => write!(f, "{}", DisplayArray(v)),
                 DynamicTexture::Vector3(v) => write!(f, "{}", DisplayArray(v)),
                 DynamicTexture::Vector4(v) => write!(f, "{}", DisplayArray(v)),
+                DynamicTexture::Procedural(p) => write!(f, "{}", p.pattern.name()),
+                DynamicTexture::Image(i) => write!(f, "image {}", i.image),
             }
         }
     }
@@ -726,16 +1139,35 @@ pub fn dynamic_sample(
 ) -> ColorRgba {
     let index = texture_index as usize;
     if dyn_scene.replaced_textures[index] {
-        dyn_scene.textures[index].sample()
+        dyn_scene.textures[index].sample(&dyn_scene.images, tex_coord)
+    } else {
+        scene.textures[index].sample(tex_coord)
+    }
+}
+
+// Like `dynamic_sample`, for base, sheen and subsurface colors. Colors from
+// glTF files are decoded when they are imported.
+pub fn dynamic_sample_color(
+    scene: &Scene,
+    dyn_scene: &DynamicScene,
+    texture_index: u32,
+    tex_coord: Point2,
+) -> ColorRgba {
+    let index = texture_index as usize;
+    if dyn_scene.replaced_textures[index] {
+        dyn_scene.textures[index].sample_color(&dyn_scene.images, tex_coord)
     } else {
         scene.textures[index].sample(tex_coord)
     }
 }
 
+// Colors of procedural and image textures, sampled at the center of the
+// texture.
 pub fn dynamic_try_sample(dyn_scene: &DynamicScene, texture_index: u32) -> Option<ColorRgba> {
     let index = texture_index as usize;
     if dyn_scene.replaced_textures[index] {
-        return Some(dyn_scene.textures[index].sample());
+        let center = Point2::new(0.5, 0.5);
+        return Some(dyn_scene.textures[index].sample_color(&dyn_scene.images, center));
     }
     None
 }
@@ -745,6 +1177,24 @@ pub fn dynamic_model(dyn_scene: &DynamicScene, material_index: u32) -> MaterialM
     dyn_scene.materials[index].model
 }
 
+pub fn mesh_by_name(scene: &Scene, name: &str) -> Option<u32> {
+    use itertools::Itertools;
+    scene
+        .meshes
+        .iter()
+        .find_position(|m| m.name == name)
+        .map(|(mesh, _)| mesh as u32)
+}
+
+pub fn material_by_name(scene: &Scene, name: &str) -> Option<u32> {
+    use itertools::Itertools;
+    scene
+        .materials
+        .iter()
+        .find_position(|m| m.name == name)
+        .map(|(material, _)| material as u32)
+}
+
 pub fn dynamic_material_by_name(
     scene: &Scene,
     dyn_scene: &DynamicScene,
@@ -757,3 +1207,132 @@ pub fn dynamic_material_by_name(
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gltf_vertex_colors() {
+        // Bytes 0..20 hold two vertices with a float and an RGBA8 color each,
+        // interleaved with a stride of 8. Bytes 20..44 hold two RGB float
+        // colors.
+        let json = r#"{
+            "accessors": [
+                {"bufferView": 0, "byteOffset": 4, "componentType": 5121, "count": 2,
+                 "type": "VEC4", "normalized": true},
+                {"bufferView": 1, "componentType": 5126, "count": 2, "type": "VEC3"}
+            ],
+            "asset": {"copyright": "", "generator": "", "version": "2.0"},
+            "bufferViews": [
+                {"buffer": 0, "byteOffset": 4, "byteLength": 16, "byteStride": 8},
+                {"buffer": 0, "byteOffset": 20, "byteLength": 24}
+            ],
+            "buffers": [{"byteLength": 44}],
+            "cameras": [],
+            "images": [],
+            "materials": [],
+            "meshes": [],
+            "nodes": [],
+            "samplers": [],
+            "scene": 0,
+            "scenes": [],
+            "textures": []
+        }"#;
+        let gltf: gltf::Gltf = serde_json::from_str(json).unwrap();
+        let mut data = vec![0_u8; 4];
+        for rgba in [[255, 0, 51, 255], [0, 102, 255, 0]] {
+            data.extend(1.0_f32.to_le_bytes());
+            data.extend(rgba);
+        }
+        for value in [0.1_f32, 0.2, 0.3, 0.4, 0.5, 0.6] {
+            data.extend(value.to_le_bytes());
+        }
+        let primitive = |color_0| gltf::Primitive {
+            attributes: gltf::Attributes {
+                position: 0,
+                texcoord_0: 0,
+                normal: 0,
+                color_0,
+            },
+            indices: 0,
+            material: 0,
+        };
+
+        let colors = import_gltf_colors(&gltf, &primitive(Some(0)), &data, 2).unwrap();
+        for (color, expected) in colors.iter().zip([[1.0, 0.0, 0.2], [0.0, 0.4, 1.0]]) {
+            for (value, expected) in [color.r(), color.g(), color.b()].into_iter().zip(expected) {
+                assert_abs_diff_eq!(value, expected);
+            }
+        }
+        let colors = import_gltf_colors(&gltf, &primitive(Some(1)), &data, 2).unwrap();
+        assert_abs_diff_eq!(colors[1].b(), 0.6);
+        let colors = import_gltf_colors(&gltf, &primitive(None), &data, 3).unwrap();
+        assert_eq!(colors, [ColorRgb::WHITE; 3]);
+
+        // Vertex counts must match.
+        assert!(import_gltf_colors(&gltf, &primitive(Some(0)), &data, 3).is_err());
+    }
+
+    #[test]
+    fn image_texture() {
+        // A 2x2 image with a different color in each quadrant.
+        let dir = std::env::temp_dir().join(format!("raydiance-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("quadrants.png");
+        let quadrants = [
+            [255, 0, 0, 255],
+            [0, 255, 0, 255],
+            [0, 0, 255, 255],
+            [255, 128, 255, 255],
+        ];
+        let mut image = imagelib::RgbaImage::new(2, 2);
+        for (pixel, color) in image.pixels_mut().zip(quadrants) {
+            *pixel = imagelib::Rgba(color);
+        }
+        image.save(&path).unwrap();
+        let image = DynamicImage::load(&path).unwrap();
+        assert_eq!(image.name, "quadrants.png");
+        assert!(matches!(
+            image.texture,
+            Texture::Image {
+                width: 2,
+                height: 2,
+                components: 4,
+                ..
+            }
+        ));
+
+        // The texture repeats and is tinted.
+        let images = [Arc::new(image)];
+        let texture = DynamicTexture::Image(ImageTexture {
+            image: 0,
+            tint: [0.5, 1.0, 1.0, 1.0],
+            uv_scale: [2.0, 2.0],
+        });
+        for (tex_coord, expected) in [
+            (Point2::new(0.1, 0.1), [0.5, 0.0, 0.0]),
+            (Point2::new(0.4, 0.1), [0.0, 1.0, 0.0]),
+            (Point2::new(0.1, 0.4), [0.0, 0.0, 1.0]),
+            (Point2::new(0.6, 0.6), [0.5, 0.0, 0.0]),
+            (Point2::new(-0.1, 0.9), [0.5, 128.0 / 255.0, 1.0]),
+        ] {
+            let color = texture.sample(&images, tex_coord);
+            for (value, expected) in [color.r(), color.g(), color.b()].into_iter().zip(expected) {
+                assert_abs_diff_eq!(value, expected);
+            }
+        }
+
+        // Color textures decode sRGB before the tint.
+        let color = texture.sample_color(&images, Point2::new(0.9, 0.9));
+        assert_abs_diff_eq!(color.r(), 0.5);
+        assert_abs_diff_eq!(color.g(), 0.215_861, epsilon = 1e-5);
+        assert_abs_diff_eq!(color.b(), 1.0);
+
+        // Broken files are errors, not panics.
+        let path = dir.join("broken.pfm");
+        std::fs::write(&path, b"PF\n0 0\n-1.0\n").unwrap();
+        assert!(DynamicImage::load(&path).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
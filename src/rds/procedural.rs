@@ -0,0 +1,281 @@
+use super::*;
+
+// Procedural textures are evaluated from texture coordinates when sampled, so
+// they have no resolution and every parameter can be animated. Each pattern
+// produces a value between 0 and 1, which interpolates between `low` and
+// `high`. Scalar fields only read the first component.
+
+//
+// Sources:
+//
+// Improving Noise
+// Ken Perlin, 2002
+// https://mrl.cs.nyu.edu/~perlin/paper445.pdf
+//
+// A Cellular Texture Basis Function
+// Steven Worley, 1996
+// https://doi.org/10.1145/237170.237267
+//
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ProceduralTexture {
+    pub pattern: ProceduralPattern,
+    // Pattern repetitions per unit of texture coordinates.
+    pub scale: f32,
+    pub offset: [f32; 2],
+    pub low: [f32; 4],
+    pub high: [f32; 4],
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ProceduralPattern {
+    Checkerboard,
+    // Ramp along the direction given by the angle, repeating once per unit.
+    Gradient {
+        angle_deg: f32,
+    },
+    // Fractal sum of gradient noise. The last octave is faded in by the
+    // fractional part of `octaves`, so it can be animated smoothly.
+    Noise {
+        octaves: f32,
+        lacunarity: f32,
+        gain: f32,
+    },
+    // Distance to the closest cell point, which is moved off the cell center
+    // by up to `jitter`.
+    Voronoi {
+        jitter: f32,
+    },
+    // Lines at integer texture coordinates, `line_width` is relative to a cell.
+    UvGrid {
+        line_width: f32,
+    },
+}
+
+impl ProceduralTexture {
+    pub fn sample(&self, tex_coord: Point2) -> ColorRgba {
+        let x = self.scale * (tex_coord.x + self.offset[0]);
+        let y = self.scale * (tex_coord.y + self.offset[1]);
+        let t = self.pattern.value(x, y).clamp(0.0, 1.0);
+        let [r, g, b, a] = lerp_array(self.low, self.high, t);
+        ColorRgba::new(r, g, b, a)
+    }
+}
+
+impl ProceduralPattern {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Checkerboard => "checkerboard",
+            Self::Gradient { .. } => "gradient",
+            Self::Noise { .. } => "noise",
+            Self::Voronoi { .. } => "voronoi",
+            Self::UvGrid { .. } => "uv-grid",
+        }
+    }
+
+    fn value(self, x: f32, y: f32) -> f32 {
+        match self {
+            Self::Checkerboard => {
+                let parity = (x.floor() as i32 + y.floor() as i32) & 1;
+                parity as f32
+            }
+            Self::Gradient { angle_deg } => {
+                let (sin, cos) = angle_deg.to_radians().sin_cos();
+                (cos * x + sin * y).rem_euclid(1.0)
+            }
+            Self::Noise {
+                octaves,
+                lacunarity,
+                gain,
+            } => {
+                let mut sum = 0.0;
+                let mut total_amplitude = 0.0;
+                let mut amplitude = 1.0;
+                let mut frequency = 1.0;
+                let mut octave = 0.0;
+                while octave < octaves {
+                    let weight = amplitude * (octaves - octave).min(1.0);
+                    sum += weight * gradient_noise(frequency * x, frequency * y);
+                    total_amplitude += weight;
+                    amplitude *= gain;
+                    frequency *= lacunarity;
+                    octave += 1.0;
+                }
+                if total_amplitude > 0.0 {
+                    0.5 + 0.5 * sum / total_amplitude
+                } else {
+                    0.5
+                }
+            }
+            Self::Voronoi { jitter } => {
+                let (cx, cy) = (x.floor() as i32, y.floor() as i32);
+                let mut closest = f32::MAX;
+                for j in -1..=1 {
+                    for i in -1..=1 {
+                        let h = hash(cx + i, cy + j);
+                        let jx = (h & 0xffff) as f32 / 65535.0 - 0.5;
+                        let jy = (h >> 16) as f32 / 65535.0 - 0.5;
+                        let px = (cx + i) as f32 + 0.5 + jitter * jx;
+                        let py = (cy + j) as f32 + 0.5 + jitter * jy;
+                        closest = closest.min((px - x).hypot(py - y));
+                    }
+                }
+                closest
+            }
+            Self::UvGrid { line_width } => {
+                let distance = |v: f32| {
+                    let f = v.rem_euclid(1.0);
+                    f.min(1.0 - f)
+                };
+                let d = distance(x).min(distance(y));
+                if d < 0.5 * line_width {
+                    1.0
+                } else {
+                    0.0
+                }
+            }
+        }
+    }
+}
+
+// Integer hash of a lattice point.
+fn hash(x: i32, y: i32) -> u32 {
+    let mut h = (x as u32).wrapping_mul(0x8da6_b343) ^ (y as u32).wrapping_mul(0xd816_3841);
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7feb_352d);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846c_a68b);
+    h ^= h >> 16;
+    h
+}
+
+// 2D gradient noise in about [-1, 1], with gradients picked from 8 directions
+// and the quintic fade curve from "Improving Noise".
+fn gradient_noise(x: f32, y: f32) -> f32 {
+    const D: f32 = std::f32::consts::FRAC_1_SQRT_2;
+    const GRADIENTS: [(f32, f32); 8] = [
+        (1.0, 0.0),
+        (D, D),
+        (0.0, 1.0),
+        (-D, D),
+        (-1.0, 0.0),
+        (-D, -D),
+        (0.0, -1.0),
+        (D, -D),
+    ];
+
+    let fade = |t: f32| t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+    let (ix, iy) = (x.floor(), y.floor());
+    let (fx, fy) = (x - ix, y - iy);
+    let (ix, iy) = (ix as i32, iy as i32);
+    let corner = |i: i32, j: i32| {
+        let (gx, gy) = GRADIENTS[(hash(ix + i, iy + j) & 7) as usize];
+        gx * (fx - i as f32) + gy * (fy - j as f32)
+    };
+    let (u, v) = (fade(fx), fade(fy));
+    let bottom = lerp_scalar(corner(0, 0), corner(1, 0), u);
+    let top = lerp_scalar(corner(0, 1), corner(1, 1), u);
+    // The largest value of 2D gradient noise is 1/sqrt(2).
+    std::f32::consts::SQRT_2 * lerp_scalar(bottom, top, v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gradient_noise_range() {
+        let mut min = f32::MAX;
+        let mut max = f32::MIN;
+        for j in 0..256 {
+            for i in 0..256 {
+                let n = gradient_noise(0.173 * i as f32 - 11.0, 0.131 * j as f32 - 7.0);
+                min = min.min(n);
+                max = max.max(n);
+            }
+        }
+        assert!((-1.0..-0.5).contains(&min), "min={min}");
+        assert!((0.5..=1.0).contains(&max), "max={max}");
+        assert_abs_diff_eq!(gradient_noise(3.0, -2.0), 0.0);
+    }
+
+    #[test]
+    fn patterns() {
+        let value = |pattern: ProceduralPattern, x, y| pattern.value(x, y);
+
+        // Checkerboard alternates between cells, also across zero.
+        let checkerboard = ProceduralPattern::Checkerboard;
+        assert_abs_diff_eq!(value(checkerboard, 0.5, 0.5), 0.0);
+        assert_abs_diff_eq!(value(checkerboard, 1.5, 0.5), 1.0);
+        assert_abs_diff_eq!(value(checkerboard, 1.5, 1.5), 0.0);
+        assert_abs_diff_eq!(value(checkerboard, -0.5, 0.5), 1.0);
+
+        // Gradient ramps along its direction and repeats.
+        let along_x = ProceduralPattern::Gradient { angle_deg: 0.0 };
+        let along_y = ProceduralPattern::Gradient { angle_deg: 90.0 };
+        assert_abs_diff_eq!(value(along_x, 0.25, 7.0), 0.25, epsilon = 1e-5);
+        assert_abs_diff_eq!(value(along_x, 1.75, 7.0), 0.75, epsilon = 1e-5);
+        assert_abs_diff_eq!(value(along_y, 7.0, 0.25), 0.25, epsilon = 1e-5);
+
+        // Without jitter, the cell points are at the cell centers.
+        let worley = ProceduralPattern::Voronoi { jitter: 0.0 };
+        assert_abs_diff_eq!(value(worley, 2.5, -3.5), 0.0);
+        assert_abs_diff_eq!(value(worley, 1.0, 0.5), 0.5);
+        assert_abs_diff_eq!(value(worley, 1.0, 1.0), 0.5_f32.sqrt(), epsilon = 1e-6);
+
+        // With jitter, the distance is zero at the jittered point of a cell.
+        let h = hash(3, 4);
+        let x = 3.5 + ((h & 0xffff) as f32 / 65535.0 - 0.5);
+        let y = 4.5 + ((h >> 16) as f32 / 65535.0 - 0.5);
+        let worley = ProceduralPattern::Voronoi { jitter: 1.0 };
+        assert_abs_diff_eq!(value(worley, x, y), 0.0, epsilon = 1e-5);
+
+        // Patterns are interpolated between the low and the high values.
+        let texture = ProceduralTexture {
+            pattern: checkerboard,
+            scale: 2.0,
+            offset: [0.0, 0.0],
+            low: [0.0; 4],
+            high: [1.0, 2.0, 3.0, 4.0],
+        };
+        let color = texture.sample(Point2::new(0.75, 0.25));
+        assert_abs_diff_eq!(color.a(), 4.0);
+    }
+
+    #[test]
+    fn patterns_deterministic() {
+        // Patterns only depend on the coordinates, so renders and frames of
+        // animations match between runs and threads.
+        assert_ne!(hash(1, 0), hash(0, 1));
+        let patterns = [
+            ProceduralPattern::Checkerboard,
+            ProceduralPattern::Gradient { angle_deg: 30.0 },
+            ProceduralPattern::Noise {
+                octaves: 4.5,
+                lacunarity: 2.0,
+                gain: 0.5,
+            },
+            ProceduralPattern::Voronoi { jitter: 0.8 },
+            ProceduralPattern::UvGrid { line_width: 0.1 },
+        ];
+        let values = move |x_offset: f32| {
+            (0..64)
+                .flat_map(|j| {
+                    (0..64).flat_map(move |i| {
+                        let (x, y) = (0.37 * i as f32 - 9.0 + x_offset, 0.29 * j as f32 - 5.0);
+                        patterns.map(|pattern| pattern.value(x, y).to_bits())
+                    })
+                })
+                .collect::<Vec<_>>()
+        };
+        let reference = values(0.0);
+        let handles = (0..4)
+            .map(|_| std::thread::spawn(move || values(0.0)))
+            .collect::<Vec<_>>();
+        for handle in handles {
+            assert_eq!(handle.join().unwrap(), reference);
+        }
+        assert_ne!(values(0.5), reference);
+    }
+}
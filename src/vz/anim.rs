@@ -20,6 +20,84 @@ impl Tweenable for ColorRgb {
     }
 }
 
+impl Tweenable for cpupt::MediumParams {
+    fn tween(&self, rhs: &Self, time: f32) -> Self {
+        Self {
+            absorption: self.absorption.tween(&rhs.absorption, time),
+            scattering: self.scattering.tween(&rhs.scattering, time),
+            density: self.density.tween(&rhs.density, time),
+            anisotropy: self.anisotropy.tween(&rhs.anisotropy, time),
+        }
+    }
+}
+
+impl Tweenable for cpupt::WhiteBalance {
+    fn tween(&self, rhs: &Self, time: f32) -> Self {
+        Self {
+            temperature: self.temperature.tween(&rhs.temperature, time),
+            tint: self.tint.tween(&rhs.tint, time),
+        }
+    }
+}
+
+// The image itself is not tweened, only its parameters.
+impl Tweenable for rds::ImageTexture {
+    fn tween(&self, rhs: &Self, time: f32) -> Self {
+        Self {
+            image: self.image,
+            tint: lerp_array(self.tint, rhs.tint, time),
+            uv_scale: lerp_array(self.uv_scale, rhs.uv_scale, time),
+        }
+    }
+}
+
+impl Tweenable for rds::ProceduralTexture {
+    fn tween(&self, rhs: &Self, time: f32) -> Self {
+        Self {
+            pattern: self.pattern.tween(&rhs.pattern, time),
+            scale: self.scale.tween(&rhs.scale, time),
+            offset: lerp_array(self.offset, rhs.offset, time),
+            low: lerp_array(self.low, rhs.low, time),
+            high: lerp_array(self.high, rhs.high, time),
+        }
+    }
+}
+
+impl Tweenable for rds::ProceduralPattern {
+    fn tween(&self, rhs: &Self, time: f32) -> Self {
+        use rds::ProceduralPattern::*;
+        match (*self, *rhs) {
+            (Checkerboard, Checkerboard) => Checkerboard,
+            (Gradient { angle_deg: lhs }, Gradient { angle_deg: rhs }) => Gradient {
+                angle_deg: lhs.tween(&rhs, time),
+            },
+            (
+                Noise {
+                    octaves,
+                    lacunarity,
+                    gain,
+                },
+                Noise {
+                    octaves: rhs_octaves,
+                    lacunarity: rhs_lacunarity,
+                    gain: rhs_gain,
+                },
+            ) => Noise {
+                octaves: octaves.tween(&rhs_octaves, time),
+                lacunarity: lacunarity.tween(&rhs_lacunarity, time),
+                gain: gain.tween(&rhs_gain, time),
+            },
+            (Voronoi { jitter: lhs }, Voronoi { jitter: rhs }) => Voronoi {
+                jitter: lhs.tween(&rhs, time),
+            },
+            (UvGrid { line_width: lhs }, UvGrid { line_width: rhs }) => UvGrid {
+                line_width: lhs.tween(&rhs, time),
+            },
+            _ => panic!("rds::ProceduralPatterns must have the same enum variant, got {self:?} and {rhs:?} instead"),
+        }
+    }
+}
+
 impl Tweenable for rds::DynamicTexture {
     fn tween(&self, rhs: &Self, time: f32) -> Self {
         match (*self, *rhs) {
@@ -27,6 +105,8 @@ impl Tweenable for rds::DynamicTexture {
             (Self::Vector2(lhs), Self::Vector2(rhs)) => Self::Vector2(lerp_array(lhs, rhs, time)),
             (Self::Vector3(lhs), Self::Vector3(rhs)) => Self::Vector3(lerp_array(lhs, rhs, time)),
             (Self::Vector4(lhs), Self::Vector4(rhs)) => Self::Vector4(lerp_array(lhs, rhs, time)),
+            (Self::Procedural(lhs), Self::Procedural(rhs)) => Self::Procedural(lhs.tween(&rhs, time)),
+            (Self::Image(lhs), Self::Image(rhs)) => Self::Image(lhs.tween(&rhs, time)),
             _ => panic!("rds::DynamicTextures must have the same enum variant, got {self:?} and {rhs:?} instead"),
         }
     }
@@ -0,0 +1,887 @@
+use super::*;
+
+//
+// Sources:
+//
+// Radiance File Formats
+// Greg Ward
+// https://radsite.lbl.gov/radiance/refer/filefmts.pdf
+//
+// PFM Portable FloatMap Image Format
+// Paul Debevec
+// https://www.pauldebevec.com/Research/HDR/PFM/
+//
+// OpenEXR File Layout
+// Academy Software Foundation
+// https://openexr.com/en/latest/OpenEXRFileLayout.html
+//
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HdrFormat {
+    Exr(ExrPixelType, ExrCompression),
+    Pfm,
+    Rgbe,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExrPixelType {
+    Half,
+    Float,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExrCompression {
+    None,
+    Zip,
+}
+
+impl HdrFormat {
+    pub const ALL: [Self; 6] = [
+        Self::Exr(ExrPixelType::Half, ExrCompression::Zip),
+        Self::Exr(ExrPixelType::Half, ExrCompression::None),
+        Self::Exr(ExrPixelType::Float, ExrCompression::Zip),
+        Self::Exr(ExrPixelType::Float, ExrCompression::None),
+        Self::Pfm,
+        Self::Rgbe,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Exr(ExrPixelType::Half, ExrCompression::Zip) => "exr-half-zip",
+            Self::Exr(ExrPixelType::Half, ExrCompression::None) => "exr-half",
+            Self::Exr(ExrPixelType::Float, ExrCompression::Zip) => "exr-float-zip",
+            Self::Exr(ExrPixelType::Float, ExrCompression::None) => "exr-float",
+            Self::Pfm => "pfm",
+            Self::Rgbe => "hdr",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Exr(..) => "exr",
+            Self::Pfm => "pfm",
+            Self::Rgbe => "hdr",
+        }
+    }
+}
+
+// Reads a high dynamic range image. The format is chosen by the file
+// extension. Rows are returned from top to bottom.
+pub fn read_from_file(path: &Path) -> Result<(Vec<ColorRgb>, (u32, u32))> {
+    let bytes =
+        std::fs::read(path).with_context(|| format!("Reading HDR image: {}", path.display()))?;
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_ascii_lowercase);
+    match extension.as_deref() {
+        Some("hdr") => read_rgbe(&bytes),
+        Some("pfm") => read_pfm(&bytes),
+        _ => bail!("Unsupported HDR image format: {}", path.display()),
+    }
+}
+
+// Writes a high dynamic range image. Rows are given from top to bottom. EXR
+// keeps the color space of the texels, the other formats have no way to tag it
+// and are converted to linear sRGB.
+pub fn write_to_file(
+    path: &Path,
+    format: HdrFormat,
+    texels: &[ColorRgb],
+    size: (u32, u32),
+    color_space: ColorSpace,
+) -> Result<()> {
+    ensure!(
+        texels.len() == (size.0 * size.1) as usize,
+        "Expected {} texels, got {} instead",
+        size.0 * size.1,
+        texels.len()
+    );
+    let srgb = || {
+        let srgb_from_texels = ColorSpace::conversion(color_space, ColorSpace::Srgb);
+        texels
+            .iter()
+            .map(|texel| texel.transform(&srgb_from_texels))
+            .collect::<Vec<_>>()
+    };
+    let bytes = match format {
+        HdrFormat::Exr(pixel_type, compression) => {
+            write_exr(texels, size, color_space, pixel_type, compression)?
+        }
+        HdrFormat::Pfm => write_pfm(&srgb(), size),
+        HdrFormat::Rgbe => write_rgbe(&srgb(), size),
+    };
+    std::fs::write(path, bytes).with_context(|| format!("Writing HDR image: {}", path.display()))
+}
+
+//
+// Radiance RGBE
+//
+
+pub fn read_rgbe(bytes: &[u8]) -> Result<(Vec<ColorRgb>, (u32, u32))> {
+    let mut cursor = bytes;
+
+    // Header.
+    let magic = read_line(&mut cursor)?;
+    ensure!(
+        magic.starts_with("#?"),
+        "Missing Radiance signature, got {magic:?} instead"
+    );
+    loop {
+        let line = read_line(&mut cursor)?;
+        if line.is_empty() {
+            break;
+        }
+        if let Some(format) = line.strip_prefix("FORMAT=") {
+            ensure!(
+                format == "32-bit_rle_rgbe",
+                "Unsupported Radiance pixel format: {format}"
+            );
+        }
+    }
+
+    // Resolution. Only the standard orientations are supported, where rows
+    // run along X.
+    let resolution = read_line(&mut cursor)?;
+    let tokens = resolution.split_whitespace().collect::<Vec<_>>();
+    let [y_sign, h, "+X", w] = tokens[..] else {
+        bail!("Unsupported Radiance resolution: {resolution}");
+    };
+    let flip_y = match y_sign {
+        "-Y" => false,
+        "+Y" => true,
+        _ => bail!("Unsupported Radiance resolution: {resolution}"),
+    };
+    let w = w.parse::<u32>()?;
+    let h = h.parse::<u32>()?;
+    ensure!(
+        w.checked_mul(h).is_some(),
+        "Radiance resolution is too large: {resolution}"
+    );
+
+    // Scanlines. The header is untrusted, so memory grows with the decoded
+    // texels rather than with the resolution.
+    let mut texels = Vec::with_capacity(((w * h) as usize).min(cursor.len()));
+    let mut scanline = vec![];
+    for _ in 0..h {
+        scanline.clear();
+        read_rgbe_scanline(&mut cursor, &mut scanline, w as usize)?;
+        texels.extend(scanline.iter().map(|&rgbe| rgbe_to_color(rgbe)));
+    }
+    if flip_y {
+        flip_rows(&mut texels, (w, h));
+    }
+
+    Ok((texels, (w, h)))
+}
+
+fn read_rgbe_scanline(cursor: &mut &[u8], scanline: &mut Vec<[u8; 4]>, w: usize) -> Result<()> {
+    // New run length encoding stores each component separately.
+    if (8..0x8000).contains(&w) && cursor.len() >= 4 && cursor[0] == 2 && cursor[1] == 2 {
+        let encoded_w = usize::from(cursor[2]) << 8 | usize::from(cursor[3]);
+        ensure!(
+            encoded_w == w,
+            "Scanline width mismatch, expected {w}, got {encoded_w} instead"
+        );
+        *cursor = &cursor[4..];
+        scanline.resize(w, [0; 4]);
+        for component in 0..4 {
+            let mut x = 0;
+            while x < w {
+                let count = usize::from(read_byte(cursor)?);
+                if count > 128 {
+                    let count = count - 128;
+                    ensure!(x + count <= w, "Run overflows scanline");
+                    let value = read_byte(cursor)?;
+                    for texel in &mut scanline[x..x + count] {
+                        texel[component] = value;
+                    }
+                    x += count;
+                } else {
+                    ensure!(count > 0 && x + count <= w, "Invalid run length {count}");
+                    for texel in &mut scanline[x..x + count] {
+                        texel[component] = read_byte(cursor)?;
+                    }
+                    x += count;
+                }
+            }
+        }
+        return Ok(());
+    }
+
+    // Flat texels, possibly with the old run length encoding, where (1,1,1,n)
+    // repeats the previous texel.
+    let mut shift = 0;
+    while scanline.len() < w {
+        ensure!(cursor.len() >= 4, "Unexpected end of file");
+        let rgbe = [cursor[0], cursor[1], cursor[2], cursor[3]];
+        *cursor = &cursor[4..];
+        if let (&[1, 1, 1, n], Some(&previous)) = (&rgbe, scanline.last()) {
+            ensure!(shift < usize::BITS, "Run length overflows");
+            let count = usize::from(n) << shift;
+            ensure!(scanline.len() + count <= w, "Run overflows scanline");
+            scanline.extend(std::iter::repeat(previous).take(count));
+            shift += 8;
+        } else {
+            scanline.push(rgbe);
+            shift = 0;
+        }
+    }
+    Ok(())
+}
+
+fn rgbe_to_color([r, g, b, e]: [u8; 4]) -> ColorRgb {
+    if e == 0 {
+        return ColorRgb::BLACK;
+    }
+    let scale = 2.0_f32.powi(i32::from(e) - (128 + 8));
+    ColorRgb::new(
+        (f32::from(r) + 0.5) * scale,
+        (f32::from(g) + 0.5) * scale,
+        (f32::from(b) + 0.5) * scale,
+    )
+}
+
+fn color_to_rgbe(color: ColorRgb) -> [u8; 4] {
+    let max = color.max_element();
+    if max.is_nan() || max <= 1e-32 {
+        return [0; 4];
+    }
+    // Find the exponent, which puts the largest component in [128, 256).
+    let mut exponent = max.log2().floor() as i32 + 1;
+    if max * 2.0_f32.powi(8 - exponent) >= 256.0 {
+        exponent += 1;
+    }
+    if max * 2.0_f32.powi(8 - exponent) < 128.0 {
+        exponent -= 1;
+    }
+    let exponent = exponent.clamp(-128, 127);
+    let scale = 2.0_f32.powi(8 - exponent);
+    [
+        (color.r() * scale) as u8,
+        (color.g() * scale) as u8,
+        (color.b() * scale) as u8,
+        (exponent + 128) as u8,
+    ]
+}
+
+pub fn write_rgbe(texels: &[ColorRgb], (w, h): (u32, u32)) -> Vec<u8> {
+    let mut bytes = format!("#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {h} +X {w}\n").into_bytes();
+    let rgbe = texels.iter().map(|&c| color_to_rgbe(c)).collect::<Vec<_>>();
+    for scanline in rgbe.chunks_exact(w as usize) {
+        // The new run length encoding only supports these widths.
+        if !(8..0x8000).contains(&w) {
+            bytes.extend(scanline.iter().flatten());
+            continue;
+        }
+        bytes.extend([2, 2, (w >> 8) as u8, (w & 0xff) as u8]);
+        for component in 0..4 {
+            let values = scanline.iter().map(|t| t[component]).collect::<Vec<_>>();
+            write_rgbe_runs(&mut bytes, &values);
+        }
+    }
+    bytes
+}
+
+// Runs of 4 or more equal values are stored as (128 + count, value), the rest
+// are dumped as (count, values...).
+fn write_rgbe_runs(bytes: &mut Vec<u8>, values: &[u8]) {
+    let run_length = |x: usize, max: usize| {
+        values[x..]
+            .iter()
+            .take(max)
+            .take_while(|&&v| v == values[x])
+            .count()
+    };
+    let mut x = 0;
+    while x < values.len() {
+        let run = run_length(x, 127);
+        if run >= 4 {
+            bytes.extend([128 + run as u8, values[x]]);
+            x += run;
+            continue;
+        }
+        let start = x;
+        while x < values.len() && x - start < 128 && run_length(x, 4) < 4 {
+            x += 1;
+        }
+        bytes.push((x - start) as u8);
+        bytes.extend(&values[start..x]);
+    }
+}
+
+//
+// Portable FloatMap
+//
+
+pub fn read_pfm(bytes: &[u8]) -> Result<(Vec<ColorRgb>, (u32, u32))> {
+    let mut cursor = bytes;
+
+    // Header. The tokens are separated by whitespace, and a single whitespace
+    // character separates the header from the texels.
+    let mut tokens = Vec::with_capacity(4);
+    while tokens.len() < 4 {
+        let start = cursor
+            .iter()
+            .position(|b| !b.is_ascii_whitespace())
+            .context("Unexpected end of file")?;
+        cursor = &cursor[start..];
+        let end = cursor
+            .iter()
+            .position(u8::is_ascii_whitespace)
+            .context("Unexpected end of file")?;
+        tokens.push(std::str::from_utf8(&cursor[..end])?.to_owned());
+        cursor = &cursor[end + 1..];
+    }
+    let channels = match tokens[0].as_str() {
+        "PF" => 3,
+        "Pf" => 1,
+        magic => bail!("Unsupported PFM signature: {magic:?}"),
+    };
+    let w = tokens[1].parse::<u32>()?;
+    let h = tokens[2].parse::<u32>()?;
+    let scale = tokens[3].parse::<f32>()?;
+    let little_endian = scale < 0.0;
+
+    // Texels.
+    let float_count = w
+        .checked_mul(h)
+        .and_then(|texel_count| (texel_count as usize).checked_mul(channels))
+        .with_context(|| format!("PFM resolution is too large: {w}x{h}"))?;
+    ensure!(
+        cursor.len() / 4 >= float_count,
+        "Unexpected end of file, expected {float_count} floats"
+    );
+    let floats = cursor[..4 * float_count]
+        .chunks_exact(4)
+        .map(|chunk| {
+            let bytes = [chunk[0], chunk[1], chunk[2], chunk[3]];
+            if little_endian {
+                f32::from_le_bytes(bytes)
+            } else {
+                f32::from_be_bytes(bytes)
+            }
+        })
+        .collect::<Vec<_>>();
+    let mut texels = floats
+        .chunks_exact(channels)
+        .map(|texel| {
+            if channels == 3 {
+                ColorRgb::new(texel[0], texel[1], texel[2])
+            } else {
+                ColorRgb::new(texel[0], texel[0], texel[0])
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // Rows are stored from bottom to top.
+    flip_rows(&mut texels, (w, h));
+
+    Ok((texels, (w, h)))
+}
+
+pub fn write_pfm(texels: &[ColorRgb], (w, h): (u32, u32)) -> Vec<u8> {
+    let mut bytes = format!("PF\n{w} {h}\n-1.0\n").into_bytes();
+
+    // Rows are stored from bottom to top, little endian.
+    for row in texels.chunks_exact(w as usize).rev() {
+        for texel in row {
+            for value in [texel.r(), texel.g(), texel.b()] {
+                bytes.extend(value.to_le_bytes());
+            }
+        }
+    }
+    bytes
+}
+
+//
+// OpenEXR
+//
+
+// Writes a single part scanline image with R, G and B channels.
+pub fn write_exr(
+    texels: &[ColorRgb],
+    size: (u32, u32),
+    color_space: ColorSpace,
+    pixel_type: ExrPixelType,
+    compression: ExrCompression,
+) -> Result<Vec<u8>> {
+    let channel = |name: &str, value: fn(&ColorRgb) -> f32| ExrChannel {
+        name: name.to_owned(),
+        samples: ExrSamples::Float(texels.iter().map(value).collect()),
+    };
+    let channels = [
+        channel("R", ColorRgb::r),
+        channel("G", ColorRgb::g),
+        channel("B", ColorRgb::b),
+    ];
+    write_exr_channels(&channels, size, color_space, pixel_type, compression)
+}
+
+// A channel with one sample per texel. Layers are expressed with dotted names,
+// such as "albedo.R".
+pub struct ExrChannel {
+    pub name: String,
+    pub samples: ExrSamples,
+}
+
+// Float samples are stored with the pixel type of the image, unsigned integers
+// always with 32 bits, so that ids survive half precision images.
+pub enum ExrSamples {
+    Float(Vec<f32>),
+    Uint(Vec<u32>),
+}
+
+// Writes a single part scanline image with any number of channels. The
+// chromaticities of the color space are stored in the header.
+pub fn write_exr_channels(
+    channels: &[ExrChannel],
+    (w, h): (u32, u32),
+    color_space: ColorSpace,
+    pixel_type: ExrPixelType,
+    compression: ExrCompression,
+) -> Result<Vec<u8>> {
+    use std::io::Write;
+
+    // Channels must be listed in alphabetical order.
+    let mut channels = channels.iter().collect::<Vec<_>>();
+    channels.sort_by(|a, b| a.name.cmp(&b.name));
+    // The data window is stored as an inclusive box of i32.
+    ensure!(
+        (1..=i32::MAX as u32).contains(&w) && (1..=i32::MAX as u32).contains(&h),
+        "Unsupported EXR resolution: {w}x{h}"
+    );
+    let texel_count = w
+        .checked_mul(h)
+        .with_context(|| format!("EXR resolution is too large: {w}x{h}"))?;
+    for channel in &channels {
+        let len = match &channel.samples {
+            ExrSamples::Float(samples) => samples.len(),
+            ExrSamples::Uint(samples) => samples.len(),
+        };
+        ensure!(
+            len == texel_count as usize,
+            "Channel {} has {len} samples, expected {texel_count}",
+            channel.name,
+        );
+    }
+
+    // Magic number and version 2, without any flags.
+    let mut bytes = vec![0x76, 0x2f, 0x31, 0x01, 2, 0, 0, 0];
+
+    // Header.
+    let attribute = |bytes: &mut Vec<u8>, name: &str, ty: &str, value: &[u8]| {
+        bytes.extend(name.as_bytes());
+        bytes.push(0);
+        bytes.extend(ty.as_bytes());
+        bytes.push(0);
+        bytes.extend((value.len() as i32).to_le_bytes());
+        bytes.extend(value);
+    };
+    let float_pixel_type_id = match pixel_type {
+        ExrPixelType::Half => 1_i32,
+        ExrPixelType::Float => 2_i32,
+    };
+    let mut channel_list = vec![];
+    for channel in &channels {
+        let pixel_type_id = match channel.samples {
+            ExrSamples::Float(_) => float_pixel_type_id,
+            ExrSamples::Uint(_) => 0_i32,
+        };
+        channel_list.extend(channel.name.as_bytes());
+        channel_list.push(0);
+        channel_list.extend(pixel_type_id.to_le_bytes());
+        // pLinear and reserved bytes, then x and y sampling.
+        channel_list.extend([0, 0, 0, 0]);
+        channel_list.extend(1_i32.to_le_bytes());
+        channel_list.extend(1_i32.to_le_bytes());
+    }
+    channel_list.push(0);
+    let (compression_id, lines_per_block) = match compression {
+        ExrCompression::None => (0_u8, 1),
+        ExrCompression::Zip => (3_u8, 16),
+    };
+    let window = [0, 0, w as i32 - 1, h as i32 - 1]
+        .into_iter()
+        .flat_map(i32::to_le_bytes)
+        .collect::<Vec<_>>();
+    let chromaticities = color_space
+        .chromaticities()
+        .into_iter()
+        .flatten()
+        .flat_map(f32::to_le_bytes)
+        .collect::<Vec<_>>();
+    attribute(&mut bytes, "channels", "chlist", &channel_list);
+    attribute(
+        &mut bytes,
+        "chromaticities",
+        "chromaticities",
+        &chromaticities,
+    );
+    attribute(&mut bytes, "compression", "compression", &[compression_id]);
+    attribute(&mut bytes, "dataWindow", "box2i", &window);
+    attribute(&mut bytes, "displayWindow", "box2i", &window);
+    attribute(&mut bytes, "lineOrder", "lineOrder", &[0]);
+    attribute(
+        &mut bytes,
+        "pixelAspectRatio",
+        "float",
+        &1.0_f32.to_le_bytes(),
+    );
+    attribute(&mut bytes, "screenWindowCenter", "v2f", &[0; 8]);
+    attribute(
+        &mut bytes,
+        "screenWindowWidth",
+        "float",
+        &1.0_f32.to_le_bytes(),
+    );
+    bytes.push(0);
+
+    // Blocks. Within a block, each scanline stores its channels one after
+    // another, in the same order as the channel list.
+    let mut blocks = vec![];
+    for block_start in (0..h as usize).step_by(lines_per_block) {
+        let block_end = (block_start + lines_per_block).min(h as usize);
+        let mut data = vec![];
+        for y in block_start..block_end {
+            let scanline = y * w as usize..(y + 1) * w as usize;
+            for channel in &channels {
+                match &channel.samples {
+                    ExrSamples::Float(samples) => {
+                        for &value in &samples[scanline.clone()] {
+                            match pixel_type {
+                                ExrPixelType::Half => data.extend(f16_bits(value).to_le_bytes()),
+                                ExrPixelType::Float => data.extend(value.to_le_bytes()),
+                            }
+                        }
+                    }
+                    ExrSamples::Uint(samples) => {
+                        for &value in &samples[scanline.clone()] {
+                            data.extend(value.to_le_bytes());
+                        }
+                    }
+                }
+            }
+        }
+        if compression == ExrCompression::Zip {
+            let mut encoder =
+                flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+            encoder.write_all(&zip_predict(&data))?;
+            let compressed = encoder.finish()?;
+            // Blocks that don't get smaller are stored uncompressed.
+            if compressed.len() < data.len() {
+                data = compressed;
+            }
+        }
+        blocks.push((block_start as i32, data));
+    }
+
+    // Offset table, followed by the blocks.
+    let mut offset = (bytes.len() + 8 * blocks.len()) as u64;
+    for (_, data) in &blocks {
+        bytes.extend(offset.to_le_bytes());
+        offset += 8 + data.len() as u64;
+    }
+    for (y, data) in blocks {
+        bytes.extend(y.to_le_bytes());
+        bytes.extend((data.len() as i32).to_le_bytes());
+        bytes.extend(data);
+    }
+
+    Ok(bytes)
+}
+
+// Splits the bytes into even and odd halves, then replaces them with their
+// differences, which makes them compress better.
+fn zip_predict(data: &[u8]) -> Vec<u8> {
+    let mut predicted = data
+        .iter()
+        .step_by(2)
+        .chain(data.iter().skip(1).step_by(2))
+        .copied()
+        .collect::<Vec<_>>();
+    let Some(&first) = predicted.first() else {
+        return predicted;
+    };
+    let mut previous = first;
+    for byte in &mut predicted[1..] {
+        let current = *byte;
+        *byte = current.wrapping_sub(previous).wrapping_add(128);
+        previous = current;
+    }
+    predicted
+}
+
+// Rounds to the nearest half float, ties to even.
+fn f16_bits(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x007f_ffff;
+
+    // Infinity and NaN.
+    if exponent == 0xff {
+        let nan = if mantissa == 0 { 0 } else { 0x0200 };
+        return sign | 0x7c00 | nan;
+    }
+
+    // Overflow to infinity.
+    let exponent = exponent - 127 + 15;
+    if exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+
+    // Subnormals and underflow to zero.
+    let round = |half: u32, remainder: u32, halfway: u32| {
+        half + u32::from(remainder > halfway || (remainder == halfway && half & 1 == 1))
+    };
+    if exponent <= 0 {
+        if exponent < -10 {
+            return sign;
+        }
+        let mantissa = mantissa | 0x0080_0000;
+        let shift = (14 - exponent) as u32;
+        let half = round(
+            mantissa >> shift,
+            mantissa & ((1 << shift) - 1),
+            1 << (shift - 1),
+        );
+        return sign | half as u16;
+    }
+
+    // Normals. A carry out of the mantissa correctly bumps the exponent.
+    let half = round(
+        ((exponent as u32) << 10) | (mantissa >> 13),
+        mantissa & 0x1fff,
+        0x1000,
+    );
+    sign | half as u16
+}
+
+//
+// Utilities
+//
+
+fn read_byte(cursor: &mut &[u8]) -> Result<u8> {
+    let (&byte, rest) = cursor.split_first().context("Unexpected end of file")?;
+    *cursor = rest;
+    Ok(byte)
+}
+
+fn read_line(cursor: &mut &[u8]) -> Result<String> {
+    let end = cursor
+        .iter()
+        .position(|&b| b == b'\n')
+        .context("Unexpected end of file")?;
+    let line = std::str::from_utf8(&cursor[..end])?.trim_end().to_owned();
+    *cursor = &cursor[end + 1..];
+    Ok(line)
+}
+
+fn flip_rows(texels: &mut [ColorRgb], (w, h): (u32, u32)) {
+    let w = w as usize;
+    let h = h as usize;
+    for y in 0..h / 2 {
+        let (top, bottom) = texels.split_at_mut((h - 1 - y) * w);
+        top[y * w..(y + 1) * w].swap_with_slice(&mut bottom[..w]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_formats() {
+        // 8x2 image, the first row with the new run length encoding and the
+        // second row as flat texels.
+        let mut rgbe = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 2 +X 8\n".to_vec();
+        rgbe.extend([2, 2, 0, 8]);
+        rgbe.extend([128 + 8, 128]);
+        rgbe.extend([128 + 8, 64]);
+        rgbe.extend([128 + 8, 0]);
+        rgbe.extend([128 + 8, 129]);
+        for _ in 0..8 {
+            rgbe.extend([0, 0, 128, 130]);
+        }
+        let (texels, size) = read_rgbe(&rgbe).unwrap();
+        assert_eq!(size, (8, 2));
+        assert_abs_diff_eq!(texels[0].r(), 1.0, epsilon = 0.01);
+        assert_abs_diff_eq!(texels[0].g(), 0.5, epsilon = 0.01);
+        assert_abs_diff_eq!(texels[0].b(), 0.0, epsilon = 0.01);
+        assert_abs_diff_eq!(texels[15].b(), 2.0, epsilon = 0.01);
+
+        // 1x2 image, little endian, stored from bottom to top.
+        let mut pfm = b"PF\n1 2\n-1.0\n".to_vec();
+        for value in [1.0_f32, 2.0, 3.0, 4.0, 5.0, 6.0] {
+            pfm.extend(value.to_le_bytes());
+        }
+        let (texels, size) = read_pfm(&pfm).unwrap();
+        assert_eq!(size, (1, 2));
+        assert_ulps_eq!(texels[0].r(), 4.0);
+        assert_ulps_eq!(texels[1].b(), 3.0);
+
+        // Resolutions that overflow are errors, not panics.
+        assert!(read_rgbe(b"#?RADIANCE\n\n-Y 65536 +X 65536\n").is_err());
+        assert!(read_rgbe(b"#?RADIANCE\n\n-Y 60000 +X 60000\n").is_err());
+        assert!(read_rgbe(b"#?RADIANCE\n\n-Y 1 +X 4000000000\n\x01\x02\x03\x80").is_err());
+        assert!(read_pfm(b"PF\n65536 65536\n-1.0\n").is_err());
+    }
+
+    #[test]
+    fn write_formats() {
+        let size = (9, 3);
+        let texels = (0..27)
+            .map(|i| match i % 9 {
+                0..=4 => ColorRgb::new(0.25, 0.5, 1.0),
+                _ => ColorRgb::new(i as f32, 0.1 * i as f32, 1000.0),
+            })
+            .collect::<Vec<_>>();
+
+        let (pfm, pfm_size) = read_pfm(&write_pfm(&texels, size)).unwrap();
+        assert_eq!(pfm_size, size);
+        assert_eq!(pfm, texels);
+
+        let (rgbe, rgbe_size) = read_rgbe(&write_rgbe(&texels, size)).unwrap();
+        assert_eq!(rgbe_size, size);
+        for (a, b) in rgbe.iter().zip(&texels) {
+            // RGBE shares the exponent, so the precision is relative to the
+            // largest component.
+            let epsilon = b.max_element() / 128.0;
+            assert_abs_diff_eq!(a.r(), b.r(), epsilon = epsilon);
+            assert_abs_diff_eq!(a.g(), b.g(), epsilon = epsilon);
+            assert_abs_diff_eq!(a.b(), b.b(), epsilon = epsilon);
+        }
+
+        let exr = write_exr(
+            &texels,
+            size,
+            ColorSpace::Srgb,
+            ExrPixelType::Float,
+            ExrCompression::None,
+        )
+        .unwrap();
+        assert_eq!(exr[..4], [0x76, 0x2f, 0x31, 0x01]);
+        // Channels are stored as B, G and R, so the file ends with red.
+        assert_eq!(exr[exr.len() - 4..], 26.0_f32.to_le_bytes());
+
+        // Empty images are errors, not panics.
+        assert!(write_exr(
+            &[],
+            (0, 4),
+            ColorSpace::Srgb,
+            ExrPixelType::Half,
+            ExrCompression::Zip
+        )
+        .is_err());
+        assert!(zip_predict(&[]).is_empty());
+
+        assert_eq!(f16_bits(1.0), 0x3c00);
+        assert_eq!(f16_bits(-2.0), 0xc000);
+        assert_eq!(f16_bits(0.1), 0x2e66);
+        assert_eq!(f16_bits(65504.0), 0x7bff);
+        assert_eq!(f16_bits(1e6), 0x7c00);
+        assert_eq!(f16_bits(2.0_f32.powi(-24)), 0x0001);
+        assert_eq!(f16_bits(f32::NAN) & 0x7e00, 0x7e00);
+    }
+
+    #[test]
+    fn exr_zip_round_trip() {
+        use std::io::Read;
+
+        let size = (7, 37);
+        let texels = (0..size.0 * size.1)
+            .map(|i| ColorRgb::new(i as f32, 0.5 * (i % 7) as f32, 0.125))
+            .collect::<Vec<_>>();
+        for pixel_type in [ExrPixelType::Half, ExrPixelType::Float] {
+            let exr = write_exr(
+                &texels,
+                size,
+                ColorSpace::Srgb,
+                pixel_type,
+                ExrCompression::Zip,
+            )
+            .unwrap();
+
+            // Skip the magic number, version and header attributes.
+            let mut cursor = &exr[8..];
+            while cursor[0] != 0 {
+                for _ in 0..2 {
+                    let end = cursor.iter().position(|&b| b == 0).unwrap();
+                    cursor = &cursor[end + 1..];
+                }
+                let len = i32::from_le_bytes(cursor[..4].try_into().unwrap()) as usize;
+                cursor = &cursor[4 + len..];
+            }
+            cursor = &cursor[1..];
+
+            // Decode the blocks through the offset table.
+            let block_count = (size.1 as usize + 15) / 16;
+            let sample_size = match pixel_type {
+                ExrPixelType::Half => 2,
+                ExrPixelType::Float => 4,
+            };
+            let mut decoded = [vec![], vec![], vec![]];
+            for block in cursor[..8 * block_count].chunks_exact(8) {
+                let offset = u64::from_le_bytes(block.try_into().unwrap()) as usize;
+                let y = i32::from_le_bytes(exr[offset..offset + 4].try_into().unwrap()) as usize;
+                let len =
+                    i32::from_le_bytes(exr[offset + 4..offset + 8].try_into().unwrap()) as usize;
+                let data = &exr[offset + 8..offset + 8 + len];
+                let lines = 16.min(size.1 as usize - y);
+                let expected_len = lines * 3 * size.0 as usize * sample_size;
+                let data = if len < expected_len {
+                    let mut predicted = vec![];
+                    flate2::read::ZlibDecoder::new(data)
+                        .read_to_end(&mut predicted)
+                        .unwrap();
+                    for i in 1..predicted.len() {
+                        predicted[i] = predicted[i]
+                            .wrapping_add(predicted[i - 1])
+                            .wrapping_sub(128);
+                    }
+                    let (even, odd) = predicted.split_at((predicted.len() + 1) / 2);
+                    let mut data = vec![];
+                    for i in 0..predicted.len() {
+                        data.push(if i % 2 == 0 { even[i / 2] } else { odd[i / 2] });
+                    }
+                    data
+                } else {
+                    data.to_vec()
+                };
+                assert_eq!(data.len(), expected_len);
+
+                // Each scanline stores B, G and R.
+                for scanline in data.chunks_exact(3 * size.0 as usize * sample_size) {
+                    for (channel, samples) in scanline
+                        .chunks_exact(size.0 as usize * sample_size)
+                        .enumerate()
+                    {
+                        decoded[channel].extend(samples.chunks_exact(sample_size).map(|sample| {
+                            match pixel_type {
+                                ExrPixelType::Half => {
+                                    u32::from(u16::from_le_bytes(sample.try_into().unwrap()))
+                                }
+                                ExrPixelType::Float => {
+                                    u32::from_le_bytes(sample.try_into().unwrap())
+                                }
+                            }
+                        }));
+                    }
+                }
+            }
+
+            // The samples are exactly representable as half floats.
+            let bits = |value: f32| match pixel_type {
+                ExrPixelType::Half => u32::from(f16_bits(value)),
+                ExrPixelType::Float => value.to_bits(),
+            };
+            let expected = [
+                texels.iter().map(|t| bits(t.b())).collect::<Vec<_>>(),
+                texels.iter().map(|t| bits(t.g())).collect::<Vec<_>>(),
+                texels.iter().map(|t| bits(t.r())).collect::<Vec<_>>(),
+            ];
+            assert_eq!(decoded, expected);
+        }
+    }
+}
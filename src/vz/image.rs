@@ -26,6 +26,23 @@ impl Rgb {
         image
     }
 
+    // Places the images next to each other, aligned to the top.
+    pub fn hstack(images: &[Self]) -> Self {
+        use imagelib::GenericImage;
+
+        let width = images.iter().map(Self::width).sum();
+        let height = images.iter().map(Self::height).max().unwrap_or(0);
+        let mut buffer = imagelib::RgbImage::new(width, height);
+        let mut x = 0;
+        for image in images {
+            buffer
+                .copy_from(&image.buffer, x, 0)
+                .expect("Failed to copy image into stack");
+            x += image.width();
+        }
+        Self { buffer }
+    }
+
     #[inline]
     pub fn width(&self) -> u32 {
         self.buffer.width()
@@ -100,27 +117,105 @@ impl Rgb {
     }
 }
 
+// Writes display encoded signals in [0, 1] as a PNG, tagged with the cICP
+// chunk of the encoding. High dynamic range encodings are written with 16 bits
+// per channel. Rows are given from top to bottom.
+pub fn write_png(
+    path: &Path,
+    signals: &[ColorRgb],
+    size: (u32, u32),
+    encoding: DisplayEncoding,
+) -> Result<()> {
+    let file = File::create(path).with_context(|| format!("Creating {}", path.display()))?;
+    encode_png(BufWriter::new(file), signals, size, encoding)?;
+    info!("Wrote to {}", path.display());
+    Ok(())
+}
+
+fn encode_png<W: std::io::Write>(
+    writer: W,
+    signals: &[ColorRgb],
+    (w, h): (u32, u32),
+    encoding: DisplayEncoding,
+) -> Result<()> {
+    ensure!(
+        signals.len() as u64 == u64::from(w) * u64::from(h),
+        "Expected {w}x{h} signals, got {} instead",
+        signals.len()
+    );
+    let (depth, data) = if encoding.is_hdr() {
+        let data = signals
+            .iter()
+            .flat_map(|signal| [signal.r(), signal.g(), signal.b()])
+            .flat_map(|value| ((value.clamp(0.0, 1.0) * 65535.0).round() as u16).to_be_bytes())
+            .collect::<Vec<_>>();
+        (png::BitDepth::Sixteen, data)
+    } else {
+        let data = signals
+            .iter()
+            .flat_map(|signal| [signal.r(), signal.g(), signal.b()])
+            .map(|value| (value.clamp(0.0, 1.0) * 255.0).round() as u8)
+            .collect::<Vec<_>>();
+        (png::BitDepth::Eight, data)
+    };
+
+    let mut encoder = png::Encoder::new(writer, w, h);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(depth);
+    // Decoders without cICP support fall back to the gAMA and cHRM chunks, or
+    // to the sRGB chunk. Transfer functions of HDR encodings have no fallback.
+    if !encoding.is_hdr() {
+        let [r, g, b, w] = encoding.color_space().chromaticities();
+        encoder.set_source_gamma(png::ScaledFloat::from_scaled(45455));
+        encoder.set_source_chromaticities(png::SourceChromaticities::new(
+            (w[0], w[1]),
+            (r[0], r[1]),
+            (g[0], g[1]),
+            (b[0], b[1]),
+        ));
+        if encoding == DisplayEncoding::Srgb {
+            encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+        }
+    }
+    let mut writer = encoder.write_header()?;
+    writer.write_chunk(png::chunk::cICP, &encoding.cicp())?;
+    writer.write_image_data(&data)?;
+    writer.finish()?;
+    Ok(())
+}
+
 impl From<imagelib::RgbImage> for Rgb {
     fn from(buffer: imagelib::RgbImage) -> Self {
         Self { buffer }
     }
 }
 
-#[allow(dead_code)]
-fn create_checkerboard_texture() {
-    let w = 32;
-    let h = 32;
-    let mut img = vec![];
-    let b = 0.5;
-    for y in 0..h {
-        for x in 0..w {
-            if (x + y) % 2 == 0 {
-                img.push(ColorRgb::new(b, b, b));
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn png_color_chunks() {
+        for encoding in DisplayEncoding::ALL {
+            let mut bytes = vec![];
+            encode_png(&mut bytes, &[ColorRgb::WHITE; 4], (2, 2), encoding).unwrap();
+            assert!(bytes
+                .windows(8)
+                .any(|w| w[..4] == *b"cICP" && w[4..] == encoding.cicp()));
+
+            let reader = png::Decoder::new(bytes.as_slice()).read_info().unwrap();
+            let info = reader.info();
+            assert_eq!(info.srgb.is_some(), encoding == DisplayEncoding::Srgb);
+            assert_eq!(info.source_gamma.is_some(), !encoding.is_hdr());
+            if let Some(chromaticities) = info.source_chromaticities {
+                let [r, _, _, _] = encoding.color_space().chromaticities();
+                assert_ulps_eq!(chromaticities.red.0.into_value(), r[0]);
             } else {
-                img.push(ColorRgb::new(1.0, 1.0, 1.0));
+                assert!(encoding.is_hdr());
             }
         }
+
+        // Mismatched sizes are errors, not panics.
+        assert!(encode_png(vec![], &[ColorRgb::WHITE; 3], (2, 2), DisplayEncoding::Srgb).is_err());
     }
-    let img = Rgb::from_colors(&img, (w, h));
-    img.save(&PathBuf::from("checkerboard.png")).unwrap();
 }
@@ -6,5 +6,6 @@ pub mod apng;
 pub mod cfg;
 pub mod ffmpeg;
 pub mod font;
+pub mod hdr;
 pub mod image;
 pub mod page;